@@ -1,8 +1,13 @@
-use anyhow::{anyhow, Result};
+use pest::error::{ErrorVariant, InputLocation};
 use pest::Parser;
 use pest_derive::Parser;
+use regex::Regex;
+use serde::{Deserialize, Serialize};
 use serde_json::Value;
+use std::cell::RefCell;
 use std::collections::HashMap;
+use std::fmt;
+use std::ops::Range;
 
 #[derive(Parser)]
 #[grammar = "condition.pest"]
@@ -23,17 +28,33 @@ pub enum ConditionExpr {
         right: Box<ConditionExpr>,
     },
 
+    // Arithmetic
+    Arith {
+        op: ArithOp,
+        left: Box<ConditionExpr>,
+        right: Box<ConditionExpr>,
+    },
+    Neg(Box<ConditionExpr>), // Unary minus on a non-literal operand
+
     // Values
-    Ref(String),    // Field reference (bare identifier)
-    Number(f64),    // Numeric literal
-    String(String), // String literal
-    Boolean(bool),  // Boolean literal
+    Ref(String),              // Field reference (bare identifier)
+    Number(f64),              // Numeric literal
+    String(String),           // String literal
+    Boolean(bool),            // Boolean literal
+    List(Vec<ConditionExpr>), // List literal ['a', 'b']
 
     // Functions
-    Count(Box<ConditionExpr>),                        // count(field)
-    Selected(Box<ConditionExpr>, Box<ConditionExpr>), // selected(field, "value")
-    Any(Vec<ConditionExpr>),                          // any(expr1, expr2, ...)
-    All(Vec<ConditionExpr>),                          // all(expr1, expr2, ...)
+    Count(Box<ConditionExpr>),                          // count(field)
+    Selected(Box<ConditionExpr>, Box<ConditionExpr>),   // selected(field, "value")
+    Any(Vec<ConditionExpr>),                            // any(expr1, expr2, ...)
+    All(Vec<ConditionExpr>),                            // all(expr1, expr2, ...)
+    Contains(Box<ConditionExpr>, Box<ConditionExpr>),   // contains(haystack, needle)
+    StartsWith(Box<ConditionExpr>, Box<ConditionExpr>), // starts_with(text, prefix)
+    EndsWith(Box<ConditionExpr>, Box<ConditionExpr>),   // ends_with(text, suffix)
+    Len(Box<ConditionExpr>),                            // len(text_or_list)
+    Lower(Box<ConditionExpr>),                          // lower(text)
+    Matches(Box<ConditionExpr>, Box<ConditionExpr>),    // matches(text, 'regex')
+    Empty(Box<ConditionExpr>),                          // empty(field)
 }
 
 #[derive(Debug, Clone, Copy, PartialEq)]
@@ -44,34 +65,606 @@ pub enum CompareOp {
     LessEqual,
     Equal,
     NotEqual,
+    In,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ArithOp {
+    Add,
+    Sub,
+    Mul,
+    Div,
+}
+
+impl CompareOp {
+    /// Operator as written in condition text
+    pub fn symbol(self) -> &'static str {
+        match self {
+            CompareOp::Greater => ">",
+            CompareOp::Less => "<",
+            CompareOp::GreaterEqual => ">=",
+            CompareOp::LessEqual => "<=",
+            CompareOp::Equal => "==",
+            CompareOp::NotEqual => "!=",
+            CompareOp::In => "in",
+        }
+    }
+}
+
+impl ArithOp {
+    /// Operator as written in condition text
+    pub fn symbol(self) -> &'static str {
+        match self {
+            ArithOp::Add => "+",
+            ArithOp::Sub => "-",
+            ArithOp::Mul => "*",
+            ArithOp::Div => "/",
+        }
+    }
+}
+
+impl ConditionExpr {
+    /// Direct sub-expressions, in source order
+    pub fn children(&self) -> Vec<&ConditionExpr> {
+        match self {
+            ConditionExpr::Or(items)
+            | ConditionExpr::And(items)
+            | ConditionExpr::List(items)
+            | ConditionExpr::Any(items)
+            | ConditionExpr::All(items) => items.iter().collect(),
+            ConditionExpr::Compare { left, right, .. }
+            | ConditionExpr::Arith { left, right, .. } => {
+                vec![left, right]
+            }
+            ConditionExpr::Selected(a, b)
+            | ConditionExpr::Contains(a, b)
+            | ConditionExpr::StartsWith(a, b)
+            | ConditionExpr::EndsWith(a, b)
+            | ConditionExpr::Matches(a, b) => vec![a, b],
+            ConditionExpr::Not(inner)
+            | ConditionExpr::Neg(inner)
+            | ConditionExpr::Count(inner)
+            | ConditionExpr::Len(inner)
+            | ConditionExpr::Lower(inner)
+            | ConditionExpr::Empty(inner) => vec![inner],
+            ConditionExpr::Ref(_)
+            | ConditionExpr::Number(_)
+            | ConditionExpr::String(_)
+            | ConditionExpr::Boolean(_) => Vec::new(),
+        }
+    }
+
+    /// Whether the expression reads any field from popup state
+    pub fn references_fields(&self) -> bool {
+        matches!(self, ConditionExpr::Ref(_))
+            || self.children().into_iter().any(|c| c.references_fields())
+    }
+
+    /// Whether the expression always produces a boolean value
+    fn is_boolean(&self) -> bool {
+        matches!(
+            self,
+            ConditionExpr::Or(_)
+                | ConditionExpr::And(_)
+                | ConditionExpr::Not(_)
+                | ConditionExpr::Compare { .. }
+                | ConditionExpr::Boolean(_)
+                | ConditionExpr::Selected(..)
+                | ConditionExpr::Any(_)
+                | ConditionExpr::All(_)
+                | ConditionExpr::Contains(..)
+                | ConditionExpr::StartsWith(..)
+                | ConditionExpr::EndsWith(..)
+                | ConditionExpr::Matches(..)
+                | ConditionExpr::Empty(_)
+        )
+    }
+
+    /// Normalize a condition without changing what it evaluates to: folds
+    /// sub-expressions that don't reference fields, flattens nested `&&`/`||`
+    /// and removes double negation
+    pub fn simplify(&self) -> ConditionExpr {
+        simplify_expr(self, true)
+    }
+
+    /// Binding strength when printed (higher binds tighter)
+    fn precedence(&self) -> u8 {
+        match self {
+            ConditionExpr::Or(items) | ConditionExpr::And(items) if items.len() < 2 => ATOM,
+            ConditionExpr::Or(_) => OR,
+            ConditionExpr::And(_) => AND,
+            ConditionExpr::Compare { .. } => COMPARE,
+            ConditionExpr::Arith {
+                op: ArithOp::Add | ArithOp::Sub,
+                ..
+            } => SUM,
+            ConditionExpr::Arith { .. } => PRODUCT,
+            ConditionExpr::Not(_) | ConditionExpr::Neg(_) => UNARY,
+            ConditionExpr::Number(n) if n.is_sign_negative() => UNARY,
+            _ => ATOM,
+        }
+    }
+}
+
+// Printing precedence levels, mirroring the grammar in condition.pest
+const OR: u8 = 1;
+const AND: u8 = 2;
+const COMPARE: u8 = 3;
+const SUM: u8 = 4;
+const PRODUCT: u8 = 5;
+const UNARY: u8 = 6;
+const ATOM: u8 = 7;
+
+/// Canonical condition text: `parse_condition(&expr.to_string())` gives back `expr`
+/// for any tree the parser can produce
+impl fmt::Display for ConditionExpr {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            // Degenerate junctions never come out of the parser; print an equivalent
+            ConditionExpr::Or(items) if items.is_empty() => write!(f, "false"),
+            ConditionExpr::And(items) if items.is_empty() => write!(f, "true"),
+            ConditionExpr::Or(items) if items.len() == 1 => write_call(f, "any", items),
+            ConditionExpr::And(items) if items.len() == 1 => write_call(f, "all", items),
+            ConditionExpr::Or(items) => write_joined(f, items, " || ", AND),
+            ConditionExpr::And(items) => write_joined(f, items, " && ", COMPARE),
+            ConditionExpr::Not(inner) => {
+                write!(f, "!")?;
+                write_operand(f, inner, UNARY)
+            }
+            ConditionExpr::Neg(inner) => {
+                write!(f, "-")?;
+                write_operand(f, inner, UNARY)
+            }
+            ConditionExpr::Compare { op, left, right } => {
+                write_operand(f, left, SUM)?;
+                write!(f, " {} ", op.symbol())?;
+                write_operand(f, right, SUM)
+            }
+            ConditionExpr::Arith { op, left, right } => {
+                // Left-associative: only the right operand needs parens at equal precedence
+                let level = self.precedence();
+                write_operand(f, left, level)?;
+                write!(f, " {} ", op.symbol())?;
+                write_operand(f, right, level + 1)
+            }
+            ConditionExpr::Ref(id) => write!(f, "{}", id),
+            ConditionExpr::Number(n) => write!(f, "{}", n),
+            ConditionExpr::String(s) => {
+                write!(f, "'")?;
+                for c in s.chars() {
+                    if c == '\\' || c == '\'' {
+                        write!(f, "\\")?;
+                    }
+                    write!(f, "{}", c)?;
+                }
+                write!(f, "'")
+            }
+            ConditionExpr::Boolean(b) => write!(f, "{}", b),
+            ConditionExpr::List(items) => {
+                write!(f, "[")?;
+                write_joined(f, items, ", ", 0)?;
+                write!(f, "]")
+            }
+            ConditionExpr::Count(a) => write_call(f, "count", [a.as_ref()]),
+            ConditionExpr::Selected(a, b) => write_call(f, "selected", [a.as_ref(), b]),
+            ConditionExpr::Any(items) => write_call(f, "any", items),
+            ConditionExpr::All(items) => write_call(f, "all", items),
+            ConditionExpr::Contains(a, b) => write_call(f, "contains", [a.as_ref(), b]),
+            ConditionExpr::StartsWith(a, b) => write_call(f, "starts_with", [a.as_ref(), b]),
+            ConditionExpr::EndsWith(a, b) => write_call(f, "ends_with", [a.as_ref(), b]),
+            ConditionExpr::Len(a) => write_call(f, "len", [a.as_ref()]),
+            ConditionExpr::Lower(a) => write_call(f, "lower", [a.as_ref()]),
+            ConditionExpr::Matches(a, b) => write_call(f, "matches", [a.as_ref(), b]),
+            ConditionExpr::Empty(a) => write_call(f, "empty", [a.as_ref()]),
+        }
+    }
+}
+
+/// Write `expr`, parenthesized if it binds looser than `min_level`
+fn write_operand(f: &mut fmt::Formatter<'_>, expr: &ConditionExpr, min_level: u8) -> fmt::Result {
+    if expr.precedence() < min_level {
+        write!(f, "({})", expr)
+    } else {
+        write!(f, "{}", expr)
+    }
+}
+
+fn write_joined<'a>(
+    f: &mut fmt::Formatter<'_>,
+    items: impl IntoIterator<Item = &'a ConditionExpr>,
+    separator: &str,
+    min_level: u8,
+) -> fmt::Result {
+    for (i, item) in items.into_iter().enumerate() {
+        if i > 0 {
+            write!(f, "{}", separator)?;
+        }
+        write_operand(f, item, min_level)?;
+    }
+    Ok(())
+}
+
+fn write_call<'a>(
+    f: &mut fmt::Formatter<'_>,
+    name: &str,
+    args: impl IntoIterator<Item = &'a ConditionExpr>,
+) -> fmt::Result {
+    write!(f, "{}(", name)?;
+    write_joined(f, args, ", ", 0)?;
+    write!(f, ")")
+}
+
+/// Conditions serialize as their canonical text
+impl Serialize for ConditionExpr {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(self)
+    }
+}
+
+impl<'de> Deserialize<'de> for ConditionExpr {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let text = String::deserialize(deserializer)?;
+        parse_condition(&text).map_err(serde::de::Error::custom)
+    }
+}
+
+/// Simplify `expr`; `in_bool` is true where only its truthiness matters
+/// (when clauses, `&&`/`||`/`!` operands), which permits more rewrites
+fn simplify_expr(expr: &ConditionExpr, in_bool: bool) -> ConditionExpr {
+    use ConditionExpr as E;
+
+    let value = |e: &ConditionExpr| Box::new(simplify_expr(e, false));
+
+    let simplified = match expr {
+        E::Or(items) => simplify_junction(items, false, in_bool),
+        E::And(items) => simplify_junction(items, true, in_bool),
+        E::Not(inner) => match simplify_expr(inner, true) {
+            E::Not(x) if in_bool || x.is_boolean() => *x,
+            other => E::Not(Box::new(other)),
+        },
+        E::Compare { op, left, right } => E::Compare {
+            op: *op,
+            left: value(left),
+            right: value(right),
+        },
+        E::Arith { op, left, right } => E::Arith {
+            op: *op,
+            left: value(left),
+            right: value(right),
+        },
+        E::Neg(inner) => E::Neg(value(inner)),
+        E::List(items) => E::List(items.iter().map(|e| simplify_expr(e, false)).collect()),
+        E::Any(items) => E::Any(items.iter().map(|e| simplify_expr(e, true)).collect()),
+        E::All(items) => E::All(items.iter().map(|e| simplify_expr(e, true)).collect()),
+        E::Count(a) => E::Count(value(a)),
+        E::Selected(a, b) => {
+            // The option argument is read as text, where a computed number reads as ""
+            // but a numeric literal reads as its digits, so don't fold one into the other
+            let option = match simplify_expr(b, false) {
+                E::Number(_) if !matches!(**b, E::Number(_)) => b.clone(),
+                other => Box::new(other),
+            };
+            E::Selected(value(a), option)
+        }
+        E::Contains(a, b) => E::Contains(value(a), value(b)),
+        E::StartsWith(a, b) => E::StartsWith(value(a), value(b)),
+        E::EndsWith(a, b) => E::EndsWith(value(a), value(b)),
+        E::Len(a) => E::Len(value(a)),
+        E::Lower(a) => E::Lower(value(a)),
+        E::Matches(a, b) => E::Matches(value(a), value(b)),
+        E::Empty(a) => E::Empty(value(a)),
+        E::Ref(_) | E::Number(_) | E::String(_) | E::Boolean(_) => expr.clone(),
+    };
+
+    fold_constant(simplified, in_bool)
+}
+
+/// Flatten nested junctions of the same kind and drop identity literals
+fn simplify_junction(items: &[ConditionExpr], is_and: bool, in_bool: bool) -> ConditionExpr {
+    let mut flat = Vec::new();
+    for item in items {
+        match simplify_expr(item, true) {
+            // `true` in an And / `false` in an Or has no effect
+            ConditionExpr::Boolean(b) if b == is_and => {}
+            // `false` in an And / `true` in an Or decides the result
+            ConditionExpr::Boolean(b) => return ConditionExpr::Boolean(b),
+            ConditionExpr::And(inner) if is_and => flat.extend(inner),
+            ConditionExpr::Or(inner) if !is_and => flat.extend(inner),
+            other => flat.push(other),
+        }
+    }
+
+    match flat.len() {
+        0 => ConditionExpr::Boolean(is_and),
+        1 if in_bool || flat[0].is_boolean() => flat.remove(0),
+        _ if is_and => ConditionExpr::And(flat),
+        _ => ConditionExpr::Or(flat),
+    }
+}
+
+/// Replace a field-independent expression with the literal it evaluates to
+fn fold_constant(expr: ConditionExpr, in_bool: bool) -> ConditionExpr {
+    if expr.references_fields() {
+        return expr;
+    }
+
+    let state = HashMap::new();
+    if in_bool {
+        return ConditionExpr::Boolean(evaluate_condition(&expr, &state));
+    }
+    match eval_to_value(&expr, &state) {
+        Value::Bool(b) => ConditionExpr::Boolean(b),
+        Value::String(s) => ConditionExpr::String(s),
+        Value::Number(n) => n.as_f64().map(ConditionExpr::Number).unwrap_or(expr),
+        // Null (e.g. division by zero) and arrays have no literal form
+        _ => expr,
+    }
+}
+
+/// Function names accepted by the condition language
+const FUNCTIONS: &[&str] = &[
+    "count",
+    "selected",
+    "any",
+    "all",
+    "contains",
+    "starts_with",
+    "ends_with",
+    "len",
+    "lower",
+    "matches",
+    "empty",
+];
+
+/// Error produced when a condition expression cannot be parsed
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct ConditionError {
+    /// What went wrong, e.g. "Unexpected `=`"
+    pub message: String,
+    /// Byte range of the offending input
+    pub span: Range<usize>,
+    /// Tokens the parser would have accepted at `span.start`
+    pub expected: Vec<String>,
+    /// "Did you mean" hints for common mistakes
+    pub suggestions: Vec<String>,
+}
+
+impl ConditionError {
+    fn new(message: impl Into<String>, span: Range<usize>, input: &str) -> Self {
+        ConditionError {
+            message: message.into(),
+            span,
+            expected: Vec::new(),
+            suggestions: suggest_fixes(input),
+        }
+    }
+
+    fn from_pest(input: &str, err: pest::error::Error<Rule>) -> Self {
+        let span = match err.location {
+            InputLocation::Pos(pos) => pos..pos + token_len(&input[pos..]),
+            InputLocation::Span((start, end)) => start..end,
+        };
+
+        let mut expected = Vec::new();
+        if let ErrorVariant::ParsingError { positives, .. } = &err.variant {
+            for rule in positives {
+                let name = describe_rule(*rule).to_string();
+                if !expected.contains(&name) {
+                    expected.push(name);
+                }
+            }
+        }
+
+        let message = if span.is_empty() {
+            "Unexpected end of condition".to_string()
+        } else {
+            format!("Unexpected `{}`", &input[span.clone()])
+        };
+
+        ConditionError {
+            expected,
+            ..ConditionError::new(message, span, input)
+        }
+    }
+}
+
+impl fmt::Display for ConditionError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} at position {}", self.message, self.span.start)?;
+        if !self.expected.is_empty() {
+            write!(f, "; expected {}", self.expected.join(" or "))?;
+        }
+        for suggestion in &self.suggestions {
+            write!(f, "; {}", suggestion)?;
+        }
+        Ok(())
+    }
+}
+
+impl std::error::Error for ConditionError {}
+
+/// Length in bytes of the token starting at the beginning of `rest`
+/// (a whole identifier/number, or a single character otherwise)
+fn token_len(rest: &str) -> usize {
+    let word: usize = rest
+        .chars()
+        .take_while(|c| c.is_alphanumeric() || *c == '_')
+        .map(char::len_utf8)
+        .sum();
+    if word > 0 {
+        word
+    } else {
+        rest.chars().next().map(char::len_utf8).unwrap_or(0)
+    }
+}
+
+/// User-facing name for a grammar rule in "expected ..." lists
+fn describe_rule(rule: Rule) -> &'static str {
+    match rule {
+        Rule::EOI => "end of condition",
+        Rule::op | Rule::in_op => "comparison operator (==, !=, <, >, <=, >=, in)",
+        Rule::add_op => "`+` or `-`",
+        Rule::mul_op => "`*` or `/`",
+        Rule::string => "string literal",
+        Rule::number => "number",
+        Rule::boolean => "true or false",
+        Rule::r#ref | Rule::ident => "field id",
+        Rule::func | Rule::args => "function call",
+        Rule::list => "list",
+        _ => "expression",
+    }
+}
+
+/// Hints for common mistakes, scanning the input outside of string literals
+fn suggest_fixes(input: &str) -> Vec<String> {
+    let mut suggestions = Vec::new();
+    let mut push = |s: String| {
+        if !suggestions.contains(&s) {
+            suggestions.push(s);
+        }
+    };
+
+    let chars: Vec<char> = input.chars().collect();
+    let mut quote: Option<char> = None;
+    let mut i = 0;
+    while i < chars.len() {
+        let c = chars[i];
+        if let Some(q) = quote {
+            if c == '\\' {
+                i += 1; // Skip escaped character
+            } else if c == q {
+                quote = None;
+            }
+            i += 1;
+            continue;
+        }
+
+        match c {
+            '\'' | '"' => quote = Some(c),
+            '=' => {
+                let prev = i.checked_sub(1).map(|p| chars[p]);
+                let next = chars.get(i + 1).copied();
+                if next == Some('=') {
+                    // "==" or "===": skip the pair, flag the triple
+                    if chars.get(i + 2) == Some(&'=') {
+                        push("use `==` for equality, not `===`".to_string());
+                        i += 2;
+                    }
+                    i += 1;
+                } else if !matches!(prev, Some('=' | '!' | '<' | '>')) {
+                    push("use `==` for equality, not `=`".to_string());
+                }
+            }
+            '@' => {
+                let id: String = chars[i + 1..]
+                    .iter()
+                    .take_while(|c| c.is_alphanumeric() || **c == '_')
+                    .collect();
+                if !id.is_empty() {
+                    push(format!(
+                        "field ids are bare identifiers: use `{}` instead of `@{}`",
+                        id, id
+                    ));
+                }
+            }
+            c if c.is_alphabetic() || c == '_' => {
+                let start = i;
+                while i < chars.len() && (chars[i].is_alphanumeric() || chars[i] == '_') {
+                    i += 1;
+                }
+                let word: String = chars[start..i].iter().collect();
+                let is_call = chars[i..].iter().find(|c| !c.is_whitespace()) == Some(&'(');
+                match word.as_str() {
+                    "and" | "AND" => push("use `&&` instead of `and`".to_string()),
+                    "or" | "OR" => push("use `||` instead of `or`".to_string()),
+                    "not" | "NOT" => push("use `!` instead of `not`".to_string()),
+                    "True" | "TRUE" | "False" | "FALSE" => push(format!(
+                        "boolean literals are lowercase: use `{}`",
+                        word.to_lowercase()
+                    )),
+                    _ if is_call && !FUNCTIONS.contains(&word.as_str()) => {
+                        if let Some(closest) = closest_function(&word) {
+                            push(format!(
+                                "did you mean `{}()` instead of `{}()`?",
+                                closest, word
+                            ));
+                        }
+                    }
+                    _ => {}
+                }
+                continue;
+            }
+            _ => {}
+        }
+        i += 1;
+    }
+
+    if quote.is_some() {
+        push("close the string literal with a matching quote".to_string());
+    }
+
+    suggestions
+}
+
+/// Closest known function name within a small edit distance
+fn closest_function(name: &str) -> Option<&'static str> {
+    FUNCTIONS
+        .iter()
+        .map(|f| (edit_distance(&name.to_lowercase(), f), *f))
+        .filter(|(d, _)| *d <= 2)
+        .min_by_key(|(d, _)| *d)
+        .map(|(_, f)| f)
+}
+
+/// Levenshtein distance between two strings
+fn edit_distance(a: &str, b: &str) -> usize {
+    let b: Vec<char> = b.chars().collect();
+    let mut prev: Vec<usize> = (0..=b.len()).collect();
+    for (i, ca) in a.chars().enumerate() {
+        let mut row = vec![i + 1];
+        for (j, cb) in b.iter().enumerate() {
+            let cost = usize::from(ca != *cb);
+            row.push((prev[j] + cost).min(prev[j + 1] + 1).min(row[j] + 1));
+        }
+        prev = row;
+    }
+    prev[b.len()]
 }
 
 /// Parse a condition expression string into AST
-pub fn parse_condition(input: &str) -> Result<ConditionExpr> {
-    let pairs = ConditionParser::parse(Rule::expr, input)
-        .map_err(|e| anyhow!("Failed to parse condition: {}", e))?;
+pub fn parse_condition(input: &str) -> Result<ConditionExpr, ConditionError> {
+    let pairs = ConditionParser::parse(Rule::condition, input)
+        .map_err(|e| ConditionError::from_pest(input, e))?;
 
     let pair = pairs
         .into_iter()
         .next()
-        .ok_or_else(|| anyhow!("Empty condition expression"))?;
+        .and_then(|condition| condition.into_inner().next())
+        .ok_or_else(|| ConditionError::new("Empty condition expression", 0..0, input))?;
 
-    build_ast(pair)
+    build_ast(pair, input)
 }
 
-fn build_ast(pair: pest::iterators::Pair<Rule>) -> Result<ConditionExpr> {
+fn build_ast(
+    pair: pest::iterators::Pair<Rule>,
+    input: &str,
+) -> Result<ConditionExpr, ConditionError> {
+    let span = pair.as_span().start()..pair.as_span().end();
+    let err = |message: String| ConditionError::new(message, span.clone(), input);
+
     match pair.as_rule() {
         Rule::expr => {
             let inner = pair.into_inner().next().unwrap();
-            build_ast(inner)
+            build_ast(inner, input)
         }
 
         Rule::or => {
             let parts: Vec<_> = pair.into_inner().collect();
             if parts.len() == 1 {
-                build_ast(parts[0].clone())
+                build_ast(parts[0].clone(), input)
             } else {
-                let exprs: Result<Vec<_>> = parts.into_iter().map(build_ast).collect();
+                let exprs: Result<Vec<_>, ConditionError> =
+                    parts.into_iter().map(|p| build_ast(p, input)).collect();
                 Ok(ConditionExpr::Or(exprs?))
             }
         }
@@ -79,16 +672,17 @@ fn build_ast(pair: pest::iterators::Pair<Rule>) -> Result<ConditionExpr> {
         Rule::and => {
             let parts: Vec<_> = pair.into_inner().collect();
             if parts.len() == 1 {
-                build_ast(parts[0].clone())
+                build_ast(parts[0].clone(), input)
             } else {
-                let exprs: Result<Vec<_>> = parts.into_iter().map(build_ast).collect();
+                let exprs: Result<Vec<_>, ConditionError> =
+                    parts.into_iter().map(|p| build_ast(p, input)).collect();
                 Ok(ConditionExpr::And(exprs?))
             }
         }
 
         Rule::comp => {
             let mut parts = pair.into_inner();
-            let left = build_ast(parts.next().unwrap())?;
+            let left = build_ast(parts.next().unwrap(), input)?;
 
             if let Some(op_pair) = parts.next() {
                 let op = match op_pair.as_str() {
@@ -98,9 +692,15 @@ fn build_ast(pair: pest::iterators::Pair<Rule>) -> Result<ConditionExpr> {
                     "<=" => CompareOp::LessEqual,
                     "==" => CompareOp::Equal,
                     "!=" => CompareOp::NotEqual,
-                    _ => return Err(anyhow!("Unknown comparison operator: {}", op_pair.as_str())),
+                    "in" => CompareOp::In,
+                    _ => {
+                        return Err(err(format!(
+                            "Unknown comparison operator: {}",
+                            op_pair.as_str()
+                        )))
+                    }
                 };
-                let right = build_ast(parts.next().unwrap())?;
+                let right = build_ast(parts.next().unwrap(), input)?;
                 Ok(ConditionExpr::Compare {
                     op,
                     left: Box::new(left),
@@ -111,19 +711,59 @@ fn build_ast(pair: pest::iterators::Pair<Rule>) -> Result<ConditionExpr> {
             }
         }
 
+        Rule::sum | Rule::product => {
+            // Left-associative chain: operand (op operand)*
+            let mut parts = pair.into_inner();
+            let mut left = build_ast(parts.next().unwrap(), input)?;
+            while let Some(op_pair) = parts.next() {
+                let op = match op_pair.as_str() {
+                    "+" => ArithOp::Add,
+                    "-" => ArithOp::Sub,
+                    "*" => ArithOp::Mul,
+                    "/" => ArithOp::Div,
+                    _ => {
+                        return Err(err(format!(
+                            "Unknown arithmetic operator: {}",
+                            op_pair.as_str()
+                        )))
+                    }
+                };
+                let right = build_ast(parts.next().unwrap(), input)?;
+                left = ConditionExpr::Arith {
+                    op,
+                    left: Box::new(left),
+                    right: Box::new(right),
+                };
+            }
+            Ok(left)
+        }
+
         Rule::value => {
+            // Parenthesized expression or a single operand
             let inner = pair.into_inner().next().unwrap();
-            match inner.as_rule() {
-                Rule::expr => build_ast(inner), // Parenthesized expression
-                Rule::value => {
-                    // Negation: "!" ~ value
-                    let negated = inner.into_inner().next().unwrap();
-                    Ok(ConditionExpr::Not(Box::new(build_ast(negated)?)))
-                }
-                _ => build_ast(inner),
+            build_ast(inner, input)
+        }
+
+        Rule::not => {
+            let negated = pair.into_inner().next().unwrap();
+            Ok(ConditionExpr::Not(Box::new(build_ast(negated, input)?)))
+        }
+
+        Rule::neg => {
+            let operand = build_ast(pair.into_inner().next().unwrap(), input)?;
+            // Fold the sign into numeric literals so "-5" is just Number(-5)
+            match operand {
+                ConditionExpr::Number(n) => Ok(ConditionExpr::Number(-n)),
+                other => Ok(ConditionExpr::Neg(Box::new(other))),
             }
         }
 
+        Rule::list => {
+            let items: Result<Vec<_>, ConditionError> =
+                pair.into_inner().map(|p| build_ast(p, input)).collect();
+            Ok(ConditionExpr::List(items?))
+        }
+
         Rule::boolean => {
             let val = pair.as_str() == "true";
             Ok(ConditionExpr::Boolean(val))
@@ -140,47 +780,82 @@ fn build_ast(pair: pest::iterators::Pair<Rule>) -> Result<ConditionExpr> {
             let func_name = parts.next().unwrap().as_str();
 
             // Next might be args rule or nothing
-            let args: Result<Vec<_>> = if let Some(args_pair) = parts.next() {
+            let args: Result<Vec<_>, ConditionError> = if let Some(args_pair) = parts.next() {
                 if args_pair.as_rule() == Rule::args {
-                    args_pair.into_inner().map(build_ast).collect()
+                    args_pair
+                        .into_inner()
+                        .map(|p| build_ast(p, input))
+                        .collect()
                 } else {
                     // Single argument without args wrapper
-                    vec![build_ast(args_pair)].into_iter().collect()
+                    vec![build_ast(args_pair, input)].into_iter().collect()
                 }
             } else {
                 Ok(vec![])
             };
             let args = args?;
 
+            // Helpers for fixed-arity functions
+            let unary = |name: &str,
+                         args: Vec<ConditionExpr>|
+             -> Result<Box<ConditionExpr>, ConditionError> {
+                let [arg]: [ConditionExpr; 1] = args
+                    .try_into()
+                    .map_err(|_| err(format!("{}() expects exactly 1 argument", name)))?;
+                Ok(Box::new(arg))
+            };
+            let binary = |name: &str,
+                          args: Vec<ConditionExpr>|
+             -> Result<(Box<ConditionExpr>, Box<ConditionExpr>), ConditionError> {
+                let [first, second]: [ConditionExpr; 2] = args
+                    .try_into()
+                    .map_err(|_| err(format!("{}() expects exactly 2 arguments", name)))?;
+                Ok((Box::new(first), Box::new(second)))
+            };
+
             match func_name {
-                "count" => {
-                    if args.len() != 1 {
-                        return Err(anyhow!("count() expects exactly 1 argument"));
-                    }
-                    Ok(ConditionExpr::Count(Box::new(args[0].clone())))
-                }
+                "count" => Ok(ConditionExpr::Count(unary(func_name, args)?)),
                 "selected" => {
-                    if args.len() != 2 {
-                        return Err(anyhow!("selected() expects exactly 2 arguments"));
-                    }
-                    Ok(ConditionExpr::Selected(
-                        Box::new(args[0].clone()),
-                        Box::new(args[1].clone()),
-                    ))
+                    let (field, value) = binary(func_name, args)?;
+                    Ok(ConditionExpr::Selected(field, value))
                 }
                 "any" => {
                     if args.is_empty() {
-                        return Err(anyhow!("any() expects at least 1 argument"));
+                        return Err(err("any() expects at least 1 argument".to_string()));
                     }
                     Ok(ConditionExpr::Any(args))
                 }
                 "all" => {
                     if args.is_empty() {
-                        return Err(anyhow!("all() expects at least 1 argument"));
+                        return Err(err("all() expects at least 1 argument".to_string()));
                     }
                     Ok(ConditionExpr::All(args))
                 }
-                _ => Err(anyhow!("Unknown function: {}", func_name)),
+                "contains" => {
+                    let (haystack, needle) = binary(func_name, args)?;
+                    Ok(ConditionExpr::Contains(haystack, needle))
+                }
+                "starts_with" => {
+                    let (text, prefix) = binary(func_name, args)?;
+                    Ok(ConditionExpr::StartsWith(text, prefix))
+                }
+                "ends_with" => {
+                    let (text, suffix) = binary(func_name, args)?;
+                    Ok(ConditionExpr::EndsWith(text, suffix))
+                }
+                "len" => Ok(ConditionExpr::Len(unary(func_name, args)?)),
+                "lower" => Ok(ConditionExpr::Lower(unary(func_name, args)?)),
+                "matches" => {
+                    let (text, pattern) = binary(func_name, args)?;
+                    // Reject invalid literal patterns up front rather than at evaluation time
+                    if let ConditionExpr::String(p) = pattern.as_ref() {
+                        Regex::new(p)
+                            .map_err(|e| err(format!("Invalid regex in matches(): {}", e)))?;
+                    }
+                    Ok(ConditionExpr::Matches(text, pattern))
+                }
+                "empty" => Ok(ConditionExpr::Empty(unary(func_name, args)?)),
+                _ => Err(err(format!("Unknown function: {}", func_name))),
             }
         }
 
@@ -190,19 +865,46 @@ fn build_ast(pair: pest::iterators::Pair<Rule>) -> Result<ConditionExpr> {
             let num = pair
                 .as_str()
                 .parse::<f64>()
-                .map_err(|e| anyhow!("Failed to parse number: {}", e))?;
+                .map_err(|e| err(format!("Failed to parse number: {}", e)))?;
             Ok(ConditionExpr::Number(num))
         }
 
         Rule::string => {
-            // Strip quotes from string literal
+            // Strip quotes from string literal and resolve escapes
             let s = pair.as_str();
             let unquoted = &s[1..s.len() - 1]; // Remove first and last char (quotes)
-            Ok(ConditionExpr::String(unquoted.to_string()))
+            Ok(ConditionExpr::String(unescape(unquoted)))
         }
 
-        _ => Err(anyhow!("Unexpected rule: {:?}", pair.as_rule())),
+        _ => Err(err(format!("Unexpected rule: {:?}", pair.as_rule()))),
+    }
+}
+
+/// Resolve backslash escapes in a string literal body.
+///
+/// Supports `\n`, `\t`, `\r`, `\\`, `\'` and `\"`. Any other escape is kept
+/// verbatim (backslash included) so regex patterns like `'v\d+'` work unchanged.
+fn unescape(s: &str) -> String {
+    let mut out = String::with_capacity(s.len());
+    let mut chars = s.chars();
+    while let Some(c) = chars.next() {
+        if c != '\\' {
+            out.push(c);
+            continue;
+        }
+        match chars.next() {
+            Some('n') => out.push('\n'),
+            Some('t') => out.push('\t'),
+            Some('r') => out.push('\r'),
+            Some(c @ ('\\' | '\'' | '"')) => out.push(c),
+            Some(other) => {
+                out.push('\\');
+                out.push(other);
+            }
+            None => out.push('\\'),
+        }
     }
+    out
 }
 
 /// Evaluate a condition expression against popup state
@@ -228,7 +930,7 @@ pub fn evaluate_condition(expr: &ConditionExpr, state: &HashMap<String, Value>)
 
         ConditionExpr::Ref(id) => {
             // Reference to field - check truthiness of value
-            state.get(id).map(is_truthy).unwrap_or(false)
+            lookup(state, id).map(is_truthy).unwrap_or(false)
         }
 
         ConditionExpr::Number(n) => *n != 0.0, // Non-zero is truthy
@@ -259,16 +961,149 @@ pub fn evaluate_condition(expr: &ConditionExpr, state: &HashMap<String, Value>)
         ConditionExpr::Any(exprs) => exprs.iter().any(|e| evaluate_condition(e, state)),
 
         ConditionExpr::All(exprs) => exprs.iter().all(|e| evaluate_condition(e, state)),
+
+        ConditionExpr::Contains(haystack, needle) => contains_value(
+            &eval_to_value(haystack, state),
+            &eval_to_value(needle, state),
+        ),
+
+        ConditionExpr::StartsWith(text, prefix) => match (
+            eval_to_value(text, state).as_str(),
+            eval_to_value(prefix, state).as_str(),
+        ) {
+            (Some(t), Some(p)) => t.starts_with(p),
+            _ => false,
+        },
+
+        ConditionExpr::EndsWith(text, suffix) => match (
+            eval_to_value(text, state).as_str(),
+            eval_to_value(suffix, state).as_str(),
+        ) {
+            (Some(t), Some(s)) => t.ends_with(s),
+            _ => false,
+        },
+
+        ConditionExpr::Matches(text, pattern) => match (
+            eval_to_value(text, state).as_str(),
+            eval_to_value(pattern, state).as_str(),
+        ) {
+            (Some(t), Some(p)) => regex_matches(p, t),
+            _ => false,
+        },
+
+        ConditionExpr::Empty(inner) => is_empty_value(&eval_to_value(inner, state)),
+
+        // Value-producing expressions: truthiness of the computed value
+        ConditionExpr::Arith { .. }
+        | ConditionExpr::Neg(_)
+        | ConditionExpr::List(_)
+        | ConditionExpr::Len(_)
+        | ConditionExpr::Lower(_) => is_truthy(&eval_to_value(expr, state)),
+    }
+}
+
+/// Most `when` clauses kept parsed per thread; a popup has far fewer, so the
+/// cache only fills up across many different definitions
+const PARSE_CACHE_LIMIT: usize = 512;
+
+/// Evaluate a `when` clause, parsing through a per-thread cache. Clauses that
+/// fail to parse count as true (fail-open), matching how the GUI renders them.
+pub(crate) fn evaluate_when(when: &str, state: &HashMap<String, Value>) -> bool {
+    thread_local! {
+        static PARSE_CACHE: RefCell<HashMap<String, Option<ConditionExpr>>> =
+            RefCell::new(HashMap::new());
+    }
+
+    PARSE_CACHE.with(|cache| {
+        let mut cache = cache.borrow_mut();
+        if cache.len() >= PARSE_CACHE_LIMIT && !cache.contains_key(when) {
+            cache.clear();
+        }
+        cache
+            .entry(when.to_string())
+            .or_insert_with(|| parse_condition(when).ok())
+            .as_ref()
+            .map(|ast| evaluate_condition(ast, state))
+            .unwrap_or(true)
+    })
+}
+
+/// Evaluation trace of a condition: the value of every sub-expression
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Trace {
+    /// The sub-expression (serialized as its canonical text)
+    pub expr: ConditionExpr,
+    /// What the sub-expression evaluates to
+    pub value: Value,
+    /// Whether it counts as true when used as a condition
+    pub passed: bool,
+    pub children: Vec<Trace>,
+}
+
+impl Trace {
+    /// The innermost sub-expression responsible for a false result, or None if
+    /// the condition passed. Descends through `&&`/`all()` into the first
+    /// operand that failed; any other node is reported as a whole.
+    pub fn failing(&self) -> Option<&Trace> {
+        if self.passed {
+            return None;
+        }
+        match self.expr {
+            ConditionExpr::And(_) | ConditionExpr::All(_) => {
+                self.children.iter().find_map(Trace::failing).or(Some(self))
+            }
+            _ => Some(self),
+        }
+    }
+}
+
+/// Indented tree, one sub-expression per line with its value
+impl fmt::Display for Trace {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fn write_node(f: &mut fmt::Formatter<'_>, trace: &Trace, depth: usize) -> fmt::Result {
+            let mark = if trace.passed { '✓' } else { '✗' };
+            writeln!(
+                f,
+                "{:indent$}{} {} = {}",
+                "",
+                mark,
+                trace.expr,
+                trace.value,
+                indent = depth * 2
+            )?;
+            for child in &trace.children {
+                write_node(f, child, depth + 1)?;
+            }
+            Ok(())
+        }
+        write_node(f, self, 0)
+    }
+}
+
+/// Evaluate a condition, recording the value of each sub-expression
+pub fn explain_condition(expr: &ConditionExpr, state: &HashMap<String, Value>) -> Trace {
+    Trace {
+        expr: expr.clone(),
+        value: eval_to_value(expr, state),
+        passed: evaluate_condition(expr, state),
+        children: expr
+            .children()
+            .into_iter()
+            .map(|child| explain_condition(child, state))
+            .collect(),
     }
 }
 
 /// Evaluate expression to JSON Value
 fn eval_to_value(expr: &ConditionExpr, state: &HashMap<String, Value>) -> Value {
     match expr {
-        ConditionExpr::Ref(id) => state.get(id).cloned().unwrap_or(Value::Null),
-        ConditionExpr::Number(n) => Value::Number(serde_json::Number::from_f64(*n).unwrap()),
+        ConditionExpr::Ref(id) => lookup(state, id).cloned().unwrap_or(Value::Null),
+        ConditionExpr::Number(n) => number_value(*n),
         ConditionExpr::String(s) => Value::String(s.clone()),
         ConditionExpr::Boolean(b) => Value::Bool(*b),
+        ConditionExpr::List(items) => {
+            Value::Array(items.iter().map(|e| eval_to_value(e, state)).collect())
+        }
         ConditionExpr::Count(field_ref) => {
             if let ConditionExpr::Ref(id) = &**field_ref {
                 Value::Number(count_selections(state, id).into())
@@ -276,7 +1111,50 @@ fn eval_to_value(expr: &ConditionExpr, state: &HashMap<String, Value>) -> Value
                 Value::Number(0.into())
             }
         }
-        _ => Value::Null,
+        ConditionExpr::Arith { op, left, right } => {
+            let (Some(l), Some(r)) = (
+                eval_to_value(left, state).as_f64(),
+                eval_to_value(right, state).as_f64(),
+            ) else {
+                return Value::Null;
+            };
+            let result = match op {
+                ArithOp::Add => l + r,
+                ArithOp::Sub => l - r,
+                ArithOp::Mul => l * r,
+                ArithOp::Div if r == 0.0 => return Value::Null,
+                ArithOp::Div => l / r,
+            };
+            number_value(result)
+        }
+        ConditionExpr::Neg(inner) => eval_to_value(inner, state)
+            .as_f64()
+            .map(|n| number_value(-n))
+            .unwrap_or(Value::Null),
+        ConditionExpr::Len(inner) => match eval_to_value(inner, state) {
+            Value::String(s) => Value::Number(s.chars().count().into()),
+            Value::Array(a) => Value::Number(a.len().into()),
+            Value::Object(o) => Value::Number(o.len().into()),
+            Value::Null => Value::Number(0.into()),
+            _ => Value::Null,
+        },
+        ConditionExpr::Lower(inner) => match eval_to_value(inner, state) {
+            Value::String(s) => Value::String(s.to_lowercase()),
+            _ => Value::Null,
+        },
+        // Logical expressions and predicates produce booleans
+        ConditionExpr::Or(_)
+        | ConditionExpr::And(_)
+        | ConditionExpr::Not(_)
+        | ConditionExpr::Compare { .. }
+        | ConditionExpr::Selected(..)
+        | ConditionExpr::Any(_)
+        | ConditionExpr::All(_)
+        | ConditionExpr::Contains(..)
+        | ConditionExpr::StartsWith(..)
+        | ConditionExpr::EndsWith(..)
+        | ConditionExpr::Matches(..)
+        | ConditionExpr::Empty(_) => Value::Bool(evaluate_condition(expr, state)),
     }
 }
 
@@ -285,17 +1163,30 @@ fn eval_to_string(expr: &ConditionExpr, state: &HashMap<String, Value>) -> Strin
     match expr {
         ConditionExpr::String(s) => s.clone(),
         ConditionExpr::Number(n) => n.to_string(),
-        ConditionExpr::Ref(id) => state
-            .get(id)
+        ConditionExpr::Ref(id) => lookup(state, id)
             .and_then(|v| v.as_str())
             .unwrap_or("")
             .to_string(),
-        _ => String::new(),
+        _ => eval_to_value(expr, state)
+            .as_str()
+            .unwrap_or("")
+            .to_string(),
     }
 }
 
+/// Convert an f64 to a JSON number, using null for NaN/infinity
+fn number_value(n: f64) -> Value {
+    serde_json::Number::from_f64(n)
+        .map(Value::Number)
+        .unwrap_or(Value::Null)
+}
+
 /// Compare two JSON values
 fn compare_values(left: &Value, op: &CompareOp, right: &Value) -> bool {
+    if *op == CompareOp::In {
+        return contains_value(right, left);
+    }
+
     // Try numeric comparison first
     if let (Some(l), Some(r)) = (left.as_f64(), right.as_f64()) {
         return match op {
@@ -305,6 +1196,7 @@ fn compare_values(left: &Value, op: &CompareOp, right: &Value) -> bool {
             CompareOp::LessEqual => l <= r,
             CompareOp::Equal => (l - r).abs() < f64::EPSILON,
             CompareOp::NotEqual => (l - r).abs() >= f64::EPSILON,
+            CompareOp::In => unreachable!(),
         };
     }
 
@@ -317,6 +1209,7 @@ fn compare_values(left: &Value, op: &CompareOp, right: &Value) -> bool {
             CompareOp::Less => l < r,
             CompareOp::GreaterEqual => l >= r,
             CompareOp::LessEqual => l <= r,
+            CompareOp::In => unreachable!(),
         };
     }
 
@@ -328,6 +1221,51 @@ fn compare_values(left: &Value, op: &CompareOp, right: &Value) -> bool {
     }
 }
 
+/// Membership test used by `in` and contains():
+/// - arrays contain an element equal to the needle
+/// - strings contain the needle as a substring
+/// - objects contain the needle as a key
+fn contains_value(haystack: &Value, needle: &Value) -> bool {
+    match haystack {
+        Value::Array(items) => items
+            .iter()
+            .any(|item| compare_values(item, &CompareOp::Equal, needle)),
+        Value::String(s) => needle.as_str().map(|n| s.contains(n)).unwrap_or(false),
+        Value::Object(o) => needle.as_str().map(|n| o.contains_key(n)).unwrap_or(false),
+        _ => false,
+    }
+}
+
+/// Check whether a value is empty (null, "", [] or {}).
+/// Unlike falsiness, `false` and `0` are answers and therefore not empty.
+fn is_empty_value(value: &Value) -> bool {
+    match value {
+        Value::Null => true,
+        Value::String(s) => s.is_empty(),
+        Value::Array(a) => a.is_empty(),
+        Value::Object(o) => o.is_empty(),
+        Value::Bool(_) | Value::Number(_) => false,
+    }
+}
+
+/// Run a regex match, caching compiled patterns since conditions are re-evaluated every frame.
+/// Invalid patterns never match.
+fn regex_matches(pattern: &str, text: &str) -> bool {
+    thread_local! {
+        static REGEX_CACHE: RefCell<HashMap<String, Option<Regex>>> = RefCell::new(HashMap::new());
+    }
+
+    REGEX_CACHE.with(|cache| {
+        cache
+            .borrow_mut()
+            .entry(pattern.to_string())
+            .or_insert_with(|| Regex::new(pattern).ok())
+            .as_ref()
+            .map(|re| re.is_match(text))
+            .unwrap_or(false)
+    })
+}
+
 /// Check if value is truthy
 fn is_truthy(value: &Value) -> bool {
     match value {
@@ -340,10 +1278,24 @@ fn is_truthy(value: &Value) -> bool {
     }
 }
 
-/// Count selections in multiselect or checkbox
+/// Value of a field reference. A dotted reference like `matrix.row_id` reads a
+/// key of an object-valued field, unless a field has that exact id.
+fn lookup<'a>(state: &'a HashMap<String, Value>, id: &str) -> Option<&'a Value> {
+    if let Some(value) = state.get(id) {
+        return Some(value);
+    }
+    let mut parts = id.split('.');
+    let mut value = state.get(parts.next()?)?;
+    for key in parts {
+        value = value.get(key)?;
+    }
+    Some(value)
+}
+
+/// Count selections in multiselect or checkbox, rows in a list, or answered
+/// rows of a matrix
 fn count_selections(state: &HashMap<String, Value>, id: &str) -> i64 {
-    state
-        .get(id)
+    lookup(state, id)
         .map(|v| {
             match v {
                 Value::Bool(b) => {
@@ -356,10 +1308,12 @@ fn count_selections(state: &HashMap<String, Value>, id: &str) -> i64 {
                 Value::Array(arr) => arr
                     .iter()
                     .filter(|v| {
-                        // Count booleans (true), non-empty strings, and positive numbers
+                        // Count booleans (true), non-empty strings, positive numbers,
+                        // and list rows
                         v.as_bool().unwrap_or(false)
                             || v.as_str().map(|s| !s.is_empty()).unwrap_or(false)
                             || v.as_i64().map(|n| n > 0).unwrap_or(false)
+                            || v.is_object()
                     })
                     .count() as i64,
                 Value::Number(n) => {
@@ -369,6 +1323,7 @@ fn count_selections(state: &HashMap<String, Value>, id: &str) -> i64 {
                         0
                     }
                 }
+                Value::Object(rows) => rows.values().filter(|v| !v.is_null()).count() as i64,
                 _ => 0,
             }
         })
@@ -377,8 +1332,7 @@ fn count_selections(state: &HashMap<String, Value>, id: &str) -> i64 {
 
 /// Check if specific value is selected
 fn is_selected(state: &HashMap<String, Value>, id: &str, value: &str) -> bool {
-    state
-        .get(id)
+    lookup(state, id)
         .map(|v| {
             match v {
                 Value::Bool(b) => *b && id == value, // Checkbox: match if checked and label matches
@@ -397,6 +1351,7 @@ fn is_selected(state: &HashMap<String, Value>, id: &str, value: &str) -> bool {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use serde_json::json;
 
     #[test]
     fn test_parse_simple_ref() {
@@ -637,4 +1592,318 @@ mod tests {
             &state
         ));
     }
+
+    fn eval(condition: &str, state: &HashMap<String, Value>) -> bool {
+        evaluate_condition(&parse_condition(condition).unwrap(), state)
+    }
+
+    #[test]
+    fn test_arithmetic_precedence() {
+        let ast = parse_condition("a + b * 2").unwrap();
+        match ast {
+            ConditionExpr::Arith {
+                op: ArithOp::Add,
+                right,
+                ..
+            } => assert!(matches!(
+                *right,
+                ConditionExpr::Arith {
+                    op: ArithOp::Mul,
+                    ..
+                }
+            )),
+            _ => panic!("Expected addition at the root"),
+        }
+
+        // Subtraction is left-associative: (10 - 4) - 3
+        let state = HashMap::new();
+        assert!(eval("10 - 4 - 3 == 3", &state));
+        assert!(eval("(10 - 4) * 2 == 12", &state));
+        assert!(eval("7 / 2 == 3.5", &state));
+    }
+
+    #[test]
+    fn test_arithmetic_on_fields() {
+        let mut state = HashMap::new();
+        state.insert("cpu".to_string(), json!(60));
+        state.insert("mem".to_string(), json!(30));
+        state.insert("name".to_string(), json!("web"));
+
+        assert!(eval("cpu + mem > 80", &state));
+        assert!(!eval("cpu - mem > 80", &state));
+        assert!(eval("cpu * 2 >= 120", &state));
+
+        // Non-numeric operands and division by zero produce null
+        assert!(!eval("name + 1 > 0", &state));
+        assert!(!eval("cpu / 0 > 0", &state));
+        assert!(eval("cpu / 0 == missing", &state));
+    }
+
+    #[test]
+    fn test_unary_minus() {
+        assert_eq!(parse_condition("-5").unwrap(), ConditionExpr::Number(-5.0));
+        assert_eq!(
+            parse_condition("-offset").unwrap(),
+            ConditionExpr::Neg(Box::new(ConditionExpr::Ref("offset".to_string())))
+        );
+
+        let mut state = HashMap::new();
+        state.insert("offset".to_string(), json!(3));
+        assert!(eval("-offset == -3", &state));
+        assert!(eval("offset > -1", &state));
+        assert!(eval("2 - -1 == 3", &state));
+    }
+
+    #[test]
+    fn test_in_operator_with_list() {
+        let mut state = HashMap::new();
+        state.insert("env".to_string(), json!("Prod"));
+        state.insert("replicas".to_string(), json!(3));
+
+        assert!(eval("env in ['Prod', 'Staging']", &state));
+        assert!(!eval("env in ['Dev']", &state));
+        assert!(!eval("env in []", &state));
+        assert!(eval("replicas in [1, 3, 5]", &state));
+        assert!(eval("!(env in ['Dev', 'Test'])", &state));
+    }
+
+    #[test]
+    fn test_in_operator_with_fields() {
+        let mut state = HashMap::new();
+        state.insert("features".to_string(), json!(["Auth", "Cache"]));
+        state.insert("branch".to_string(), json!("release/1.2"));
+
+        // Membership in a multiselect value
+        assert!(eval("'Auth' in features", &state));
+        assert!(!eval("'Billing' in features", &state));
+        // Substring of a text value
+        assert!(eval("'release' in branch", &state));
+    }
+
+    #[test]
+    fn test_in_keyword_is_not_an_identifier_prefix() {
+        // "index" must parse as a reference, not the "in" operator
+        let mut state = HashMap::new();
+        state.insert("index".to_string(), json!(2));
+        assert!(eval("index == 2", &state));
+    }
+
+    #[test]
+    fn test_boolean_keyword_is_not_an_identifier_prefix() {
+        assert_eq!(
+            parse_condition("trueish").unwrap(),
+            ConditionExpr::Ref("trueish".to_string())
+        );
+    }
+
+    #[test]
+    fn test_dotted_reference() {
+        let mut state = HashMap::new();
+        state.insert("survey".to_string(), json!({"pace": "Agree", "scope": null}));
+        state.insert("a.b".to_string(), json!(true));
+
+        let eval = |cond: &str| evaluate_condition(&parse_condition(cond).unwrap(), &state);
+        assert!(eval("survey.pace == 'Agree'"));
+        assert!(!eval("survey.scope"));
+        assert!(eval("count(survey) == 1"));
+        assert!(!eval("survey.missing == 'Agree'"));
+        assert!(!eval("pace.survey"));
+        // An id containing a dot wins over the object lookup
+        assert!(eval("a.b"));
+        assert!(parse_condition("survey.").is_err());
+    }
+
+    #[test]
+    fn test_string_functions() {
+        let mut state = HashMap::new();
+        state.insert("branch".to_string(), json!("feature/Login"));
+        state.insert("tags".to_string(), json!(["ui", "auth"]));
+
+        assert!(eval("contains(branch, 'Log')", &state));
+        assert!(eval("contains(tags, 'auth')", &state));
+        assert!(!eval("contains(tags, 'db')", &state));
+        assert!(eval("starts_with(branch, 'feature/')", &state));
+        assert!(!eval("starts_with(branch, 'fix/')", &state));
+        assert!(eval("ends_with(branch, 'Login')", &state));
+        assert!(eval("len(branch) == 13", &state));
+        assert!(eval("len(tags) == 2", &state));
+        assert!(eval("len(missing) == 0", &state));
+        assert!(eval("lower(branch) == 'feature/login'", &state));
+        assert!(eval("starts_with(lower(branch), 'feature/l')", &state));
+    }
+
+    #[test]
+    fn test_matches_regex() {
+        let mut state = HashMap::new();
+        state.insert("version".to_string(), json!("v1.24"));
+
+        assert!(eval("matches(version, '^v\\d+\\.\\d+$')", &state));
+        assert!(eval(r"matches(version, '^v\d+')", &state));
+        assert!(!eval("matches(version, '^\\d')", &state));
+        assert!(!eval("matches(missing, '.*')", &state));
+    }
+
+    #[test]
+    fn test_matches_rejects_invalid_literal_regex() {
+        let err = parse_condition("matches(version, '(')").unwrap_err();
+        assert!(err.to_string().contains("Invalid regex"));
+    }
+
+    #[test]
+    fn test_empty_function() {
+        let mut state = HashMap::new();
+        state.insert("name".to_string(), json!(""));
+        state.insert("features".to_string(), json!([]));
+        state.insert("enabled".to_string(), json!(false));
+        state.insert("count".to_string(), json!(0));
+        state.insert("notes".to_string(), json!("hi"));
+
+        assert!(eval("empty(name)", &state));
+        assert!(eval("empty(features)", &state));
+        assert!(eval("empty(missing)", &state));
+        assert!(!eval("empty(notes)", &state));
+        // false and 0 are answers, not empty values
+        assert!(!eval("empty(enabled)", &state));
+        assert!(!eval("empty(count)", &state));
+    }
+
+    #[test]
+    fn test_function_arity_errors() {
+        assert!(parse_condition("len(a, b)").is_err());
+        assert!(parse_condition("contains(a)").is_err());
+        assert!(parse_condition("empty()").is_err());
+    }
+
+    #[test]
+    fn test_string_escapes() {
+        assert_eq!(
+            parse_condition(r#"'it\'s'"#).unwrap(),
+            ConditionExpr::String("it's".to_string())
+        );
+        assert_eq!(
+            parse_condition(r#""say \"hi\"""#).unwrap(),
+            ConditionExpr::String("say \"hi\"".to_string())
+        );
+        assert_eq!(
+            parse_condition(r"'a\\b\nc'").unwrap(),
+            ConditionExpr::String("a\\b\nc".to_string())
+        );
+
+        let mut state = HashMap::new();
+        state.insert("owner".to_string(), json!("O'Brien"));
+        assert!(eval(r"owner == 'O\'Brien'", &state));
+    }
+
+    #[test]
+    fn test_boolean_subexpressions_compare_as_values() {
+        let mut state = HashMap::new();
+        state.insert("cpu".to_string(), json!(90));
+        assert!(eval("(cpu > 80) == true", &state));
+        assert!(eval("contains('abc', 'b') != false", &state));
+    }
+
+    #[test]
+    fn test_parse_error_span_and_expected() {
+        let err = parse_condition("count(tags) >").unwrap_err();
+        assert_eq!(err.message, "Unexpected end of condition");
+        assert_eq!(err.span, 13..13);
+        assert!(err.expected.contains(&"expression".to_string()));
+
+        let err = parse_condition("a == 1 )").unwrap_err();
+        assert_eq!(err.message, "Unexpected `)`");
+        assert_eq!(err.span, 7..8);
+        assert!(err.to_string().contains("at position 7"));
+    }
+
+    #[test]
+    fn test_parse_error_rejects_trailing_input() {
+        // Previously the parser stopped at the first complete expression
+        assert!(parse_condition("a b").is_err());
+        assert!(parse_condition("count(x) > 1 2").is_err());
+    }
+
+    #[test]
+    fn test_parse_error_suggestions() {
+        let hint = |input: &str| parse_condition(input).unwrap_err().suggestions;
+
+        assert_eq!(hint("env = 'Prod'"), vec!["use `==` for equality, not `=`"]);
+        assert_eq!(hint("a === 1"), vec!["use `==` for equality, not `===`"]);
+        assert!(hint("a and b")[0].contains("`&&`"));
+        assert!(hint("a or b")[0].contains("`||`"));
+        assert!(hint("@enabled && x")[0].contains("use `enabled` instead of `@enabled`"));
+        assert_eq!(
+            hint("cont(tags) > 1"),
+            vec!["did you mean `count()` instead of `cont()`?"]
+        );
+        assert!(hint("name == 'Bob")[0].contains("close the string"));
+        // Operators inside string literals are not flagged
+        assert_eq!(
+            hint("name == 'a = b' and x"),
+            vec!["use `&&` instead of `and`"]
+        );
+    }
+
+    #[test]
+    fn test_semantic_errors_have_spans() {
+        let err = parse_condition("a && len(a, b)").unwrap_err();
+        assert_eq!(err.span, 5..14);
+        assert_eq!(err.message, "len() expects exactly 1 argument");
+
+        let err = parse_condition("x || bogus(a)").unwrap_err();
+        assert_eq!(err.message, "Unknown function: bogus");
+        assert_eq!(err.span, 5..13);
+    }
+
+    #[test]
+    fn test_explain_condition_values() {
+        let mut state = HashMap::new();
+        state.insert("cpu".to_string(), json!(50));
+        state.insert("tags".to_string(), json!(["a", "b"]));
+
+        let expr = parse_condition("cpu + 10 > 80").unwrap();
+        let trace = explain_condition(&expr, &state);
+        assert!(!trace.passed);
+        assert_eq!(trace.value, json!(false));
+        assert_eq!(trace.children[0].value, json!(60.0));
+        assert_eq!(trace.children[0].children[0].value, json!(50));
+        assert_eq!(trace.children[1].value, json!(80.0));
+
+        let trace = explain_condition(&parse_condition("count(tags)").unwrap(), &state);
+        assert_eq!(trace.value, json!(2));
+        assert!(trace.passed);
+    }
+
+    #[test]
+    fn test_explain_condition_failing_subexpression() {
+        let mut state = HashMap::new();
+        state.insert("enabled".to_string(), json!(true));
+        state.insert("mode".to_string(), json!("Basic"));
+
+        let expr = parse_condition("enabled && (mode == 'Advanced' && x > 1)").unwrap();
+        let trace = explain_condition(&expr, &state);
+        let failing = trace.failing().unwrap();
+        assert_eq!(failing.expr.to_string(), "mode == 'Advanced'");
+        assert_eq!(failing.children[0].value, json!("Basic"));
+
+        // Or reports the whole disjunction, since every branch failed
+        let expr = parse_condition("enabled && (mode == 'A' || mode == 'B')").unwrap();
+        let trace = explain_condition(&expr, &state);
+        assert_eq!(
+            trace.failing().unwrap().expr.to_string(),
+            "mode == 'A' || mode == 'B'"
+        );
+
+        let expr = parse_condition("enabled").unwrap();
+        assert!(explain_condition(&expr, &state).failing().is_none());
+    }
+
+    #[test]
+    fn test_trace_display() {
+        let state = HashMap::from([("a".to_string(), json!(1))]);
+        let trace = explain_condition(&parse_condition("a > 2").unwrap(), &state);
+        assert_eq!(
+            trace.to_string(),
+            "✗ a > 2 = false\n  ✓ a = 1\n  ✓ 2 = 2.0\n"
+        );
+    }
 }
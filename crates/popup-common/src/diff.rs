@@ -0,0 +1,254 @@
+//! Unified diff parsing and per-hunk review decisions for the `diff` element
+
+use schemars::JsonSchema;
+use serde::Serialize;
+use serde_json::{json, Map, Value};
+
+/// One file's section of a unified diff
+#[derive(Debug, Clone, PartialEq)]
+pub struct FileDiff {
+    /// New path (old path for deletions), without the `a/`/`b/` prefix
+    pub path: String,
+    pub hunks: Vec<Hunk>,
+}
+
+/// A `@@ -a,b +c,d @@` block
+#[derive(Debug, Clone, PartialEq)]
+pub struct Hunk {
+    pub header: String,
+    pub lines: Vec<DiffLine>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct DiffLine {
+    pub kind: LineKind,
+    /// Line number in the old file (None for additions)
+    pub old_line: Option<u32>,
+    /// Line number in the new file (None for removals)
+    pub new_line: Option<u32>,
+    pub text: String,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LineKind {
+    Context,
+    Added,
+    Removed,
+}
+
+/// The user's verdict on a hunk
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, JsonSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum HunkDecision {
+    Accept,
+    Reject,
+}
+
+/// Review state for one hunk, in diff order
+#[derive(Debug, Clone, PartialEq, Serialize, JsonSchema)]
+pub struct HunkReview {
+    #[serde(skip)]
+    pub file: String,
+    pub hunk: usize,
+    /// None until the user accepts or rejects
+    pub decision: Option<HunkDecision>,
+    #[serde(skip_serializing_if = "String::is_empty")]
+    pub comment: String,
+}
+
+/// Parse unified diff text (as produced by `git diff` or `diff -u`).
+/// Lines outside any file or hunk (e.g. `index` lines) are ignored.
+pub fn parse_unified_diff(text: &str) -> Vec<FileDiff> {
+    let mut files: Vec<FileDiff> = Vec::new();
+    let mut old_path: Option<String> = None;
+    let (mut old_line, mut new_line) = (0, 0);
+
+    for line in text.lines() {
+        if let Some(path) = line.strip_prefix("--- ") {
+            old_path = Some(strip_path_prefix(path, "a/"));
+            continue;
+        }
+        if let Some(path) = line.strip_prefix("+++ ") {
+            let new_path = strip_path_prefix(path, "b/");
+            let path = match old_path.take() {
+                Some(old) if new_path == "/dev/null" => old,
+                _ => new_path,
+            };
+            files.push(FileDiff {
+                path,
+                hunks: Vec::new(),
+            });
+            continue;
+        }
+        if line.starts_with("@@") {
+            let Some((old_start, new_start)) = parse_hunk_header(line) else {
+                continue;
+            };
+            (old_line, new_line) = (old_start, new_start);
+            // Hunks without file headers still get a home
+            if files.is_empty() {
+                files.push(FileDiff {
+                    path: String::new(),
+                    hunks: Vec::new(),
+                });
+            }
+            if let Some(file) = files.last_mut() {
+                file.hunks.push(Hunk {
+                    header: line.to_string(),
+                    lines: Vec::new(),
+                });
+            }
+            continue;
+        }
+
+        let Some(hunk) = files.last_mut().and_then(|f| f.hunks.last_mut()) else {
+            continue;
+        };
+        let (kind, text) = match line.chars().next() {
+            Some('+') => (LineKind::Added, &line[1..]),
+            Some('-') => (LineKind::Removed, &line[1..]),
+            Some(' ') => (LineKind::Context, &line[1..]),
+            // Some tools drop the space on empty context lines
+            None => (LineKind::Context, ""),
+            // "\ No newline at end of file" and anything unexpected
+            _ => continue,
+        };
+        let (old, new) = match kind {
+            LineKind::Context => (Some(old_line), Some(new_line)),
+            LineKind::Added => (None, Some(new_line)),
+            LineKind::Removed => (Some(old_line), None),
+        };
+        old_line += u32::from(old.is_some());
+        new_line += u32::from(new.is_some());
+        hunk.lines.push(DiffLine {
+            kind,
+            old_line: old,
+            new_line: new,
+            text: text.to_string(),
+        });
+    }
+
+    files
+}
+
+/// Starting old/new line numbers from `@@ -a,b +c,d @@`
+fn parse_hunk_header(line: &str) -> Option<(u32, u32)> {
+    let mut parts = line.split_whitespace().skip(1);
+    let start = |range: &str| range.split(',').next()?.parse::<u32>().ok();
+    let old = start(parts.next()?.strip_prefix('-')?)?;
+    let new = start(parts.next()?.strip_prefix('+')?)?;
+    Some((old, new))
+}
+
+/// Drop the `a/`/`b/` prefix and any trailing timestamp (`diff -u` style)
+fn strip_path_prefix(path: &str, prefix: &str) -> String {
+    let path = path.split('\t').next().unwrap_or(path).trim_end();
+    path.strip_prefix(prefix).unwrap_or(path).to_string()
+}
+
+/// Fresh, undecided reviews for every hunk of a diff
+pub fn initial_reviews(files: &[FileDiff]) -> Vec<HunkReview> {
+    files
+        .iter()
+        .flat_map(|file| {
+            (0..file.hunks.len()).map(|hunk| HunkReview {
+                file: file.path.clone(),
+                hunk,
+                decision: None,
+                comment: String::new(),
+            })
+        })
+        .collect()
+}
+
+/// Result JSON: `{"path": [{"hunk": 0, "decision": "accept", "comment": "..."}]}`
+pub fn reviews_to_json(reviews: &[HunkReview]) -> Value {
+    let mut files = Map::new();
+    for review in reviews {
+        let entry = files
+            .entry(review.file.clone())
+            .or_insert_with(|| Value::Array(Vec::new()));
+        if let Value::Array(hunks) = entry {
+            hunks.push(json!(review));
+        }
+    }
+    Value::Object(files)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SAMPLE: &str = "\
+diff --git a/src/lib.rs b/src/lib.rs
+index 3b18e51..a9c2f4d 100644
+--- a/src/lib.rs
++++ b/src/lib.rs
+@@ -1,3 +1,4 @@
+ use std::fmt;
++use std::io;
+
+ fn main() {
+@@ -10,2 +11,2 @@ fn helper() {
+-    old();
++    new();
+diff --git a/README.md b/README.md
+deleted file mode 100644
+--- a/README.md
++++ /dev/null
+@@ -1 +0,0 @@
+-# Title
+\\ No newline at end of file
+";
+
+    #[test]
+    fn test_parse_files_and_hunks() {
+        let files = parse_unified_diff(SAMPLE);
+        assert_eq!(files.len(), 2);
+        assert_eq!(files[0].path, "src/lib.rs");
+        assert_eq!(files[0].hunks.len(), 2);
+        assert_eq!(files[1].path, "README.md");
+        assert_eq!(files[1].hunks[0].lines.len(), 1);
+
+        let first = &files[0].hunks[0];
+        assert_eq!(first.header, "@@ -1,3 +1,4 @@");
+        let numbers: Vec<_> = first
+            .lines
+            .iter()
+            .map(|l| (l.kind, l.old_line, l.new_line))
+            .collect();
+        assert_eq!(
+            numbers,
+            vec![
+                (LineKind::Context, Some(1), Some(1)),
+                (LineKind::Added, None, Some(2)),
+                (LineKind::Context, Some(2), Some(3)),
+                (LineKind::Context, Some(3), Some(4)),
+            ]
+        );
+
+        let second = &files[0].hunks[1];
+        assert_eq!(second.lines[0].old_line, Some(10));
+        assert_eq!(second.lines[1].new_line, Some(11));
+    }
+
+    #[test]
+    fn test_reviews_keyed_by_file() {
+        let mut reviews = initial_reviews(&parse_unified_diff(SAMPLE));
+        assert_eq!(reviews.len(), 3);
+        reviews[0].decision = Some(HunkDecision::Accept);
+        reviews[1].decision = Some(HunkDecision::Reject);
+        reviews[1].comment = "keep old()".to_string();
+
+        assert_eq!(
+            reviews_to_json(&reviews),
+            json!({
+                "src/lib.rs": [
+                    {"hunk": 0, "decision": "accept"},
+                    {"hunk": 1, "decision": "reject", "comment": "keep old()"}
+                ],
+                "README.md": [{"hunk": 0, "decision": null}]
+            })
+        );
+    }
+}
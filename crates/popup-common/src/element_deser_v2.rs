@@ -1,4 +1,4 @@
-use crate::{Element, OptionValue};
+use crate::{check_image_source, table, validation, Element, Layout, MatrixRow, OptionValue, PathMode, RatingStyle, Span, TableSelection, TagPattern};
 use serde::de::{self, MapAccess, Visitor};
 use serde::{Deserialize, Deserializer, Serialize, Serializer};
 use serde::ser::SerializeMap;
@@ -51,12 +51,42 @@ impl From<PolyChildren> for Vec<Element> {
             PolyChildren::String(s) => vec![Element::Text {
                 text: s,
                 id: None,
+                span: None,
                 when: None,
             }],
         }
     }
 }
 
+// --- Matrix Rows ---
+
+/// Matrix rows from an array of statements (id derived from the text) or
+/// `{"id": ..., "label": ...}` objects
+fn parse_matrix_rows(value: Value) -> Result<Vec<MatrixRow>, String> {
+    let Value::Array(items) = value else {
+        return Err("matrix rows must be an array".to_string());
+    };
+    let mut rows: Vec<MatrixRow> = Vec::new();
+    for item in items {
+        let row = match item {
+            Value::String(label) => MatrixRow { id: label_to_snake_case(&label), label },
+            Value::Object(mut obj) => {
+                let label = obj.remove("label").and_then(|v| v.as_str().map(|s| s.to_string()))
+                    .ok_or("matrix row objects need a label")?;
+                let id = obj.remove("id").and_then(|v| v.as_str().map(|s| s.to_string()))
+                    .unwrap_or_else(|| label_to_snake_case(&label));
+                MatrixRow { id, label }
+            }
+            _ => return Err("matrix rows must be strings or {id, label} objects".to_string()),
+        };
+        if rows.iter().any(|r| r.id == row.id) {
+            return Err(format!("matrix has two rows with id `{}`", row.id));
+        }
+        rows.push(row);
+    }
+    Ok(rows)
+}
+
 // --- ID Generation Logic ---
 
 fn label_to_snake_case(label: &str) -> String {
@@ -115,19 +145,25 @@ impl<'de> Visitor<'de> for ElementVisitor {
             obj.insert(key, value);
         }
 
+        // Layout hint shared by every element type
+        let span = obj.remove("span")
+            .map(serde_json::from_value::<Span>)
+            .transpose()
+            .map_err(de::Error::custom)?;
+
         // 2. Identify the Variant
         if let Some(text_val) = obj.remove("text") {
             let text = text_val.as_str().ok_or_else(|| de::Error::custom("text must be string"))?.to_string();
             let id = obj.remove("id").and_then(|v| v.as_str().map(|s| s.to_string()));
             let when = obj.remove("when").and_then(|v| v.as_str().map(|s| s.to_string()));
-            return Ok(Element::Text { text, id, when });
+            return Ok(Element::Text { text, id, span, when });
         }
 
         if let Some(md_val) = obj.remove("markdown") {
             let markdown = md_val.as_str().ok_or_else(|| de::Error::custom("markdown must be string"))?.to_string();
             let id = obj.remove("id").and_then(|v| v.as_str().map(|s| s.to_string()));
             let when = obj.remove("when").and_then(|v| v.as_str().map(|s| s.to_string()));
-            return Ok(Element::Markdown { markdown, id, when });
+            return Ok(Element::Markdown { markdown, id, span, when });
         }
 
         if let Some(lbl_val) = obj.remove("slider") {
@@ -140,7 +176,33 @@ impl<'de> Visitor<'de> for ElementVisitor {
             let default = obj.remove("default").and_then(|v| v.as_f64().map(|f| f as f32));
             let when = obj.remove("when").and_then(|v| v.as_str().map(|s| s.to_string()));
 
-            return Ok(Element::Slider { slider, id, min, max, default, when });
+            return Ok(Element::Slider { slider, id, min, max, default, span, when });
+        }
+
+        if let Some(lbl_val) = obj.remove("range") {
+            let range = lbl_val.as_str().ok_or_else(|| de::Error::custom("range must be string"))?.to_string();
+            let id = obj.remove("id").and_then(|v| v.as_str().map(|s| s.to_string()))
+                .unwrap_or_else(|| label_to_snake_case(&range));
+            let min = obj.remove("min").and_then(|v| v.as_f64()).ok_or_else(|| de::Error::custom("missing min"))? as f32;
+            let max = obj.remove("max").and_then(|v| v.as_f64()).ok_or_else(|| de::Error::custom("missing max"))? as f32;
+            let step = obj.remove("step").and_then(|v| v.as_f64()).map(|f| f as f32).unwrap_or(1.0);
+            let low = obj.remove("low").and_then(|v| v.as_f64().map(|f| f as f32));
+            let high = obj.remove("high").and_then(|v| v.as_f64().map(|f| f as f32));
+            if min >= max {
+                return Err(de::Error::custom(format!("range '{}' needs min below max", range)));
+            }
+            if step <= 0.0 {
+                return Err(de::Error::custom(format!("range '{}' needs a positive step", range)));
+            }
+            let (lo, hi) = (low.unwrap_or(min), high.unwrap_or(max));
+            if lo > hi || lo < min || hi > max {
+                return Err(de::Error::custom(format!(
+                    "range '{}' needs min <= low <= high <= max, got low {} and high {}", range, lo, hi
+                )));
+            }
+            let when = obj.remove("when").and_then(|v| v.as_str().map(|s| s.to_string()));
+
+            return Ok(Element::Range { range, id, min, max, step, low, high, span, when });
         }
 
         if let Some(lbl_val) = obj.remove("check") {
@@ -157,7 +219,7 @@ impl<'de> Visitor<'de> for ElementVisitor {
                 Vec::new()
             };
 
-            return Ok(Element::Check { check, id, default, reveals, when });
+            return Ok(Element::Check { check, id, default, reveals, span, when });
         }
 
         if let Some(lbl_val) = obj.remove("input") {
@@ -166,9 +228,19 @@ impl<'de> Visitor<'de> for ElementVisitor {
                 .unwrap_or_else(|| label_to_snake_case(&input));
             let placeholder = obj.remove("placeholder").and_then(|v| v.as_str().map(|s| s.to_string()));
             let rows = obj.remove("rows").and_then(|v| v.as_u64().map(|u| u as u32));
+            // `value` is accepted as another name for the prefill
+            let default = match (obj.remove("default"), obj.remove("value")) {
+                (Some(_), Some(_)) => {
+                    return Err(de::Error::custom(format!("input '{}' has both default and value; use one", input)));
+                }
+                (Some(v), None) | (None, Some(v)) => Some(
+                    v.as_str().ok_or_else(|| de::Error::custom("input default must be string"))?.to_string(),
+                ),
+                (None, None) => None,
+            };
             let when = obj.remove("when").and_then(|v| v.as_str().map(|s| s.to_string()));
 
-            return Ok(Element::Input { input, id, placeholder, rows, when });
+            return Ok(Element::Input { input, id, placeholder, rows, default, span, when });
         }
 
         if let Some(lbl_val) = obj.remove("select") {
@@ -181,6 +253,7 @@ impl<'de> Visitor<'de> for ElementVisitor {
                 .map_err(de::Error::custom)?.into();
             
             let default = obj.remove("default").and_then(|v| v.as_str().map(|s| s.to_string()));
+            let searchable = obj.remove("searchable").and_then(|v| v.as_bool()).unwrap_or(false);
             let when = obj.remove("when").and_then(|v| v.as_str().map(|s| s.to_string()));
 
             let reveals = if let Some(rev_val) = obj.remove("reveals") {
@@ -204,7 +277,7 @@ impl<'de> Visitor<'de> for ElementVisitor {
                 }
             }
 
-            return Ok(Element::Select { select, id, options, default, option_children, reveals, when });
+            return Ok(Element::Select { select, id, options, default, option_children, reveals, searchable, span, when });
         }
 
         if let Some(lbl_val) = obj.remove("multi") {
@@ -216,6 +289,17 @@ impl<'de> Visitor<'de> for ElementVisitor {
             let options: Vec<OptionValue> = serde_json::from_value::<PolyOptions>(opts_val)
                 .map_err(de::Error::custom)?.into();
             
+            let default: Vec<String> = match obj.remove("default") {
+                Some(v) => {
+                    let po: PolyOptions = serde_json::from_value(v).map_err(de::Error::custom)?;
+                    Vec::<OptionValue>::from(po).iter().map(|o| o.value().to_string()).collect()
+                }
+                None => Vec::new(),
+            };
+            if let Some(unknown) = default.iter().find(|d| !options.iter().any(|o| o.value() == d.as_str())) {
+                return Err(de::Error::custom(format!("multi '{}' default '{}' is not one of its options", multi, unknown)));
+            }
+            let searchable = obj.remove("searchable").and_then(|v| v.as_bool()).unwrap_or(false);
             let when = obj.remove("when").and_then(|v| v.as_str().map(|s| s.to_string()));
 
             let reveals = if let Some(rev_val) = obj.remove("reveals") {
@@ -237,7 +321,263 @@ impl<'de> Visitor<'de> for ElementVisitor {
                 }
             }
 
-            return Ok(Element::Multi { multi, id, options, option_children, reveals, when });
+            return Ok(Element::Multi { multi, id, options, default, option_children, reveals, searchable, span, when });
+        }
+
+        if let Some(src_val) = obj.remove("image") {
+            let image = src_val.as_str().ok_or_else(|| de::Error::custom("image must be string"))?.to_string();
+            check_image_source(&image).map_err(de::Error::custom)?;
+            let id = obj.remove("id").and_then(|v| v.as_str().map(|s| s.to_string()));
+            let alt = obj.remove("alt").and_then(|v| v.as_str().map(|s| s.to_string()));
+            let max_width = obj.remove("max_width").and_then(|v| v.as_f64().map(|f| f as f32));
+            let max_height = obj.remove("max_height").and_then(|v| v.as_f64().map(|f| f as f32));
+            let when = obj.remove("when").and_then(|v| v.as_str().map(|s| s.to_string()));
+
+            return Ok(Element::Image { image, id, alt, max_width, max_height, span, when });
+        }
+
+        if let Some(code_val) = obj.remove("code") {
+            let code = code_val.as_str().ok_or_else(|| de::Error::custom("code must be string"))?.to_string();
+            let id = obj.remove("id").and_then(|v| v.as_str().map(|s| s.to_string()));
+            let language = obj.remove("language").and_then(|v| v.as_str().map(|s| s.to_string()));
+            let editable = obj.remove("editable").and_then(|v| v.as_bool()).unwrap_or(false);
+            let line_numbers = obj.remove("line_numbers").and_then(|v| v.as_bool()).unwrap_or(true);
+            let when = obj.remove("when").and_then(|v| v.as_str().map(|s| s.to_string()));
+            if editable && id.is_none() {
+                return Err(de::Error::custom("editable code needs an id to return the edited text under"));
+            }
+
+            return Ok(Element::Code { code, id, language, editable, line_numbers, span, when });
+        }
+
+        if let Some(tbl_val) = obj.remove("table") {
+            let table = tbl_val.as_str().ok_or_else(|| de::Error::custom("table must be string"))?.to_string();
+            let id = obj.remove("id").and_then(|v| v.as_str().map(|s| s.to_string()))
+                .unwrap_or_else(|| label_to_snake_case(&table));
+            let columns: Vec<String> = match obj.remove("columns") {
+                Some(v) => serde_json::from_value(v).map_err(de::Error::custom)?,
+                None => return Err(de::Error::custom(format!("table '{}' needs columns", table))),
+            };
+            if columns.is_empty() {
+                return Err(de::Error::custom(format!("table '{}' needs at least one column", table)));
+            }
+            let rows = match obj.remove("rows") {
+                Some(Value::Array(rows)) => table::normalize_rows(&columns, rows).map_err(de::Error::custom)?,
+                Some(_) => return Err(de::Error::custom("table rows must be an array")),
+                None => Vec::new(),
+            };
+            let key = obj.remove("key").and_then(|v| v.as_str().map(|s| s.to_string()));
+            if let Some(k) = &key {
+                if !columns.contains(k) {
+                    return Err(de::Error::custom(format!("table key `{}` is not one of its columns", k)));
+                }
+            }
+            let selection: TableSelection = match obj.remove("selection") {
+                Some(v) => serde_json::from_value(v).map_err(de::Error::custom)?,
+                None => TableSelection::None,
+            };
+            if selection != TableSelection::None {
+                let mut seen = std::collections::HashSet::new();
+                for row_key in table::row_keys(&columns, &rows, key.as_deref()) {
+                    if !seen.insert(row_key.clone()) {
+                        return Err(de::Error::custom(format!(
+                            "table '{}' has duplicate row key `{}`; set `key` to a column with unique values",
+                            table, row_key
+                        )));
+                    }
+                }
+            }
+            let when = obj.remove("when").and_then(|v| v.as_str().map(|s| s.to_string()));
+
+            return Ok(Element::Table { table, id, columns, rows, key, selection, span, when });
+        }
+
+        if let Some(diff_val) = obj.remove("diff") {
+            let diff = diff_val.as_str().ok_or_else(|| de::Error::custom("diff must be string"))?.to_string();
+            let label = obj.remove("label").and_then(|v| v.as_str().map(|s| s.to_string()));
+            let id = obj.remove("id").and_then(|v| v.as_str().map(|s| s.to_string()))
+                .or_else(|| label.as_deref().map(label_to_snake_case))
+                .ok_or_else(|| de::Error::custom("diff needs an id (or a label to derive one from)"))?;
+            let when = obj.remove("when").and_then(|v| v.as_str().map(|s| s.to_string()));
+
+            return Ok(Element::Diff { diff, id, label, span, when });
+        }
+
+        if let Some(lbl_val) = obj.remove("rating") {
+            let rating = lbl_val.as_str().ok_or_else(|| de::Error::custom("rating must be string"))?.to_string();
+            let id = obj.remove("id").and_then(|v| v.as_str().map(|s| s.to_string()))
+                .unwrap_or_else(|| label_to_snake_case(&rating));
+            let max = obj.remove("max").and_then(|v| v.as_u64()).unwrap_or(5) as u32;
+            if !(2..=10).contains(&max) {
+                return Err(de::Error::custom(format!("rating '{}' max must be between 2 and 10", rating)));
+            }
+            let default = obj.remove("default").and_then(|v| v.as_u64()).map(|n| n as u32);
+            if default.is_some_and(|d| d < 1 || d > max) {
+                return Err(de::Error::custom(format!("rating '{}' default must be between 1 and {}", rating, max)));
+            }
+            let low_label = obj.remove("low_label").and_then(|v| v.as_str().map(|s| s.to_string()));
+            let high_label = obj.remove("high_label").and_then(|v| v.as_str().map(|s| s.to_string()));
+            let style: RatingStyle = match obj.remove("style") {
+                Some(v) => serde_json::from_value(v).map_err(de::Error::custom)?,
+                None => RatingStyle::Stars,
+            };
+            let when = obj.remove("when").and_then(|v| v.as_str().map(|s| s.to_string()));
+
+            return Ok(Element::Rating { rating, id, max, default, low_label, high_label, style, span, when });
+        }
+
+        if let Some(lbl_val) = obj.remove("matrix") {
+            let matrix = lbl_val.as_str().ok_or_else(|| de::Error::custom("matrix must be string"))?.to_string();
+            let id = obj.remove("id").and_then(|v| v.as_str().map(|s| s.to_string()))
+                .unwrap_or_else(|| label_to_snake_case(&matrix));
+            let rows = match obj.remove("rows") {
+                Some(v) => parse_matrix_rows(v).map_err(de::Error::custom)?,
+                None => Vec::new(),
+            };
+            if rows.is_empty() {
+                return Err(de::Error::custom(format!("matrix '{}' needs at least one row", matrix)));
+            }
+            let columns: Vec<String> = match obj.remove("columns") {
+                Some(v) => {
+                    let po: PolyOptions = serde_json::from_value(v).map_err(de::Error::custom)?;
+                    Vec::<OptionValue>::from(po).iter().map(|o| o.value().to_string()).collect()
+                }
+                None => Vec::new(),
+            };
+            if columns.len() < 2 {
+                return Err(de::Error::custom(format!("matrix '{}' needs at least two columns", matrix)));
+            }
+            let when = obj.remove("when").and_then(|v| v.as_str().map(|s| s.to_string()));
+
+            return Ok(Element::Matrix { matrix, id, rows, columns, span, when });
+        }
+
+        if let Some(lbl_val) = obj.remove("color") {
+            let color = lbl_val.as_str().ok_or_else(|| de::Error::custom("color must be string"))?.to_string();
+            let id = obj.remove("id").and_then(|v| v.as_str().map(|s| s.to_string()))
+                .unwrap_or_else(|| label_to_snake_case(&color));
+            let alpha = obj.remove("alpha").and_then(|v| v.as_bool()).unwrap_or(false);
+            // Normalize up front so the result format doesn't depend on how the caller spelled it
+            let hex = |v: &Value| -> Result<String, A::Error> {
+                v.as_str()
+                    .and_then(validation::parse_hex_color)
+                    .map(|rgba| validation::format_hex_color(rgba, alpha))
+                    .ok_or_else(|| de::Error::custom(format!("color {} is not a hex color like \"#3366ff\"", v)))
+            };
+            let default = obj.remove("default").map(|v| hex(&v)).transpose()?;
+            let palette = match obj.remove("palette") {
+                Some(Value::Array(arr)) => arr.iter().map(hex).collect::<Result<Vec<_>, _>>()?,
+                Some(_) => return Err(de::Error::custom("palette must be an array of hex colors")),
+                None => Vec::new(),
+            };
+            let when = obj.remove("when").and_then(|v| v.as_str().map(|s| s.to_string()));
+
+            return Ok(Element::Color { color, id, default, alpha, palette, span, when });
+        }
+
+        if let Some(lbl_val) = obj.remove("path") {
+            let path = lbl_val.as_str().ok_or_else(|| de::Error::custom("path must be string"))?.to_string();
+            let id = obj.remove("id").and_then(|v| v.as_str().map(|s| s.to_string()))
+                .unwrap_or_else(|| label_to_snake_case(&path));
+            let root = obj.remove("root").and_then(|v| v.as_str().map(|s| s.to_string()));
+            let filters: Vec<String> = match obj.remove("filters") {
+                Some(v) => {
+                    let po: PolyOptions = serde_json::from_value(v).map_err(de::Error::custom)?;
+                    Vec::<OptionValue>::from(po).iter().map(|o| o.value().to_string()).collect()
+                }
+                None => Vec::new(),
+            };
+            let mode: PathMode = match obj.remove("mode") {
+                Some(v) => serde_json::from_value(v).map_err(de::Error::custom)?,
+                None => PathMode::File,
+            };
+            let multiple = obj.remove("multiple").and_then(|v| v.as_bool()).unwrap_or(false);
+            let when = obj.remove("when").and_then(|v| v.as_str().map(|s| s.to_string()));
+
+            return Ok(Element::Path { path, id, root, filters, mode, multiple, span, when });
+        }
+
+        if let Some(phrase_val) = obj.remove("confirm_phrase") {
+            let confirm_phrase = phrase_val.as_str().ok_or_else(|| de::Error::custom("confirm_phrase must be string"))?.to_string();
+            if confirm_phrase.trim().is_empty() {
+                return Err(de::Error::custom("confirm_phrase can't be empty"));
+            }
+            let id = obj.remove("id").and_then(|v| v.as_str().map(|s| s.to_string()))
+                .unwrap_or_else(|| format!("confirm_{}", label_to_snake_case(&confirm_phrase)));
+            let label = obj.remove("label").and_then(|v| v.as_str().map(|s| s.to_string()));
+            let case_sensitive = obj.remove("case_sensitive").and_then(|v| v.as_bool()).unwrap_or(true);
+            let when = obj.remove("when").and_then(|v| v.as_str().map(|s| s.to_string()));
+
+            return Ok(Element::ConfirmPhrase { confirm_phrase, id, label, case_sensitive, span, when });
+        }
+
+        if let Some(lbl_val) = obj.remove("secret") {
+            let secret = lbl_val.as_str().ok_or_else(|| de::Error::custom("secret must be string"))?.to_string();
+            let id = obj.remove("id").and_then(|v| v.as_str().map(|s| s.to_string()))
+                .unwrap_or_else(|| label_to_snake_case(&secret));
+            let placeholder = obj.remove("placeholder").and_then(|v| v.as_str().map(|s| s.to_string()));
+            let to_file = obj.remove("to_file").and_then(|v| v.as_bool()).unwrap_or(false);
+            let when = obj.remove("when").and_then(|v| v.as_str().map(|s| s.to_string()));
+
+            return Ok(Element::Secret { secret, id, placeholder, to_file, span, when });
+        }
+
+        if let Some(lbl_val) = obj.remove("tags") {
+            let tags = lbl_val.as_str().ok_or_else(|| de::Error::custom("tags must be string"))?.to_string();
+            let id = obj.remove("id").and_then(|v| v.as_str().map(|s| s.to_string()))
+                .unwrap_or_else(|| label_to_snake_case(&tags));
+            let suggestions: Vec<String> = match obj.remove("suggestions") {
+                Some(v) => {
+                    let po: PolyOptions = serde_json::from_value(v).map_err(de::Error::custom)?;
+                    Vec::<OptionValue>::from(po).iter().map(|o| o.value().to_string()).collect()
+                }
+                None => Vec::new(),
+            };
+            let max_tags = obj.remove("max_tags").and_then(|v| v.as_u64()).map(|n| n as usize);
+            let pattern = obj.remove("pattern").and_then(|v| v.as_str().map(|s| s.to_string()))
+                .map(|p| TagPattern::new(&p))
+                .transpose()
+                .map_err(|e| de::Error::custom(format!("tags '{}' has an invalid pattern: {}", tags, e)))?;
+            let placeholder = obj.remove("placeholder").and_then(|v| v.as_str().map(|s| s.to_string()));
+            let when = obj.remove("when").and_then(|v| v.as_str().map(|s| s.to_string()));
+
+            return Ok(Element::Tags { tags, id, suggestions, max_tags, pattern, placeholder, span, when });
+        }
+
+        if let Some(lbl_val) = obj.remove("kv") {
+            let kv = lbl_val.as_str().ok_or_else(|| de::Error::custom("kv must be string"))?.to_string();
+            let id = obj.remove("id").and_then(|v| v.as_str().map(|s| s.to_string()))
+                .unwrap_or_else(|| label_to_snake_case(&kv));
+            let keys: Vec<String> = match obj.remove("keys") {
+                Some(v) => serde_json::from_value(v).map_err(de::Error::custom)?,
+                None => Vec::new(),
+            };
+            let pairs: Vec<(String, String)> = keys.iter().map(|k| (k.clone(), String::new())).collect();
+            if let Some(key) = crate::validation::duplicate_key(&pairs) {
+                return Err(de::Error::custom(format!("kv '{}' lists key `{}` twice", kv, key)));
+            }
+            let placeholder = obj.remove("placeholder").and_then(|v| v.as_str().map(|s| s.to_string()));
+            let when = obj.remove("when").and_then(|v| v.as_str().map(|s| s.to_string()));
+
+            return Ok(Element::Kv { kv, id, keys, placeholder, span, when });
+        }
+
+        if let Some(lbl_val) = obj.remove("list") {
+            let list = lbl_val.as_str().ok_or_else(|| de::Error::custom("list must be string"))?.to_string();
+            let id = obj.remove("id").and_then(|v| v.as_str().map(|s| s.to_string()))
+                .unwrap_or_else(|| label_to_snake_case(&list));
+            let when = obj.remove("when").and_then(|v| v.as_str().map(|s| s.to_string()));
+
+            let elems_val = obj.remove("elements").ok_or_else(|| de::Error::custom(format!("list '{}' missing elements", list)))?;
+            let elements: Vec<Element> = serde_json::from_value::<PolyChildren>(elems_val)
+                .map_err(de::Error::custom)?.into();
+            let min_items = obj.remove("min_items").and_then(|v| v.as_u64()).unwrap_or(0) as usize;
+            let max_items = obj.remove("max_items").and_then(|v| v.as_u64()).map(|n| n as usize);
+            if max_items.is_some_and(|max| max < min_items) {
+                return Err(de::Error::custom(format!("list '{}' has max_items below min_items", list)));
+            }
+
+            return Ok(Element::List { list, id, elements, min_items, max_items, span, when });
         }
 
         if let Some(lbl_val) = obj.remove("group") {
@@ -249,8 +589,14 @@ impl<'de> Visitor<'de> for ElementVisitor {
             // Groups must have array elements, but let's be kind and use PolyChildren just in case single object is passed
             let elements: Vec<Element> = serde_json::from_value::<PolyChildren>(elems_val)
                 .map_err(de::Error::custom)?.into();
-
-            return Ok(Element::Group { group, id, elements, when });
+            let layout = obj.remove("layout")
+                .map(serde_json::from_value::<Layout>)
+                .transpose()
+                .map_err(de::Error::custom)?;
+            let collapsed = obj.remove("collapsed").and_then(|v| v.as_bool()).unwrap_or(false);
+            let summary = obj.remove("summary").and_then(|v| v.as_str().map(|s| s.to_string()));
+
+            return Ok(Element::Group { group, id, elements, layout, collapsed, summary, span, when });
         }
 
         Err(de::Error::custom("Unknown element type"))
@@ -278,59 +624,68 @@ impl Serialize for Element {
         // (Assuming you want this file to be fully self-contained replacement)
         
         match self {
-            Element::Text { text, id, when } => {
+            Element::Text { text, id, span, when } => {
                 let mut map = serializer.serialize_map(None)?;
                 map.serialize_entry("text", text)?;
                 if let Some(v) = id { map.serialize_entry("id", v)?; }
+                if let Some(v) = span { map.serialize_entry("span", v)?; }
                 if let Some(v) = when { map.serialize_entry("when", v)?; }
                 map.end()
             }
-            Element::Markdown { markdown, id, when } => {
+            Element::Markdown { markdown, id, span, when } => {
                 let mut map = serializer.serialize_map(None)?;
                 map.serialize_entry("markdown", markdown)?;
                 if let Some(v) = id { map.serialize_entry("id", v)?; }
+                if let Some(v) = span { map.serialize_entry("span", v)?; }
                 if let Some(v) = when { map.serialize_entry("when", v)?; }
                 map.end()
             }
-            Element::Slider { slider, id, min, max, default, when } => {
+            Element::Slider { slider, id, min, max, default, span, when } => {
                 let mut map = serializer.serialize_map(None)?;
                 map.serialize_entry("slider", slider)?;
                 map.serialize_entry("id", id)?;
                 map.serialize_entry("min", min)?;
                 map.serialize_entry("max", max)?;
                 if let Some(v) = default { map.serialize_entry("default", v)?; }
+                if let Some(v) = span { map.serialize_entry("span", v)?; }
                 if let Some(v) = when { map.serialize_entry("when", v)?; }
                 map.end()
             }
-            Element::Check { check, id, default, reveals, when } => {
+            Element::Check { check, id, default, reveals, span, when } => {
                 let mut map = serializer.serialize_map(None)?;
                 map.serialize_entry("check", check)?;
                 map.serialize_entry("id", id)?;
                 if *default { map.serialize_entry("default", default)?; }
                 if !reveals.is_empty() { map.serialize_entry("reveals", reveals)?; }
+                if let Some(v) = span { map.serialize_entry("span", v)?; }
                 if let Some(v) = when { map.serialize_entry("when", v)?; }
                 map.end()
             }
-            Element::Input { input, id, placeholder, rows, when } => {
+            Element::Input { input, id, placeholder, rows, default, span, when } => {
                 let mut map = serializer.serialize_map(None)?;
                 map.serialize_entry("input", input)?;
                 map.serialize_entry("id", id)?;
                 if let Some(v) = placeholder { map.serialize_entry("placeholder", v)?; }
                 if let Some(v) = rows { map.serialize_entry("rows", v)?; }
+                if let Some(v) = default { map.serialize_entry("default", v)?; }
+                if let Some(v) = span { map.serialize_entry("span", v)?; }
                 if let Some(v) = when { map.serialize_entry("when", v)?; }
                 map.end()
             }
-            Element::Multi { multi, id, options, option_children, reveals, when } => {
+            Element::Multi { multi, id, options, default, option_children, reveals, searchable, span, when } => {
                 let mut map = serializer.serialize_map(None)?;
                 map.serialize_entry("multi", multi)?;
                 map.serialize_entry("id", id)?;
                 map.serialize_entry("options", options)?;
+                if !default.is_empty() { map.serialize_entry("default", default)?; }
                 for (k, v) in option_children { map.serialize_entry(k, v)?; }
                 if !reveals.is_empty() { map.serialize_entry("reveals", reveals)?; }
+                if *searchable { map.serialize_entry("searchable", &true)?; }
+                if let Some(v) = span { map.serialize_entry("span", v)?; }
                 if let Some(v) = when { map.serialize_entry("when", v)?; }
                 map.end()
             }
-            Element::Select { select, id, options, default, option_children, reveals, when } => {
+            Element::Select { select, id, options, default, option_children, reveals, searchable, span, when } => {
                 let mut map = serializer.serialize_map(None)?;
                 map.serialize_entry("select", select)?;
                 map.serialize_entry("id", id)?;
@@ -338,14 +693,183 @@ impl Serialize for Element {
                 if let Some(v) = default { map.serialize_entry("default", v)?; }
                 for (k, v) in option_children { map.serialize_entry(k, v)?; }
                 if !reveals.is_empty() { map.serialize_entry("reveals", reveals)?; }
+                if *searchable { map.serialize_entry("searchable", &true)?; }
+                if let Some(v) = span { map.serialize_entry("span", v)?; }
+                if let Some(v) = when { map.serialize_entry("when", v)?; }
+                map.end()
+            }
+            Element::Image { image, id, alt, max_width, max_height, span, when } => {
+                let mut map = serializer.serialize_map(None)?;
+                map.serialize_entry("image", image)?;
+                if let Some(v) = id { map.serialize_entry("id", v)?; }
+                if let Some(v) = alt { map.serialize_entry("alt", v)?; }
+                if let Some(v) = max_width { map.serialize_entry("max_width", v)?; }
+                if let Some(v) = max_height { map.serialize_entry("max_height", v)?; }
+                if let Some(v) = span { map.serialize_entry("span", v)?; }
+                if let Some(v) = when { map.serialize_entry("when", v)?; }
+                map.end()
+            }
+            Element::Code { code, id, language, editable, line_numbers, span, when } => {
+                let mut map = serializer.serialize_map(None)?;
+                map.serialize_entry("code", code)?;
+                if let Some(v) = id { map.serialize_entry("id", v)?; }
+                if let Some(v) = language { map.serialize_entry("language", v)?; }
+                if *editable { map.serialize_entry("editable", editable)?; }
+                if !*line_numbers { map.serialize_entry("line_numbers", line_numbers)?; }
+                if let Some(v) = span { map.serialize_entry("span", v)?; }
+                if let Some(v) = when { map.serialize_entry("when", v)?; }
+                map.end()
+            }
+            Element::Table { table, id, columns, rows, key, selection, span, when } => {
+                let mut map = serializer.serialize_map(None)?;
+                map.serialize_entry("table", table)?;
+                map.serialize_entry("id", id)?;
+                map.serialize_entry("columns", columns)?;
+                map.serialize_entry("rows", rows)?;
+                if let Some(v) = key { map.serialize_entry("key", v)?; }
+                if *selection != TableSelection::None { map.serialize_entry("selection", selection)?; }
+                if let Some(v) = span { map.serialize_entry("span", v)?; }
+                if let Some(v) = when { map.serialize_entry("when", v)?; }
+                map.end()
+            }
+            Element::Diff { diff, id, label, span, when } => {
+                let mut map = serializer.serialize_map(None)?;
+                map.serialize_entry("diff", diff)?;
+                map.serialize_entry("id", id)?;
+                if let Some(v) = label { map.serialize_entry("label", v)?; }
+                if let Some(v) = span { map.serialize_entry("span", v)?; }
+                if let Some(v) = when { map.serialize_entry("when", v)?; }
+                map.end()
+            }
+            Element::Rating { rating, id, max, default, low_label, high_label, style, span, when } => {
+                let mut map = serializer.serialize_map(None)?;
+                map.serialize_entry("rating", rating)?;
+                map.serialize_entry("id", id)?;
+                map.serialize_entry("max", max)?;
+                if let Some(v) = default { map.serialize_entry("default", v)?; }
+                if let Some(v) = low_label { map.serialize_entry("low_label", v)?; }
+                if let Some(v) = high_label { map.serialize_entry("high_label", v)?; }
+                if *style != RatingStyle::Stars { map.serialize_entry("style", style)?; }
+                if let Some(v) = span { map.serialize_entry("span", v)?; }
+                if let Some(v) = when { map.serialize_entry("when", v)?; }
+                map.end()
+            }
+            Element::Matrix { matrix, id, rows, columns, span, when } => {
+                let mut map = serializer.serialize_map(None)?;
+                map.serialize_entry("matrix", matrix)?;
+                map.serialize_entry("id", id)?;
+                // Plain statements when the id is the derived one
+                let rows: Vec<Value> = rows.iter().map(|row| {
+                    if row.id == label_to_snake_case(&row.label) {
+                        Value::String(row.label.clone())
+                    } else {
+                        serde_json::json!({"id": row.id, "label": row.label})
+                    }
+                }).collect();
+                map.serialize_entry("rows", &rows)?;
+                map.serialize_entry("columns", columns)?;
+                if let Some(v) = span { map.serialize_entry("span", v)?; }
+                if let Some(v) = when { map.serialize_entry("when", v)?; }
+                map.end()
+            }
+            Element::Color { color, id, default, alpha, palette, span, when } => {
+                let mut map = serializer.serialize_map(None)?;
+                map.serialize_entry("color", color)?;
+                map.serialize_entry("id", id)?;
+                if let Some(v) = default { map.serialize_entry("default", v)?; }
+                if *alpha { map.serialize_entry("alpha", &true)?; }
+                if !palette.is_empty() { map.serialize_entry("palette", palette)?; }
+                if let Some(v) = span { map.serialize_entry("span", v)?; }
+                if let Some(v) = when { map.serialize_entry("when", v)?; }
+                map.end()
+            }
+            Element::Range { range, id, min, max, step, low, high, span, when } => {
+                let mut map = serializer.serialize_map(None)?;
+                map.serialize_entry("range", range)?;
+                map.serialize_entry("id", id)?;
+                map.serialize_entry("min", min)?;
+                map.serialize_entry("max", max)?;
+                if *step != 1.0 { map.serialize_entry("step", step)?; }
+                if let Some(v) = low { map.serialize_entry("low", v)?; }
+                if let Some(v) = high { map.serialize_entry("high", v)?; }
+                if let Some(v) = span { map.serialize_entry("span", v)?; }
+                if let Some(v) = when { map.serialize_entry("when", v)?; }
+                map.end()
+            }
+            Element::Path { path, id, root, filters, mode, multiple, span, when } => {
+                let mut map = serializer.serialize_map(None)?;
+                map.serialize_entry("path", path)?;
+                map.serialize_entry("id", id)?;
+                if let Some(v) = root { map.serialize_entry("root", v)?; }
+                if !filters.is_empty() { map.serialize_entry("filters", filters)?; }
+                if *mode != PathMode::File { map.serialize_entry("mode", mode)?; }
+                if *multiple { map.serialize_entry("multiple", &true)?; }
+                if let Some(v) = span { map.serialize_entry("span", v)?; }
+                if let Some(v) = when { map.serialize_entry("when", v)?; }
+                map.end()
+            }
+            Element::ConfirmPhrase { confirm_phrase, id, label, case_sensitive, span, when } => {
+                let mut map = serializer.serialize_map(None)?;
+                map.serialize_entry("confirm_phrase", confirm_phrase)?;
+                map.serialize_entry("id", id)?;
+                if let Some(v) = label { map.serialize_entry("label", v)?; }
+                if !*case_sensitive { map.serialize_entry("case_sensitive", &false)?; }
+                if let Some(v) = span { map.serialize_entry("span", v)?; }
+                if let Some(v) = when { map.serialize_entry("when", v)?; }
+                map.end()
+            }
+            Element::Secret { secret, id, placeholder, to_file, span, when } => {
+                let mut map = serializer.serialize_map(None)?;
+                map.serialize_entry("secret", secret)?;
+                map.serialize_entry("id", id)?;
+                if let Some(v) = placeholder { map.serialize_entry("placeholder", v)?; }
+                if *to_file { map.serialize_entry("to_file", &true)?; }
+                if let Some(v) = span { map.serialize_entry("span", v)?; }
+                if let Some(v) = when { map.serialize_entry("when", v)?; }
+                map.end()
+            }
+            Element::Tags { tags, id, suggestions, max_tags, pattern, placeholder, span, when } => {
+                let mut map = serializer.serialize_map(None)?;
+                map.serialize_entry("tags", tags)?;
+                map.serialize_entry("id", id)?;
+                if !suggestions.is_empty() { map.serialize_entry("suggestions", suggestions)?; }
+                if let Some(v) = max_tags { map.serialize_entry("max_tags", v)?; }
+                if let Some(v) = pattern { map.serialize_entry("pattern", v.as_str())?; }
+                if let Some(v) = placeholder { map.serialize_entry("placeholder", v)?; }
+                if let Some(v) = span { map.serialize_entry("span", v)?; }
                 if let Some(v) = when { map.serialize_entry("when", v)?; }
                 map.end()
             }
-            Element::Group { group, id, elements, when } => {
+            Element::Kv { kv, id, keys, placeholder, span, when } => {
+                let mut map = serializer.serialize_map(None)?;
+                map.serialize_entry("kv", kv)?;
+                map.serialize_entry("id", id)?;
+                if !keys.is_empty() { map.serialize_entry("keys", keys)?; }
+                if let Some(v) = placeholder { map.serialize_entry("placeholder", v)?; }
+                if let Some(v) = span { map.serialize_entry("span", v)?; }
+                if let Some(v) = when { map.serialize_entry("when", v)?; }
+                map.end()
+            }
+            Element::List { list, id, elements, min_items, max_items, span, when } => {
+                let mut map = serializer.serialize_map(None)?;
+                map.serialize_entry("list", list)?;
+                map.serialize_entry("id", id)?;
+                map.serialize_entry("elements", elements)?;
+                if *min_items > 0 { map.serialize_entry("min_items", min_items)?; }
+                if let Some(v) = max_items { map.serialize_entry("max_items", v)?; }
+                if let Some(v) = span { map.serialize_entry("span", v)?; }
+                if let Some(v) = when { map.serialize_entry("when", v)?; }
+                map.end()
+            }
+            Element::Group { group, id, elements, layout, collapsed, summary, span, when } => {
                 let mut map = serializer.serialize_map(None)?;
                 map.serialize_entry("group", group)?;
                 if let Some(v) = id { map.serialize_entry("id", v)?; }
                 map.serialize_entry("elements", elements)?;
+                if let Some(v) = layout { map.serialize_entry("layout", v)?; }
+                if *collapsed { map.serialize_entry("collapsed", &true)?; }
+                if let Some(v) = summary { map.serialize_entry("summary", v)?; }
+                if let Some(v) = span { map.serialize_entry("span", v)?; }
                 if let Some(v) = when { map.serialize_entry("when", v)?; }
                 map.end()
             }
@@ -362,6 +886,7 @@ mod tests {
         let elem = Element::Text {
             text: "Hello world".to_string(),
             id: Some("msg".to_string()),
+            span: None,
             when: None,
         };
         let json = serde_json::to_string(&elem).unwrap();
@@ -385,6 +910,7 @@ mod tests {
         let original = Element::Text {
             text: "Test".to_string(),
             id: None,
+            span: Some(Span::Full),
             when: Some("@enabled".to_string()),
         };
         let json = serde_json::to_string(&original).unwrap();
@@ -510,6 +1036,7 @@ mod tests {
             min: 0.0,
             max: 100.0,
             default: Some(75.0),
+            span: None,
             when: None,
         };
         let json = serde_json::to_value(&elem).unwrap();
@@ -529,6 +1056,7 @@ mod tests {
                 min: 0.0,
                 max: 100.0,
                 default: Some(50.0),
+                span: None,
                 when: None,
             }],
         );
@@ -543,6 +1071,8 @@ mod tests {
             default: Some("Dark".to_string()),
             option_children,
             reveals: vec![],
+            searchable: false,
+            span: None,
             when: None,
         };
 
@@ -559,6 +1089,7 @@ mod tests {
             vec![Element::Text {
                 text: "Advanced mode".to_string(),
                 id: None,
+                span: None,
                 when: None,
             }],
         );
@@ -573,6 +1104,8 @@ mod tests {
             default: None,
             option_children,
             reveals: vec![],
+            searchable: false,
+            span: None,
             when: None,
         };
 
@@ -590,11 +1123,13 @@ mod tests {
             Element::Text {
                 text: "Hello".to_string(),
                 id: Some("msg".to_string()),
+                span: None,
                 when: None,
             },
             Element::Markdown {
                 markdown: "## Header\n- **Bold** item\n- *Italic* item".to_string(),
                 id: Some("content".to_string()),
+                span: None,
                 when: None,
             },
             Element::Slider {
@@ -603,6 +1138,7 @@ mod tests {
                 min: 0.0,
                 max: 100.0,
                 default: None,
+                span: None,
                 when: None,
             },
             Element::Check {
@@ -610,6 +1146,7 @@ mod tests {
                 id: "enabled".to_string(),
                 default: true,
                 reveals: vec![],
+                span: None,
                 when: None,
             },
             Element::Input {
@@ -617,6 +1154,8 @@ mod tests {
                 id: "name".to_string(),
                 placeholder: Some("Enter name".to_string()),
                 rows: Some(3),
+                default: Some("Ada".to_string()),
+                span: None,
                 when: None,
             },
             Element::Multi {
@@ -626,14 +1165,153 @@ mod tests {
                     OptionValue::Simple("A".to_string()),
                     OptionValue::Simple("B".to_string()),
                 ],
+                default: vec!["B".to_string()],
                 option_children: HashMap::new(),
                 reveals: vec![],
+                searchable: false,
+                span: None,
+                when: None,
+            },
+            Element::Code {
+                code: "fn main() {}".to_string(),
+                id: Some("snippet".to_string()),
+                language: Some("rust".to_string()),
+                editable: true,
+                line_numbers: false,
+                span: None,
+                when: None,
+            },
+            Element::Table {
+                table: "Failing tests".to_string(),
+                id: "failing".to_string(),
+                columns: vec!["name".to_string(), "secs".to_string()],
+                rows: vec![vec!["test_a".to_string(), "1.5".to_string()]],
+                key: Some("name".to_string()),
+                selection: TableSelection::Multi,
+                span: None,
+                when: None,
+            },
+            Element::List {
+                list: "Env Vars".to_string(),
+                id: "env".to_string(),
+                elements: vec![Element::Input {
+                    input: "Name".to_string(),
+                    id: "name".to_string(),
+                    placeholder: None,
+                    rows: None,
+                    default: None,
+                    span: None,
+                    when: None,
+                }],
+                min_items: 1,
+                max_items: Some(10),
+                span: None,
+                when: None,
+            },
+            Element::Rating {
+                rating: "Overall".to_string(),
+                id: "overall".to_string(),
+                max: 7,
+                default: Some(4),
+                low_label: Some("Poor".to_string()),
+                high_label: Some("Great".to_string()),
+                style: RatingStyle::Points,
+                span: None,
+                when: None,
+            },
+            Element::Matrix {
+                matrix: "Agreement".to_string(),
+                id: "agreement".to_string(),
+                rows: vec![
+                    MatrixRow { id: "pace_was_right".to_string(), label: "Pace was right".to_string() },
+                    MatrixRow { id: "scope".to_string(), label: "Scope was clear".to_string() },
+                ],
+                columns: vec!["Disagree".to_string(), "Neutral".to_string(), "Agree".to_string()],
+                span: None,
+                when: None,
+            },
+            Element::Color {
+                color: "Accent".to_string(),
+                id: "accent".to_string(),
+                default: Some("#3366ff80".to_string()),
+                alpha: true,
+                palette: vec!["#ff0000ff".to_string(), "#00000000".to_string()],
+                span: None,
+                when: None,
+            },
+            Element::Range {
+                range: "Latency budget".to_string(),
+                id: "latency".to_string(),
+                min: 0.0,
+                max: 1000.0,
+                step: 50.0,
+                low: Some(100.0),
+                high: Some(400.0),
+                span: None,
+                when: None,
+            },
+            Element::Path {
+                path: "Config".to_string(),
+                id: "config".to_string(),
+                root: Some("~/projects".to_string()),
+                filters: vec!["*.toml".to_string(), "*.json".to_string()],
+                mode: PathMode::Both,
+                multiple: true,
+                span: None,
+                when: None,
+            },
+            Element::ConfirmPhrase {
+                confirm_phrase: "prod-db-main".to_string(),
+                id: "confirm_prod_db_main".to_string(),
+                label: Some("This deletes the database".to_string()),
+                case_sensitive: false,
+                span: None,
+                when: None,
+            },
+            Element::Secret {
+                secret: "API token".to_string(),
+                id: "token".to_string(),
+                placeholder: Some("sk-...".to_string()),
+                to_file: true,
+                span: None,
+                when: None,
+            },
+            Element::Tags {
+                tags: "Labels".to_string(),
+                id: "labels".to_string(),
+                suggestions: vec!["bug".to_string(), "docs".to_string()],
+                max_tags: Some(3),
+                pattern: Some(TagPattern::new("[a-z-]+").unwrap()),
+                placeholder: None,
+                span: None,
+                when: None,
+            },
+            Element::Kv {
+                kv: "Headers".to_string(),
+                id: "headers".to_string(),
+                keys: vec!["Accept".to_string()],
+                placeholder: Some("value".to_string()),
+                span: None,
                 when: None,
             },
             Element::Group {
                 group: "Settings".to_string(),
                 id: None,
                 elements: vec![],
+                layout: None,
+                collapsed: false,
+                summary: None,
+                span: None,
+                when: None,
+            },
+            Element::Group {
+                group: "Preferences".to_string(),
+                id: None,
+                elements: vec![],
+                layout: Some(Layout::Tabs),
+                collapsed: true,
+                summary: Some("Defaults".to_string()),
+                span: None,
                 when: None,
             },
         ];
@@ -1,6 +1,12 @@
 pub mod condition;
+pub mod diff;
 // mod element_deser;
 pub mod element_deser_v2;
+pub mod path;
+mod prefill;
+pub mod secret;
+pub mod table;
+pub mod validation;
 
 #[cfg(test)]
 mod tests;
@@ -8,9 +14,15 @@ mod tests;
 use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 use serde_json::Value;
+use std::borrow::Cow;
 use std::collections::HashMap;
 
-pub use condition::{evaluate_condition, parse_condition, ConditionExpr};
+pub use diff::{HunkDecision, HunkReview};
+pub use validation::{validate_elements, FieldError, TagPattern};
+
+pub use condition::{
+    evaluate_condition, explain_condition, parse_condition, ConditionError, ConditionExpr, Trace,
+};
 
 /// Option value for Choice/Multiselect - can be simple string or with description
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize, JsonSchema)]
@@ -23,6 +35,13 @@ pub enum OptionValue {
         value: String,
         #[serde(alias = "because")]
         description: String,
+        /// Thumbnail shown next to the option (local path or data URI)
+        #[serde(
+            default,
+            skip_serializing_if = "Option::is_none",
+            deserialize_with = "deserialize_icon"
+        )]
+        icon: Option<String>,
     },
 }
 
@@ -42,19 +61,237 @@ impl OptionValue {
             OptionValue::WithDescription { description, .. } => Some(description),
         }
     }
+
+    /// Get the icon if present
+    pub fn icon(&self) -> Option<&str> {
+        match self {
+            OptionValue::Simple(_) => None,
+            OptionValue::WithDescription { icon, .. } => icon.as_deref(),
+        }
+    }
 }
 
-#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, JsonSchema)]
+fn deserialize_icon<'de, D: serde::Deserializer<'de>>(deserializer: D) -> Result<Option<String>, D::Error> {
+    let icon = String::deserialize(deserializer)?;
+    check_image_source(&icon).map_err(serde::de::Error::custom)?;
+    Ok(Some(icon))
+}
+
+/// Images must be local files or inline data so a popup never fetches from the network
+pub(crate) fn check_image_source(src: &str) -> Result<(), String> {
+    if let Some(data) = src.strip_prefix("data:") {
+        return match data.split_once(',') {
+            Some((header, _)) if header.starts_with("image/") && header.ends_with(";base64") => Ok(()),
+            _ => Err("image data URIs must look like `data:image/png;base64,...`".to_string()),
+        };
+    }
+    match src.split_once("://") {
+        Some(("file", _)) | None => Ok(()),
+        Some(_) => Err(format!(
+            "remote image `{}` is not allowed; use a local file path or a base64 data URI",
+            src
+        )),
+    }
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize, JsonSchema)]
+#[serde(try_from = "RawPopupDefinition")]
 pub struct PopupDefinition {
+    pub title: String,
+    /// Elements of a single-page popup (empty when `pages` is used)
+    pub elements: Vec<Element>,
+    /// Wizard pages, shown one at a time with Next/Back
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub pages: Vec<Page>,
+    /// Schema version; 3 and later default to `hidden_fields: unset`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub version: Option<u32>,
+    /// How `when` clauses see fields of hidden elements
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub hidden_fields: Option<HiddenFields>,
+    /// Arrangement of top-level elements (and of each page's elements)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub layout: Option<Layout>,
+    /// Starting values by element id, in the same format as the result
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    pub initial_values: HashMap<String, Value>,
+}
+
+/// Wire format of `PopupDefinition`: exactly one of `elements` or `pages`
+#[derive(Deserialize, JsonSchema)]
+struct RawPopupDefinition {
+    title: String,
+    elements: Option<Vec<Element>>,
+    #[serde(default)]
+    pages: Vec<Page>,
+    #[serde(default)]
+    version: Option<u32>,
+    #[serde(default)]
+    hidden_fields: Option<HiddenFields>,
+    #[serde(default)]
+    layout: Option<Layout>,
+    #[serde(default)]
+    initial_values: HashMap<String, Value>,
+}
+
+impl TryFrom<RawPopupDefinition> for PopupDefinition {
+    type Error = String;
+
+    fn try_from(raw: RawPopupDefinition) -> Result<Self, Self::Error> {
+        let elements = match (raw.elements, raw.pages.is_empty()) {
+            (None, true) => return Err("missing field `elements`".to_string()),
+            (Some(elements), false) if !elements.is_empty() => {
+                return Err("`elements` and `pages` cannot both be set; \
+                     move the elements onto a page"
+                    .to_string())
+            }
+            (elements, _) => elements.unwrap_or_default(),
+        };
+
+        // `initial_values` are checked later, once "Other" options exist for
+        // them to select (see `initial_value_errors`)
+        Ok(PopupDefinition {
+            title: raw.title,
+            elements,
+            pages: raw.pages,
+            version: raw.version,
+            hidden_fields: raw.hidden_fields,
+            layout: raw.layout,
+            initial_values: raw.initial_values,
+        })
+    }
+}
+
+/// One step of a multi-page popup
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize, JsonSchema)]
+pub struct Page {
     pub title: String,
     pub elements: Vec<Element>,
+    /// Condition for showing the page; skipped pages contribute no values
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub when: Option<String>,
+    /// Ids that must be answered before moving past the page
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub required: Vec<String>,
 }
 
+impl Page {
+    /// The page as a group element, so visibility and lookups treat the
+    /// page's `when` like any other container
+    fn as_group(&self) -> Element {
+        Element::Group {
+            group: self.title.clone(),
+            id: None,
+            elements: self.elements.clone(),
+            layout: None,
+            collapsed: false,
+            summary: None,
+            span: None,
+            when: self.when.clone(),
+        }
+    }
+}
+
+/// How `when` clauses see the values of elements that aren't currently shown
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum HiddenFields {
+    /// Hidden fields keep their last value (schema v2 behavior)
+    #[default]
+    Keep,
+    /// Hidden fields read as null
+    Unset,
+}
+
+/// First schema version where hidden fields read as null by default
+pub const HIDDEN_FIELDS_UNSET_VERSION: u32 = 3;
+
+/// Upper bound on visibility passes in `PopupState::condition_values`
+const MAX_VISIBILITY_PASSES: usize = 16;
+
 impl PopupDefinition {
     /// Get the effective title
     pub fn effective_title(&self) -> &str {
         &self.title
     }
+
+    /// Every element in the popup: `elements`, or each page wrapped as a group
+    pub fn all_elements(&self) -> Cow<'_, [Element]> {
+        if self.pages.is_empty() {
+            Cow::Borrowed(&self.elements)
+        } else {
+            Cow::Owned(self.pages.iter().map(Page::as_group).collect())
+        }
+    }
+
+    /// The hidden-field mode in effect: explicit `hidden_fields`, otherwise
+    /// `Unset` for schema v3+ and `Keep` for older definitions
+    pub fn hidden_field_mode(&self) -> HiddenFields {
+        self.hidden_fields.unwrap_or(match self.version {
+            Some(v) if v >= HIDDEN_FIELDS_UNSET_VERSION => HiddenFields::Unset,
+            _ => HiddenFields::Keep,
+        })
+    }
+
+    /// Values for evaluating this definition's `when` clauses against `state`
+    pub fn condition_values(&self, state: &PopupState) -> HashMap<String, Value> {
+        state.condition_values(&self.all_elements(), self.hidden_field_mode())
+    }
+
+    /// One message per `initial_values` entry that names no field or doesn't
+    /// fit its element (empty if all apply). Check after injecting "Other"
+    /// options, so a past result's Other picks and texts find their fields.
+    pub fn initial_value_errors(&self) -> Vec<String> {
+        let mut state = PopupState::default();
+        let elements = self.all_elements();
+        state.init_elements(&elements);
+        prefill::apply(&elements, &mut state.values, &self.initial_values)
+    }
+
+    /// Parse every `when` clause in the definition, returning one entry per
+    /// clause that fails to parse (empty if all conditions are valid)
+    pub fn condition_errors(&self) -> Vec<WhenClauseError> {
+        let mut errors = Vec::new();
+        collect_condition_errors(&self.all_elements(), &mut errors);
+        errors
+    }
+}
+
+/// A `when` clause that failed to parse, with the element it belongs to
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct WhenClauseError {
+    /// Element id, or its label for elements without an id
+    pub element: String,
+    /// The offending condition text
+    pub when: String,
+    #[serde(flatten)]
+    pub error: ConditionError,
+}
+
+impl std::fmt::Display for WhenClauseError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "Invalid when clause on '{}' (`{}`): {}", self.element, self.when, self.error)
+    }
+}
+
+fn collect_condition_errors(elements: &[Element], errors: &mut Vec<WhenClauseError>) {
+    for element in elements {
+        if let Some(when) = element.when() {
+            if let Err(error) = parse_condition(when) {
+                errors.push(WhenClauseError {
+                    element: element.id().unwrap_or(element.label()).to_string(),
+                    when: when.to_string(),
+                    error,
+                });
+            }
+        }
+        for children in element.children() {
+            collect_condition_errors(children, errors);
+        }
+        if let Element::List { elements, .. } = element {
+            collect_condition_errors(elements, errors);
+        }
+    }
 }
 
 /// Schema v2: Element types using element-as-key pattern
@@ -66,6 +303,7 @@ pub enum Element {
     Text {
         text: String,
         id: Option<String>,
+        span: Option<Span>,
         when: Option<String>,
     },
 
@@ -73,6 +311,7 @@ pub enum Element {
     Markdown {
         markdown: String, // Markdown content becomes the discriminator key
         id: Option<String>,
+        span: Option<Span>,
         when: Option<String>,
     },
 
@@ -83,6 +322,22 @@ pub enum Element {
         min: f32,
         max: f32,
         default: Option<f32>,
+        span: Option<Span>,
+        when: Option<String>,
+    },
+
+    /// Two-handle slider picking a low..=high band within min..=max
+    Range {
+        range: String, // Label text becomes the discriminator key
+        id: String,
+        min: f32,
+        max: f32,
+        /// Snap interval, counted from `min`
+        step: f32,
+        /// Starting handle positions (default: min and max)
+        low: Option<f32>,
+        high: Option<f32>,
+        span: Option<Span>,
         when: Option<String>,
     },
 
@@ -92,6 +347,7 @@ pub enum Element {
         id: String,
         default: bool,
         reveals: Vec<Element>,
+        span: Option<Span>,
         when: Option<String>,
     },
 
@@ -101,6 +357,58 @@ pub enum Element {
         id: String,
         placeholder: Option<String>,
         rows: Option<u32>,
+        /// Draft text the user starts from, unlike the placeholder it is submitted
+        default: Option<String>,
+        span: Option<Span>,
+        when: Option<String>,
+    },
+
+    /// Masked text input for tokens and passphrases, kept out of logs
+    Secret {
+        secret: String, // Label text becomes the discriminator key
+        id: String,
+        placeholder: Option<String>,
+        /// Return a path to a private temp file holding the value instead of the value
+        to_file: bool,
+        span: Option<Span>,
+        when: Option<String>,
+    },
+
+    /// Color picker returning a hex string, "#rrggbb" or "#rrggbbaa" with alpha
+    Color {
+        color: String, // Label text becomes the discriminator key
+        id: String,
+        /// Normalized hex; empty until picked when absent
+        default: Option<String>,
+        alpha: bool,
+        /// Preset swatches, as normalized hex
+        palette: Vec<String>,
+        span: Option<Span>,
+        when: Option<String>,
+    },
+
+    /// File/directory browser rooted at `root`, returning absolute paths
+    Path {
+        path: String, // Label text becomes the discriminator key
+        id: String,
+        /// Directory the browser starts in and can't leave (default: working directory)
+        root: Option<String>,
+        /// File name globs such as "*.rs"; directories are always listed
+        filters: Vec<String>,
+        mode: PathMode,
+        multiple: bool,
+        span: Option<Span>,
+        when: Option<String>,
+    },
+
+    /// Submit stays disabled until the user types the phrase (e.g. a resource name)
+    ConfirmPhrase {
+        confirm_phrase: String, // Phrase to type becomes the discriminator key
+        id: String,
+        /// Prompt shown above the input, e.g. "This deletes the database"
+        label: Option<String>,
+        case_sensitive: bool,
+        span: Option<Span>,
         when: Option<String>,
     },
 
@@ -109,10 +417,14 @@ pub enum Element {
         multi: String, // Label text becomes the discriminator key
         id: String,
         options: Vec<OptionValue>,
+        default: Vec<String>, // Preselected option values, not indices
         // Option-as-key nesting: HashMap<option_value, Vec<Element>>
         // Custom serialize/deserialize handles option children as direct JSON keys
         option_children: HashMap<String, Vec<Element>>,
         reveals: Vec<Element>,
+        /// Type-to-filter list instead of a plain dropdown/checkbox grid
+        searchable: bool,
+        span: Option<Span>,
         when: Option<String>,
     },
 
@@ -126,6 +438,118 @@ pub enum Element {
         // Custom serialize/deserialize handles option children as direct JSON keys
         option_children: HashMap<String, Vec<Element>>,
         reveals: Vec<Element>,
+        /// Type-to-filter list instead of a plain dropdown/checkbox grid
+        searchable: bool,
+        span: Option<Span>,
+        when: Option<String>,
+    },
+
+    /// Image from a local file or base64 data URI
+    Image {
+        image: String, // Path or data URI becomes the discriminator key
+        id: Option<String>,
+        alt: Option<String>,
+        max_width: Option<f32>,
+        max_height: Option<f32>,
+        span: Option<Span>,
+        when: Option<String>,
+    },
+
+    /// Syntax-highlighted code block, optionally editable
+    Code {
+        code: String, // Source text becomes the discriminator key
+        id: Option<String>,
+        language: Option<String>,
+        /// Let the user edit the code; the edited text is returned under `id`
+        editable: bool,
+        line_numbers: bool,
+        span: Option<Span>,
+        when: Option<String>,
+    },
+
+    /// Read-only data table, optionally with row selection
+    Table {
+        table: String, // Label text becomes the discriminator key
+        id: String,
+        columns: Vec<String>,
+        /// One cell per column
+        rows: Vec<Vec<String>>,
+        /// Column whose cells identify rows in the result (default: first column)
+        key: Option<String>,
+        selection: TableSelection,
+        span: Option<Span>,
+        when: Option<String>,
+    },
+
+    /// Repeatable rows built from a template of child elements
+    List {
+        list: String, // Label text becomes the discriminator key
+        id: String,
+        /// Template for each row; ids and `when` clauses are scoped to the row
+        elements: Vec<Element>,
+        min_items: usize,
+        max_items: Option<usize>,
+        span: Option<Span>,
+        when: Option<String>,
+    },
+
+    /// Free-form tokens entered as chips, returned as a string array
+    Tags {
+        tags: String, // Label text becomes the discriminator key
+        id: String,
+        /// Autocomplete candidates; tags aren't limited to these
+        suggestions: Vec<String>,
+        max_tags: Option<usize>,
+        /// Regex each tag must match in full
+        #[schemars(with = "Option<String>")]
+        pattern: Option<TagPattern>,
+        placeholder: Option<String>,
+        span: Option<Span>,
+        when: Option<String>,
+    },
+
+    /// Score from 1 to `max`, shown as stars or numbered points
+    Rating {
+        rating: String, // Label text becomes the discriminator key
+        id: String,
+        max: u32,
+        default: Option<u32>,
+        /// Captions at the low and high ends of the scale
+        low_label: Option<String>,
+        high_label: Option<String>,
+        style: RatingStyle,
+        span: Option<Span>,
+        when: Option<String>,
+    },
+
+    /// Statements answered against a shared set of choices, one choice per row
+    Matrix {
+        matrix: String, // Label text becomes the discriminator key
+        id: String,
+        rows: Vec<MatrixRow>,
+        columns: Vec<String>,
+        span: Option<Span>,
+        when: Option<String>,
+    },
+
+    /// Editable key/value pairs, returned as a JSON object
+    Kv {
+        kv: String, // Label text becomes the discriminator key
+        id: String,
+        /// Fixed set of keys; when empty the user adds their own
+        keys: Vec<String>,
+        /// Hint shown in empty value boxes
+        placeholder: Option<String>,
+        span: Option<Span>,
+        when: Option<String>,
+    },
+
+    /// Unified diff for review, with accept/reject and a comment per hunk
+    Diff {
+        diff: String, // Diff text becomes the discriminator key
+        id: String,
+        label: Option<String>,
+        span: Option<Span>,
         when: Option<String>,
     },
 
@@ -134,10 +558,245 @@ pub enum Element {
         group: String, // Label text becomes the discriminator key
         id: Option<String>,
         elements: Vec<Element>,
+        layout: Option<Layout>,
+        /// Start with the group folded shut; its fields still count as shown
+        collapsed: bool,
+        /// Text shown in the header while collapsed, instead of the answered fields
+        summary: Option<String>,
+        span: Option<Span>,
         when: Option<String>,
     },
 }
 
+/// How a popup, page or group arranges its children
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum Layout {
+    /// Two columns once the window is wide enough, one otherwise
+    #[default]
+    Auto,
+    /// One element per line
+    Single,
+    /// A fixed number of columns, filled top to bottom (`{"columns": 3}`)
+    Columns(u32),
+    /// Side by side, wrapping onto new lines when out of room
+    Row,
+    /// Child groups become tabs, one shown at a time; other children stay above the tab bar
+    Tabs,
+}
+
+/// Which rows of a table the user can select
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum TableSelection {
+    #[default]
+    None,
+    Single,
+    Multi,
+}
+
+/// How a rating scale is drawn
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum RatingStyle {
+    #[default]
+    Stars,
+    /// Numbered buttons, for Likert-style scales
+    Points,
+}
+
+/// What a path picker lets the user choose
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum PathMode {
+    #[default]
+    File,
+    Dir,
+    Both,
+}
+
+/// One statement of a matrix question; its id keys the result and conditions
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, JsonSchema)]
+pub struct MatrixRow {
+    pub id: String,
+    pub label: String,
+}
+
+/// Horizontal extent of an element in a multi-column layout
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum Span {
+    /// Take the whole width, breaking the columns above and below it
+    Full,
+}
+
+impl Element {
+    /// The element's `when` clause, if any
+    pub fn when(&self) -> Option<&str> {
+        let when = match self {
+            Element::Text { when, .. }
+            | Element::Markdown { when, .. }
+            | Element::Slider { when, .. }
+            | Element::Range { when, .. }
+            | Element::Check { when, .. }
+            | Element::Input { when, .. }
+            | Element::Secret { when, .. }
+            | Element::Color { when, .. }
+            | Element::Path { when, .. }
+            | Element::ConfirmPhrase { when, .. }
+            | Element::Multi { when, .. }
+            | Element::Select { when, .. }
+            | Element::Image { when, .. }
+            | Element::Code { when, .. }
+            | Element::Table { when, .. }
+            | Element::List { when, .. }
+            | Element::Kv { when, .. }
+            | Element::Tags { when, .. }
+            | Element::Rating { when, .. }
+            | Element::Matrix { when, .. }
+            | Element::Diff { when, .. }
+            | Element::Group { when, .. } => when,
+        };
+        when.as_deref()
+    }
+
+    /// The element's `span`, if any
+    pub fn span(&self) -> Option<Span> {
+        match self {
+            Element::Text { span, .. }
+            | Element::Markdown { span, .. }
+            | Element::Slider { span, .. }
+            | Element::Range { span, .. }
+            | Element::Check { span, .. }
+            | Element::Input { span, .. }
+            | Element::Secret { span, .. }
+            | Element::Color { span, .. }
+            | Element::Path { span, .. }
+            | Element::ConfirmPhrase { span, .. }
+            | Element::Multi { span, .. }
+            | Element::Select { span, .. }
+            | Element::Image { span, .. }
+            | Element::Code { span, .. }
+            | Element::Table { span, .. }
+            | Element::List { span, .. }
+            | Element::Kv { span, .. }
+            | Element::Tags { span, .. }
+            | Element::Rating { span, .. }
+            | Element::Matrix { span, .. }
+            | Element::Diff { span, .. }
+            | Element::Group { span, .. } => *span,
+        }
+    }
+
+    /// The element's id (optional for text, markdown, images, code and groups)
+    pub fn id(&self) -> Option<&str> {
+        match self {
+            Element::Text { id, .. }
+            | Element::Markdown { id, .. }
+            | Element::Image { id, .. }
+            | Element::Code { id, .. }
+            | Element::Group { id, .. } => id.as_deref(),
+            Element::Slider { id, .. }
+            | Element::Range { id, .. }
+            | Element::Check { id, .. }
+            | Element::Input { id, .. }
+            | Element::Secret { id, .. }
+            | Element::Color { id, .. }
+            | Element::Path { id, .. }
+            | Element::ConfirmPhrase { id, .. }
+            | Element::Multi { id, .. }
+            | Element::Select { id, .. }
+            | Element::Table { id, .. }
+            | Element::List { id, .. }
+            | Element::Kv { id, .. }
+            | Element::Tags { id, .. }
+            | Element::Rating { id, .. }
+            | Element::Matrix { id, .. }
+            | Element::Diff { id, .. } => Some(id),
+        }
+    }
+
+    /// The element's discriminator text (label, content for text/markdown/code, alt text for
+    /// images, label or id for diffs)
+    pub fn label(&self) -> &str {
+        match self {
+            Element::Text { text, .. } => text,
+            Element::Markdown { markdown, .. } => markdown,
+            Element::Slider { slider, .. } => slider,
+            Element::Range { range, .. } => range,
+            Element::Check { check, .. } => check,
+            Element::Input { input, .. } => input,
+            Element::Secret { secret, .. } => secret,
+            Element::Color { color, .. } => color,
+            Element::Path { path, .. } => path,
+            Element::ConfirmPhrase {
+                confirm_phrase,
+                label,
+                ..
+            } => label.as_deref().unwrap_or(confirm_phrase),
+            Element::Multi { multi, .. } => multi,
+            Element::Select { select, .. } => select,
+            Element::Image { image, alt, .. } => alt.as_deref().unwrap_or(image),
+            Element::Code { code, .. } => code,
+            Element::Table { table, .. } => table,
+            Element::List { list, .. } => list,
+            Element::Kv { kv, .. } => kv,
+            Element::Tags { tags, .. } => tags,
+            Element::Rating { rating, .. } => rating,
+            Element::Matrix { matrix, .. } => matrix,
+            Element::Diff { id, label, .. } => label.as_deref().unwrap_or(id),
+            Element::Group { group, .. } => group,
+        }
+    }
+
+    /// Nested element lists (reveals, option children, group contents), in option order.
+    /// List templates aren't included: their fields live in per-row state.
+    pub fn children(&self) -> Vec<&[Element]> {
+        match self {
+            Element::Check { reveals, .. } => vec![reveals],
+            Element::Multi {
+                options,
+                option_children,
+                reveals,
+                ..
+            }
+            | Element::Select {
+                options,
+                option_children,
+                reveals,
+                ..
+            } => {
+                let mut lists: Vec<&[Element]> = options
+                    .iter()
+                    .filter_map(|opt| option_children.get(opt.value()))
+                    .map(Vec::as_slice)
+                    .collect();
+                lists.push(reveals);
+                lists
+            }
+            Element::Group { elements, .. } => vec![elements],
+            Element::Text { .. }
+            | Element::Markdown { .. }
+            | Element::Slider { .. }
+            | Element::Range { .. }
+            | Element::Input { .. }
+            | Element::Secret { .. }
+            | Element::Color { .. }
+            | Element::Path { .. }
+            | Element::ConfirmPhrase { .. }
+            | Element::Image { .. }
+            | Element::Code { .. }
+            | Element::Table { .. }
+            | Element::List { .. }
+            | Element::Kv { .. }
+            | Element::Tags { .. }
+            | Element::Rating { .. }
+            | Element::Matrix { .. }
+            | Element::Diff { .. } => Vec::new(),
+        }
+    }
+}
+
 /// Unified value type for all widget states
 #[derive(Debug, Clone, PartialEq, JsonSchema)]
 pub enum ElementValue {
@@ -146,6 +805,24 @@ pub enum ElementValue {
     Text(String),
     MultiChoice(Vec<bool>),
     Choice(Option<usize>),
+    /// Per-hunk decisions for a diff element
+    Review(Vec<HunkReview>),
+    /// Keys of the selected table rows, in row order
+    Rows(Vec<String>),
+    /// One value map per list row, keyed by template element id
+    List(Vec<HashMap<String, ElementValue>>),
+    /// Key/value rows of a kv editor, in display order
+    Pairs(Vec<(String, String)>),
+    /// Tags entered so far, in entry order
+    Tags(Vec<String>),
+    /// Picked absolute paths, in pick order
+    Paths(Vec<String>),
+    /// Low and high ends of a range, low <= high
+    Range(f32, f32),
+    /// Chosen score, if any
+    Rating(Option<u32>),
+    /// Chosen column of each matrix row, in row order
+    Matrix(Vec<Option<usize>>),
 }
 
 /// Runtime state of the popup (v2 schema)
@@ -158,7 +835,11 @@ pub struct PopupState {
 impl PopupState {
     pub fn new(definition: &PopupDefinition) -> Self {
         let mut state = PopupState::default();
-        state.init_elements(&definition.elements);
+        let elements = definition.all_elements();
+        state.init_elements(&elements);
+        // Bad entries are reported by `initial_value_errors`; here they just
+        // keep their defaults
+        prefill::apply(&elements, &mut state.values, &definition.initial_values);
         state
     }
 
@@ -176,6 +857,17 @@ impl PopupState {
                     self.values
                         .insert(id.clone(), ElementValue::Number(default_value));
                 }
+                Element::Range {
+                    id,
+                    min,
+                    max,
+                    low,
+                    high,
+                    ..
+                } => {
+                    let band = ElementValue::Range(low.unwrap_or(*min), high.unwrap_or(*max));
+                    self.values.insert(id.clone(), band);
+                }
                 Element::Check {
                     id,
                     default,
@@ -186,22 +878,36 @@ impl PopupState {
                         .insert(id.clone(), ElementValue::Boolean(*default));
                     self.init_elements(reveals);
                 }
-                Element::Input { id, .. } => {
+                Element::Input { id, default, .. } => {
+                    let text = default.clone().unwrap_or_default();
+                    self.values.insert(id.clone(), ElementValue::Text(text));
+                }
+                Element::Secret { id, .. } | Element::ConfirmPhrase { id, .. } => {
                     self.values
                         .insert(id.clone(), ElementValue::Text(String::new()));
                 }
+                Element::Color { id, default, .. } => {
+                    let hex = default.clone().unwrap_or_default();
+                    self.values.insert(id.clone(), ElementValue::Text(hex));
+                }
+                Element::Path { id, .. } => {
+                    self.values.insert(id.clone(), ElementValue::Paths(Vec::new()));
+                }
                 Element::Multi {
                     id,
                     options,
+                    default,
                     option_children,
                     reveals,
                     ..
                 } => {
-                    self.values.insert(
-                        id.clone(),
-                        ElementValue::MultiChoice(vec![false; options.len()]),
-                    ); // options.len() still works with Vec<OptionValue>
-                       // Recurse into option-as-key children
+                    let selections = options
+                        .iter()
+                        .map(|opt| default.iter().any(|d| d == opt.value()))
+                        .collect();
+                    self.values
+                        .insert(id.clone(), ElementValue::MultiChoice(selections));
+                    // Recurse into option-as-key children
                     for children in option_children.values() {
                         self.init_elements(children);
                     }
@@ -235,6 +941,62 @@ impl PopupState {
                 Element::Markdown { .. } => {
                     // Markdown elements have no state
                 }
+                Element::Image { .. } => {
+                    // Image elements have no state
+                }
+                Element::Code {
+                    code,
+                    id: Some(id),
+                    editable: true,
+                    ..
+                } => {
+                    self.values
+                        .insert(id.clone(), ElementValue::Text(code.clone()));
+                }
+                Element::Code { .. } => {
+                    // Read-only code has no state
+                }
+                Element::Table { id, selection, .. } => {
+                    if *selection != TableSelection::None {
+                        self.values.insert(id.clone(), ElementValue::Rows(Vec::new()));
+                    }
+                }
+                Element::List {
+                    id,
+                    elements,
+                    min_items,
+                    max_items,
+                    ..
+                } => {
+                    // Start with one row to fill in, unless that breaks max_items
+                    let count = (*min_items).max(1).min(max_items.unwrap_or(usize::MAX));
+                    let rows = (0..count).map(|_| PopupState::list_row(elements)).collect();
+                    self.values.insert(id.clone(), ElementValue::List(rows));
+                }
+                Element::Kv { id, keys, .. } => {
+                    // Fixed keys get a row each; free-form starts with one blank row
+                    let pairs = if keys.is_empty() {
+                        vec![(String::new(), String::new())]
+                    } else {
+                        keys.iter().map(|k| (k.clone(), String::new())).collect()
+                    };
+                    self.values.insert(id.clone(), ElementValue::Pairs(pairs));
+                }
+                Element::Tags { id, .. } => {
+                    self.values.insert(id.clone(), ElementValue::Tags(Vec::new()));
+                }
+                Element::Rating { id, default, .. } => {
+                    self.values.insert(id.clone(), ElementValue::Rating(*default));
+                }
+                Element::Matrix { id, rows, .. } => {
+                    self.values
+                        .insert(id.clone(), ElementValue::Matrix(vec![None; rows.len()]));
+                }
+                Element::Diff { id, diff, .. } => {
+                    let files = diff::parse_unified_diff(diff);
+                    self.values
+                        .insert(id.clone(), ElementValue::Review(diff::initial_reviews(&files)));
+                }
             }
         }
     }
@@ -275,6 +1037,76 @@ impl PopupState {
         }
     }
 
+    pub fn get_review_mut(&mut self, id: &str) -> Option<&mut Vec<HunkReview>> {
+        match self.values.get_mut(id) {
+            Some(ElementValue::Review(ref mut r)) => Some(r),
+            _ => None,
+        }
+    }
+
+    pub fn get_rows_mut(&mut self, id: &str) -> Option<&mut Vec<String>> {
+        match self.values.get_mut(id) {
+            Some(ElementValue::Rows(ref mut r)) => Some(r),
+            _ => None,
+        }
+    }
+
+    pub fn get_list_mut(&mut self, id: &str) -> Option<&mut Vec<HashMap<String, ElementValue>>> {
+        match self.values.get_mut(id) {
+            Some(ElementValue::List(ref mut rows)) => Some(rows),
+            _ => None,
+        }
+    }
+
+    pub fn get_pairs_mut(&mut self, id: &str) -> Option<&mut Vec<(String, String)>> {
+        match self.values.get_mut(id) {
+            Some(ElementValue::Pairs(ref mut pairs)) => Some(pairs),
+            _ => None,
+        }
+    }
+
+    pub fn get_tags_mut(&mut self, id: &str) -> Option<&mut Vec<String>> {
+        match self.values.get_mut(id) {
+            Some(ElementValue::Tags(ref mut tags)) => Some(tags),
+            _ => None,
+        }
+    }
+
+    pub fn get_range_mut(&mut self, id: &str) -> Option<(&mut f32, &mut f32)> {
+        match self.values.get_mut(id) {
+            Some(ElementValue::Range(ref mut low, ref mut high)) => Some((low, high)),
+            _ => None,
+        }
+    }
+
+    pub fn get_paths_mut(&mut self, id: &str) -> Option<&mut Vec<String>> {
+        match self.values.get_mut(id) {
+            Some(ElementValue::Paths(ref mut paths)) => Some(paths),
+            _ => None,
+        }
+    }
+
+    pub fn get_rating_mut(&mut self, id: &str) -> Option<&mut Option<u32>> {
+        match self.values.get_mut(id) {
+            Some(ElementValue::Rating(ref mut score)) => Some(score),
+            _ => None,
+        }
+    }
+
+    pub fn get_matrix_mut(&mut self, id: &str) -> Option<&mut Vec<Option<usize>>> {
+        match self.values.get_mut(id) {
+            Some(ElementValue::Matrix(ref mut picks)) => Some(picks),
+            _ => None,
+        }
+    }
+
+    /// Fresh state for a new row of a list with the given template
+    pub fn list_row(template: &[Element]) -> HashMap<String, ElementValue> {
+        let mut row = PopupState::default();
+        row.init_elements(template);
+        row.values
+    }
+
     // Const accessors for condition evaluation
     pub fn get_boolean(&self, id: &str) -> bool {
         match self.values.get(id) {
@@ -322,6 +1154,9 @@ impl PopupState {
                     Element::Input { id, .. } if id == target_id => return Some(element),
                     Element::Multi { id, .. } if id == target_id => return Some(element),
                     Element::Select { id, .. } if id == target_id => return Some(element),
+                    Element::List { id, .. } if id == target_id => return Some(element),
+                    Element::Matrix { id, .. } if id == target_id => return Some(element),
+                    Element::Path { id, .. } if id == target_id => return Some(element),
                     Element::Group { id: Some(id), .. } if id == target_id => return Some(element),
 
                     // Recurse into nested structures
@@ -411,11 +1246,174 @@ impl PopupState {
                             json!(idx)
                         }
                     }
+                    ElementValue::Review(reviews) => diff::reviews_to_json(reviews),
+                    ElementValue::Rows(keys) => json!(keys),
+                    ElementValue::List(rows) => {
+                        // Each row's values, resolved against the list's template
+                        let template = match find_element_by_id(elements, id) {
+                            Some(Element::List { elements, .. }) => elements.as_slice(),
+                            _ => &[],
+                        };
+                        let rows: Vec<Value> = rows
+                            .iter()
+                            .map(|row| {
+                                let row_state = PopupState {
+                                    values: row.clone(),
+                                    button_clicked: None,
+                                };
+                                json!(row_state.to_value_map(template))
+                            })
+                            .collect();
+                        json!(rows)
+                    }
+                    ElementValue::Pairs(pairs) => pairs_to_json(pairs),
+                    ElementValue::Tags(tags) => json!(tags),
+                    ElementValue::Paths(paths) => match find_element_by_id(elements, id) {
+                        Some(Element::Path { multiple, .. }) => paths_to_json(paths, *multiple),
+                        _ => json!(paths),
+                    },
+                    ElementValue::Range(low, high) => range_to_json(*low, *high),
+                    ElementValue::Rating(score) => json!(score),
+                    ElementValue::Matrix(picks) => match find_element_by_id(elements, id) {
+                        Some(Element::Matrix { rows, columns, .. }) => {
+                            matrix_to_json(picks, rows, columns)
+                        }
+                        _ => json!(picks),
+                    },
                 };
                 (id.clone(), json_val)
             })
             .collect()
     }
+
+    /// Values to evaluate `when` clauses against, honoring the hidden-field mode.
+    ///
+    /// With `HiddenFields::Unset`, fields that aren't currently shown (failed
+    /// `when`, unchecked reveals, unselected option children) are left out, so
+    /// they read as null. Hiding a field can change other conditions, so this
+    /// iterates to a fixpoint, capped in case conditions oscillate.
+    pub fn condition_values(
+        &self,
+        elements: &[Element],
+        mode: HiddenFields,
+    ) -> HashMap<String, Value> {
+        let all_values = self.to_value_map(elements);
+        if mode == HiddenFields::Keep {
+            return all_values;
+        }
+
+        let mut values = all_values.clone();
+        for _ in 0..MAX_VISIBILITY_PASSES {
+            let active = self.active_element_ids(elements, &values);
+            let next: HashMap<String, Value> = all_values
+                .iter()
+                .filter(|(id, _)| active.contains(id))
+                .map(|(id, value)| (id.clone(), value.clone()))
+                .collect();
+            if next == values {
+                break;
+            }
+            values = next;
+        }
+        values
+    }
+
+    /// Ids of the elements currently shown: `when` clauses pass against `values`,
+    /// checkboxes are checked for their reveals, and options are selected for
+    /// their children. Unparseable `when` clauses count as shown.
+    pub fn active_element_ids(
+        &self,
+        elements: &[Element],
+        values: &HashMap<String, Value>,
+    ) -> Vec<String> {
+        let mut active_ids = Vec::new();
+        self.collect_active_ids(elements, values, &mut active_ids);
+        active_ids
+    }
+
+    fn collect_active_ids(
+        &self,
+        elements: &[Element],
+        values: &HashMap<String, Value>,
+        active_ids: &mut Vec<String>,
+    ) {
+        for element in elements {
+            if let Some(when) = element.when() {
+                if !condition::evaluate_when(when, values) {
+                    continue;
+                }
+            }
+            // Groups are containers, not fields; only their contents are reported
+            if let Some(id) = element
+                .id()
+                .filter(|_| !matches!(element, Element::Group { .. }))
+            {
+                active_ids.push(id.to_string());
+            }
+
+            match element {
+                Element::Check { id, reveals, .. } => {
+                    if self.get_boolean(id) {
+                        self.collect_active_ids(reveals, values, active_ids);
+                    }
+                }
+                Element::Multi {
+                    id,
+                    options,
+                    option_children,
+                    reveals,
+                    ..
+                } => {
+                    let selections = self.get_multichoice(id).cloned().unwrap_or_default();
+                    for (option, _) in options.iter().zip(&selections).filter(|(_, &on)| on) {
+                        if let Some(children) = option_children.get(option.value()) {
+                            self.collect_active_ids(children, values, active_ids);
+                        }
+                    }
+                    // Reveals show once any option is selected
+                    if selections.contains(&true) {
+                        self.collect_active_ids(reveals, values, active_ids);
+                    }
+                }
+                Element::Select {
+                    id,
+                    options,
+                    option_children,
+                    reveals,
+                    ..
+                } => {
+                    if let Some(Some(idx)) = self.get_choice(id) {
+                        let selected = options.get(idx);
+                        if let Some(children) = selected.and_then(|o| option_children.get(o.value())) {
+                            self.collect_active_ids(children, values, active_ids);
+                        }
+                        self.collect_active_ids(reveals, values, active_ids);
+                    }
+                }
+                Element::Group { elements, .. } => {
+                    self.collect_active_ids(elements, values, active_ids);
+                }
+                Element::Text { .. }
+                | Element::Markdown { .. }
+                | Element::Slider { .. }
+                | Element::Range { .. }
+                | Element::Input { .. }
+                | Element::Secret { .. }
+                | Element::Color { .. }
+                | Element::Path { .. }
+                | Element::ConfirmPhrase { .. }
+                | Element::Image { .. }
+                | Element::Code { .. }
+                | Element::Table { .. }
+                | Element::List { .. }
+                | Element::Kv { .. }
+                | Element::Tags { .. }
+                | Element::Rating { .. }
+                | Element::Matrix { .. }
+                | Element::Diff { .. } => {}
+            }
+        }
+    }
 }
 
 /// Result that gets serialized to JSON
@@ -436,28 +1434,23 @@ pub enum PopupResult {
 
 impl PopupResult {
     pub fn from_state(state: &PopupState) -> Self {
-        use serde_json::json;
+        PopupResult::Completed {
+            values: plain_values(&state.values),
+            button: state
+                .button_clicked
+                .clone()
+                .unwrap_or_else(|| "cancel".to_string()),
+        }
+    }
 
+    pub fn from_state_with_context(state: &PopupState, definition: &PopupDefinition) -> Self {
+        let all_elements = definition.all_elements();
         let values = state
             .values
             .iter()
             .filter_map(|(id, value)| {
-                let json_value = match value {
-                    ElementValue::Number(n) => json!(*n as i32),
-                    ElementValue::Boolean(b) => json!(*b),
-                    ElementValue::Text(s) if !s.is_empty() => json!(s),
-                    ElementValue::MultiChoice(selections) => {
-                        let indices: Vec<usize> = selections
-                            .iter()
-                            .enumerate()
-                            .filter_map(|(i, &selected)| selected.then_some(i))
-                            .collect();
-                        json!(indices)
-                    }
-                    ElementValue::Choice(Some(idx)) => json!(*idx),
-                    _ => return None, // Skip empty text, unselected choice
-                };
-                Some((id.clone(), json_value))
+                let element = find_value_element(&all_elements, id);
+                value_to_json(value, element).map(|json| (id.clone(), json))
             })
             .collect();
 
@@ -470,107 +1463,22 @@ impl PopupResult {
         }
     }
 
-    pub fn from_state_with_context(state: &PopupState, definition: &PopupDefinition) -> Self {
-        use serde_json::json;
-
-        // Helper to find element by ID recursively
-        fn find_element_by_id<'a>(elements: &'a [Element], id: &str) -> Option<&'a Element> {
-            for element in elements {
-                match element {
-                    e @ Element::Slider { id: eid, .. } if eid == id => return Some(e),
-                    e @ Element::Check { id: eid, .. } if eid == id => return Some(e),
-                    e @ Element::Input { id: eid, .. } if eid == id => return Some(e),
-                    e @ Element::Multi { id: eid, .. } if eid == id => return Some(e),
-                    e @ Element::Select { id: eid, .. } if eid == id => return Some(e),
-                    Element::Group {
-                        elements: children, ..
-                    } => {
-                        if let Some(e) = find_element_by_id(children, id) {
-                            return Some(e);
-                        }
-                    }
-                    // Search in reveals for Checkbox
-                    Element::Check { reveals, .. } => {
-                        if !reveals.is_empty() {
-                            if let Some(e) = find_element_by_id(reveals, id) {
-                                return Some(e);
-                            }
-                        }
-                    }
-                    Element::Slider { .. } | Element::Input { .. } => {
-                        // Slider and Input have no reveals or option_children
-                    }
-                    // Search in both reveals and option_children for Multiselect and Choice
-                    Element::Multi {
-                        reveals,
-                        option_children,
-                        ..
-                    }
-                    | Element::Select {
-                        reveals,
-                        option_children,
-                        ..
-                    } => {
-                        // Search reveals first
-                        if !reveals.is_empty() {
-                            if let Some(e) = find_element_by_id(reveals, id) {
-                                return Some(e);
-                            }
-                        }
-                        // Then search option_children
-                        if !option_children.is_empty() {
-                            for child_elements in option_children.values() {
-                                if let Some(e) = find_element_by_id(child_elements, id) {
-                                    return Some(e);
-                                }
-                            }
-                        }
-                    }
-                    _ => {}
-                }
-            }
-            None
-        }
-
-        let mut values = HashMap::new();
-
-        for (id, value) in &state.values {
-            let element = find_element_by_id(&definition.elements, id);
-
-            let json_value = match (value, element) {
-                (ElementValue::Number(n), Some(Element::Slider { .. })) => {
-                    json!(*n as i32)
-                }
-                (ElementValue::Boolean(b), _) => json!(*b),
-                (ElementValue::Text(s), _) if !s.is_empty() => json!(s),
-                (ElementValue::MultiChoice(selections), Some(Element::Multi { options, .. })) => {
-                    let selected: Vec<&str> = selections
-                        .iter()
-                        .enumerate()
-                        .filter_map(|(i, &sel)| sel.then_some(options.get(i).map(|o| o.value())))
-                        .flatten()
-                        .collect();
-                    json!(selected)
-                }
-                (ElementValue::Choice(Some(idx)), Some(Element::Select { options, .. })) => options
-                    .get(*idx)
-                    .map(|opt| json!(opt.value()))
-                    .unwrap_or(json!(null)),
-                (ElementValue::Choice(None), _) => continue,
-                (ElementValue::Number(n), _) => json!(*n as i32),
-                (ElementValue::MultiChoice(selections), _) => {
-                    let indices: Vec<usize> = selections
-                        .iter()
-                        .enumerate()
-                        .filter_map(|(i, &selected)| selected.then_some(i))
-                        .collect();
-                    json!(indices)
-                }
-                _ => continue,
-            };
-
-            values.insert(id.clone(), json_value);
-        }
+    pub fn from_state_with_active_elements(
+        state: &PopupState,
+        definition: &PopupDefinition,
+        active_ids: &[String],
+    ) -> Self {
+        let all_elements = definition.all_elements();
+        let values = state
+            .values
+            .iter()
+            // Skip values whose elements aren't shown
+            .filter(|(id, _)| active_ids.contains(id))
+            .filter_map(|(id, value)| {
+                let element = find_value_element(&all_elements, id);
+                value_to_json(value, element).map(|json| (id.clone(), json))
+            })
+            .collect();
 
         PopupResult::Completed {
             values,
@@ -581,104 +1489,30 @@ impl PopupResult {
         }
     }
 
-    pub fn from_state_with_active_elements(
-        state: &PopupState,
-        definition: &PopupDefinition,
-        active_ids: &[String],
-    ) -> Self {
-        use serde_json::json;
-
-        // Helper to find element by ID recursively (reuse from from_state_with_context)
-        fn find_element_by_id<'a>(elements: &'a [Element], id: &str) -> Option<&'a Element> {
-            for element in elements {
-                match element {
-                    e @ Element::Slider { id: eid, .. } if eid == id => return Some(e),
-                    e @ Element::Check { id: eid, .. } if eid == id => return Some(e),
-                    e @ Element::Input { id: eid, .. } if eid == id => return Some(e),
-                    e @ Element::Multi { id: eid, .. } if eid == id => return Some(e),
-                    e @ Element::Select { id: eid, .. } if eid == id => return Some(e),
-                    Element::Group {
-                        elements: children, ..
-                    } => {
-                        if let Some(e) = find_element_by_id(children, id) {
-                            return Some(e);
-                        }
-                    }
-                    // Search in reveals for Checkbox
-                    Element::Check { reveals, .. } => {
-                        if !reveals.is_empty() {
-                            if let Some(e) = find_element_by_id(reveals, id) {
-                                return Some(e);
-                            }
-                        }
-                    }
-                    Element::Slider { .. } | Element::Input { .. } => {
-                        // Slider and Input have no reveals or option_children
-                    }
-                    // Search in both reveals and option_children for Multiselect and Choice
-                    Element::Multi {
-                        reveals,
-                        option_children,
-                        ..
-                    }
-                    | Element::Select {
-                        reveals,
-                        option_children,
-                        ..
-                    } => {
-                        // Search reveals first
-                        if !reveals.is_empty() {
-                            if let Some(e) = find_element_by_id(reveals, id) {
-                                return Some(e);
-                            }
-                        }
-                        // Then search option_children
-                        if !option_children.is_empty() {
-                            for child_elements in option_children.values() {
-                                if let Some(e) = find_element_by_id(child_elements, id) {
-                                    return Some(e);
-                                }
-                            }
-                        }
-                    }
-                    _ => {}
-                }
-            }
-            None
+    /// Swap the values of `to_file` secret inputs for `{"file": path}`, writing each
+    /// value to a temp file only the current user can read
+    pub fn move_secrets_to_files(&mut self, definition: &PopupDefinition) {
+        if let PopupResult::Completed { values, .. } = self {
+            let mut map: serde_json::Map<String, Value> =
+                std::mem::take(values).into_iter().collect();
+            secret::deliver_to_files(&definition.all_elements(), &mut map);
+            *values = map.into_iter().collect();
         }
+    }
+}
 
-        let mut values = HashMap::new();
-
-        for (id, value) in &state.values {
-            // Skip this value if it's not in the active elements
-            if !active_ids.contains(id) {
-                continue;
-            }
-
-            let element = find_element_by_id(&definition.elements, id);
+/// Result values without element context: choices come back as indices
+fn plain_values(values: &HashMap<String, ElementValue>) -> HashMap<String, Value> {
+    use serde_json::json;
 
-            let json_value = match (value, element) {
-                (ElementValue::Number(n), Some(Element::Slider { .. })) => {
-                    json!(*n as i32)
-                }
-                (ElementValue::Boolean(b), _) => json!(*b),
-                (ElementValue::Text(s), _) if !s.is_empty() => json!(s),
-                (ElementValue::MultiChoice(selections), Some(Element::Multi { options, .. })) => {
-                    let selected: Vec<&str> = selections
-                        .iter()
-                        .enumerate()
-                        .filter_map(|(i, &sel)| sel.then_some(options.get(i).map(|o| o.value())))
-                        .flatten()
-                        .collect();
-                    json!(selected)
-                }
-                (ElementValue::Choice(Some(idx)), Some(Element::Select { options, .. })) => options
-                    .get(*idx)
-                    .map(|opt| json!(opt.value()))
-                    .unwrap_or(json!(null)),
-                (ElementValue::Choice(None), _) => continue,
-                (ElementValue::Number(n), _) => json!(*n as i32),
-                (ElementValue::MultiChoice(selections), _) => {
+    values
+        .iter()
+        .filter_map(|(id, value)| {
+            let json_value = match value {
+                ElementValue::Number(n) => json!(*n as i32),
+                ElementValue::Boolean(b) => json!(*b),
+                ElementValue::Text(s) if !s.is_empty() => json!(s),
+                ElementValue::MultiChoice(selections) => {
                     let indices: Vec<usize> = selections
                         .iter()
                         .enumerate()
@@ -686,18 +1520,185 @@ impl PopupResult {
                         .collect();
                     json!(indices)
                 }
-                _ => continue,
+                ElementValue::Choice(Some(idx)) => json!(*idx),
+                ElementValue::Review(reviews) => diff::reviews_to_json(reviews),
+                ElementValue::Rows(keys) => json!(keys),
+                ElementValue::List(rows) => {
+                    json!(rows.iter().map(plain_values).collect::<Vec<_>>())
+                }
+                ElementValue::Pairs(pairs) => pairs_to_json(pairs),
+                ElementValue::Tags(tags) => json!(tags),
+                ElementValue::Paths(paths) if !paths.is_empty() => json!(paths),
+                ElementValue::Range(low, high) => range_to_json(*low, *high),
+                ElementValue::Rating(Some(score)) => json!(score),
+                ElementValue::Matrix(picks) => json!(picks),
+                _ => return None, // Skip empty text, unselected choice
             };
+            Some((id.clone(), json_value))
+        })
+        .collect()
+}
 
-            values.insert(id.clone(), json_value);
+/// Element owning the value stored under `id`, searching nested elements.
+/// List templates are not searched: their values live in the list's rows.
+fn find_value_element<'a>(elements: &'a [Element], id: &str) -> Option<&'a Element> {
+    elements.iter().find_map(|element| match element {
+        Element::Slider { id: eid, .. }
+        | Element::Range { id: eid, .. }
+        | Element::Check { id: eid, .. }
+        | Element::Input { id: eid, .. }
+        | Element::Secret { id: eid, .. }
+        | Element::Color { id: eid, .. }
+        | Element::Path { id: eid, .. }
+        | Element::ConfirmPhrase { id: eid, .. }
+        | Element::Multi { id: eid, .. }
+        | Element::Select { id: eid, .. }
+        | Element::List { id: eid, .. }
+        | Element::Matrix { id: eid, .. }
+            if eid == id =>
+        {
+            Some(element)
         }
+        _ => element
+            .children()
+            .into_iter()
+            .find_map(|children| find_value_element(children, id)),
+    })
+}
 
-        PopupResult::Completed {
-            values,
-            button: state
-                .button_clicked
-                .clone()
-                .unwrap_or_else(|| "cancel".to_string()),
+/// Result JSON for one value, or None to leave it out (empty text, no choice)
+fn value_to_json(value: &ElementValue, element: Option<&Element>) -> Option<Value> {
+    use serde_json::json;
+
+    let json_value = match (value, element) {
+        (ElementValue::Number(n), Some(Element::Slider { .. })) => {
+            json!(*n as i32)
+        }
+        (ElementValue::Boolean(b), _) => json!(*b),
+        // Whether the phrase was typed, not the typing itself
+        (
+            ElementValue::Text(s),
+            Some(Element::ConfirmPhrase {
+                confirm_phrase,
+                case_sensitive,
+                ..
+            }),
+        ) => json!(validation::phrase_matches(s, confirm_phrase, *case_sensitive)),
+        (ElementValue::Text(s), _) if !s.is_empty() => json!(s),
+        (ElementValue::MultiChoice(selections), Some(Element::Multi { options, .. })) => {
+            let selected: Vec<&str> = selections
+                .iter()
+                .enumerate()
+                .filter_map(|(i, &sel)| sel.then_some(options.get(i).map(|o| o.value())))
+                .flatten()
+                .collect();
+            json!(selected)
+        }
+        (ElementValue::Choice(Some(idx)), Some(Element::Select { options, .. })) => options
+            .get(*idx)
+            .map(|opt| json!(opt.value()))
+            .unwrap_or(json!(null)),
+        (ElementValue::Choice(None), _) => return None,
+        (ElementValue::Review(reviews), _) => diff::reviews_to_json(reviews),
+        (ElementValue::Rows(keys), _) => json!(keys),
+        (ElementValue::List(rows), Some(Element::List { elements, .. })) => {
+            list_rows_to_json(rows, elements)
+        }
+        (ElementValue::List(rows), _) => json!(rows.iter().map(plain_values).collect::<Vec<_>>()),
+        (ElementValue::Pairs(pairs), _) => pairs_to_json(pairs),
+        (ElementValue::Tags(tags), _) => json!(tags),
+        (ElementValue::Paths(paths), _) if paths.is_empty() => return None,
+        (ElementValue::Paths(paths), Some(Element::Path { multiple, .. })) => {
+            paths_to_json(paths, *multiple)
+        }
+        (ElementValue::Paths(paths), _) => json!(paths),
+        (ElementValue::Range(low, high), _) => range_to_json(*low, *high),
+        (ElementValue::Rating(Some(score)), _) => json!(score),
+        (ElementValue::Matrix(picks), Some(Element::Matrix { rows, columns, .. })) => {
+            matrix_to_json(picks, rows, columns)
         }
+        (ElementValue::Matrix(picks), _) => json!(picks),
+        (ElementValue::Number(n), _) => json!(*n as i32),
+        (ElementValue::MultiChoice(selections), _) => {
+            let indices: Vec<usize> = selections
+                .iter()
+                .enumerate()
+                .filter_map(|(i, &selected)| selected.then_some(i))
+                .collect();
+            json!(indices)
+        }
+        _ => return None,
+    };
+    Some(json_value)
+}
+
+/// Range result: `{"low": x, "high": y}`, whole numbers without a fraction
+fn range_to_json(low: f32, high: f32) -> Value {
+    // f32 steps like 0.1 drift; four places is plenty for a slider
+    let number = |x: f32| {
+        let x = (x as f64 * 1e4).round() / 1e4;
+        if x.fract() == 0.0 {
+            Value::from(x as i64)
+        } else {
+            Value::from(x)
+        }
+    };
+    let mut object = serde_json::Map::new();
+    object.insert("low".to_string(), number(low));
+    object.insert("high".to_string(), number(high));
+    Value::Object(object)
+}
+
+/// Path picker result: an array with `multiple`, else the one path (or null)
+fn paths_to_json(paths: &[String], multiple: bool) -> Value {
+    if multiple {
+        Value::from(paths.to_vec())
+    } else {
+        paths.first().map_or(Value::Null, |p| Value::String(p.clone()))
     }
 }
+
+/// Matrix result: the chosen column of each answered row, keyed by row id
+fn matrix_to_json(picks: &[Option<usize>], rows: &[MatrixRow], columns: &[String]) -> Value {
+    let object: serde_json::Map<String, Value> = rows
+        .iter()
+        .zip(picks)
+        .filter_map(|(row, pick)| {
+            let column = columns.get((*pick)?)?;
+            Some((row.id.clone(), Value::String(column.clone())))
+        })
+        .collect();
+    Value::Object(object)
+}
+
+/// Kv editor result: rows with an empty key are left out
+fn pairs_to_json(pairs: &[(String, String)]) -> Value {
+    let object: serde_json::Map<String, Value> = pairs
+        .iter()
+        .filter(|(key, _)| !key.trim().is_empty())
+        .map(|(key, value)| (key.trim().to_string(), Value::String(value.clone())))
+        .collect();
+    Value::Object(object)
+}
+
+/// One object per list row, holding the row's shown fields
+fn list_rows_to_json(rows: &[HashMap<String, ElementValue>], template: &[Element]) -> Value {
+    rows.iter()
+        .map(|row| {
+            let row_state = PopupState {
+                values: row.clone(),
+                button_clicked: None,
+            };
+            let active = row_state.active_element_ids(template, &row_state.to_value_map(template));
+            let fields: serde_json::Map<String, Value> = row
+                .iter()
+                .filter(|(id, _)| active.contains(id))
+                .filter_map(|(id, value)| {
+                    value_to_json(value, find_value_element(template, id))
+                        .map(|json| (id.clone(), json))
+                })
+                .collect();
+            Value::Object(fields)
+        })
+        .collect()
+}
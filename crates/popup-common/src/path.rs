@@ -0,0 +1,158 @@
+//! Root resolution, glob filters and existence checks for the `path` picker
+
+use std::io;
+use std::path::{Path, PathBuf};
+
+use crate::PathMode;
+
+/// Absolute, canonical root of a picker: `root` with a leading `~` expanded,
+/// or the working directory when unset
+pub fn resolve_root(root: Option<&str>) -> io::Result<PathBuf> {
+    let dir = match root {
+        Some(root) => match root.strip_prefix('~') {
+            Some(rest) if rest.is_empty() || rest.starts_with('/') => {
+                let home = std::env::var_os("HOME")
+                    .ok_or_else(|| io::Error::new(io::ErrorKind::NotFound, "HOME is not set"))?;
+                PathBuf::from(home).join(rest.trim_start_matches('/'))
+            }
+            _ => PathBuf::from(root),
+        },
+        None => std::env::current_dir()?,
+    };
+    let dir = dir.canonicalize()?;
+    if !dir.is_dir() {
+        return Err(io::Error::new(
+            io::ErrorKind::NotADirectory,
+            format!("{} is not a directory", dir.display()),
+        ));
+    }
+    Ok(dir)
+}
+
+/// Whether a file name passes the filters; no filters let everything through
+pub fn matches_filters(filters: &[String], name: &str) -> bool {
+    filters.is_empty() || filters.iter().any(|f| glob_matches(f, name))
+}
+
+/// Case-insensitive glob match of a whole file name: `*` is any run of
+/// characters, `?` any one character
+pub fn glob_matches(pattern: &str, name: &str) -> bool {
+    let pattern: Vec<char> = pattern.to_lowercase().chars().collect();
+    let name: Vec<char> = name.to_lowercase().chars().collect();
+
+    // Greedy match, backtracking to the last `*` on a mismatch
+    let (mut p, mut n) = (0, 0);
+    let mut star: Option<(usize, usize)> = None;
+    while n < name.len() {
+        match pattern.get(p) {
+            Some('*') => {
+                star = Some((p, n));
+                p += 1;
+            }
+            Some(&c) if c == '?' || c == name[n] => {
+                p += 1;
+                n += 1;
+            }
+            _ => match star {
+                Some((sp, sn)) => {
+                    p = sp + 1;
+                    n = sn + 1;
+                    star = Some((sp, sn + 1));
+                }
+                None => return false,
+            },
+        }
+    }
+    pattern[p..].iter().all(|&c| c == '*')
+}
+
+/// Whether a picked path still exists, resolves inside the picker's `root` (see
+/// `resolve_root`), is the kind the mode asks for, and (for files) passes the
+/// filters
+pub fn check_path(
+    path: &str,
+    root: Option<&str>,
+    mode: PathMode,
+    filters: &[String],
+) -> Result<(), String> {
+    let p = Path::new(path);
+    if !p.is_absolute() {
+        return Err(format!("`{}` is not an absolute path", path));
+    }
+    let meta = p
+        .metadata()
+        .map_err(|_| format!("`{}` does not exist", path))?;
+    // Symlinks are followed, so a link can't lead out of the root either
+    let root = resolve_root(root).map_err(|e| format!("can't open the picker's root: {}", e))?;
+    let canonical = p
+        .canonicalize()
+        .map_err(|e| format!("can't resolve `{}`: {}", path, e))?;
+    if !canonical.starts_with(&root) {
+        return Err(format!("`{}` is outside {}", path, root.display()));
+    }
+    match mode {
+        PathMode::File if !meta.is_file() => Err(format!("`{}` is not a file", path)),
+        PathMode::Dir if !meta.is_dir() => Err(format!("`{}` is not a directory", path)),
+        _ if meta.is_file() => {
+            let name = p
+                .file_name()
+                .map(|n| n.to_string_lossy())
+                .unwrap_or_default();
+            if matches_filters(filters, &name) {
+                Ok(())
+            } else {
+                Err(format!("`{}` doesn't match {}", name, filters.join(", ")))
+            }
+        }
+        _ => Ok(()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_glob_matches() {
+        assert!(glob_matches("*.rs", "main.rs"));
+        assert!(glob_matches("*.RS", "lib.rs"));
+        assert!(glob_matches("Cargo.*", "Cargo.toml"));
+        assert!(glob_matches("?.txt", "a.txt"));
+        assert!(glob_matches("*test*", "json_parser_tests.rs"));
+        assert!(glob_matches("*", ""));
+        assert!(!glob_matches("*.rs", "main.rs.bak"));
+        assert!(!glob_matches("?.txt", "ab.txt"));
+        assert!(!glob_matches("a*b*c", "abca"));
+    }
+
+    #[test]
+    fn test_check_path() {
+        let root = Some(concat!(env!("CARGO_MANIFEST_DIR"), "/src"));
+        let dir = resolve_root(root).unwrap();
+        let lib = dir.join("lib.rs").display().to_string();
+        let grammar = dir.join("condition.pest").display().to_string();
+        let tests = dir.join("tests").display().to_string();
+        let rs = ["*.rs".to_string()];
+
+        assert!(check_path(&lib, root, PathMode::File, &[]).is_ok());
+        assert!(check_path(&lib, root, PathMode::Dir, &[]).is_err());
+        assert!(check_path(&tests, root, PathMode::Dir, &[]).is_ok());
+        assert!(check_path(&tests, root, PathMode::Both, &rs).is_ok());
+        assert!(check_path(&grammar, root, PathMode::Both, &rs).is_err());
+        assert!(check_path("lib.rs", root, PathMode::File, &[]).is_err());
+        let missing = dir.join("missing.rs").display().to_string();
+        assert!(check_path(&missing, root, PathMode::File, &[]).is_err());
+    }
+
+    #[test]
+    fn test_check_path_stays_inside_root() {
+        let root = Some(concat!(env!("CARGO_MANIFEST_DIR"), "/src"));
+        let dir = resolve_root(root).unwrap();
+        // Existing files above the root, directly or through `..`
+        let manifest = dir.parent().unwrap().join("Cargo.toml");
+        let err = check_path(&manifest.display().to_string(), root, PathMode::File, &[]);
+        assert!(err.unwrap_err().contains("is outside"));
+        let sneaky = dir.join("../Cargo.toml").display().to_string();
+        assert!(check_path(&sneaky, root, PathMode::File, &[]).is_err());
+    }
+}
@@ -0,0 +1,303 @@
+//! Seeding popup state from `initial_values` or the CLI's `--prefill` file
+//!
+//! Values use the result format, so a previous result can be fed back in:
+//! option text for select and multi, `{"low", "high"}` for ranges, row ids to
+//! column text for matrices, and so on. Option indices are accepted too.
+//!
+//! Answers that must be given afresh (secrets, confirmation phrases and diff
+//! reviews) are never prefilled: their entries are accepted and dropped, so
+//! secrets don't end up in popup state and a past result still applies.
+
+use std::collections::HashMap;
+
+use serde_json::Value;
+
+use crate::{path, table, validation, Element, ElementValue, PopupState, TableSelection};
+
+/// Keys a result carries besides field values
+const RESULT_KEYS: [&str; 2] = ["status", "button"];
+
+/// Overwrite `values` with `initial`, keyed by element id. Returns one message
+/// per id that isn't a field of `elements` or whose value doesn't fit it; those
+/// ids keep their defaults.
+pub(crate) fn apply(
+    elements: &[Element],
+    values: &mut HashMap<String, ElementValue>,
+    initial: &HashMap<String, Value>,
+) -> Vec<String> {
+    let mut ids: Vec<&String> = initial.keys().collect();
+    ids.sort();
+
+    let mut errors = Vec::new();
+    for id in ids {
+        let element = find_element(elements, id).filter(|_| values.contains_key(id.as_str()));
+        let element = match element {
+            Some(element) => element,
+            None if RESULT_KEYS.contains(&id.as_str()) => continue,
+            None => {
+                errors.push(format!("`{}` is not a field of this popup", id));
+                continue;
+            }
+        };
+        if matches!(
+            element,
+            Element::Secret { .. } | Element::ConfirmPhrase { .. } | Element::Diff { .. }
+        ) {
+            continue;
+        }
+        match convert(element, &initial[id]) {
+            Ok(value) => {
+                values.insert(id.clone(), value);
+            }
+            Err(e) => errors.push(format!("`{}`: {}", id, e)),
+        }
+    }
+    errors
+}
+
+/// Element with this id, searching nested elements but not list templates
+fn find_element<'a>(elements: &'a [Element], id: &str) -> Option<&'a Element> {
+    elements.iter().find_map(|element| {
+        if element.id() == Some(id) {
+            return Some(element);
+        }
+        element
+            .children()
+            .into_iter()
+            .find_map(|children| find_element(children, id))
+    })
+}
+
+/// State value for `element` from a value in result format
+fn convert(element: &Element, value: &Value) -> Result<ElementValue, String> {
+    match element {
+        Element::Slider { min, max, .. } => {
+            let n = number(value)?;
+            if n < *min || n > *max {
+                return Err(format!("{} is outside {}..{}", n, min, max));
+            }
+            Ok(ElementValue::Number(n))
+        }
+        Element::Range { min, max, .. } => {
+            let end = |key: &str| match value.get(key) {
+                Some(v) => number(v),
+                None => Err(expected("an object with low and high", value)),
+            };
+            let (low, high) = (end("low")?, end("high")?);
+            if low > high || low < *min || high > *max {
+                return Err(format!(
+                    "needs {} <= low <= high <= {}, got low {} and high {}",
+                    min, max, low, high
+                ));
+            }
+            Ok(ElementValue::Range(low, high))
+        }
+        Element::Check { .. } => value
+            .as_bool()
+            .map(ElementValue::Boolean)
+            .ok_or_else(|| expected("true or false", value)),
+        Element::Input { .. } | Element::Code { .. } => {
+            Ok(ElementValue::Text(string(value)?.to_string()))
+        }
+        Element::Color { alpha, .. } => {
+            let rgba = validation::parse_hex_color(string(value)?)
+                .ok_or_else(|| expected("a hex color like \"#3366ff\"", value))?;
+            Ok(ElementValue::Text(validation::format_hex_color(
+                rgba, *alpha,
+            )))
+        }
+        Element::Select { options, .. } => match value {
+            Value::Null => Ok(ElementValue::Choice(None)),
+            _ => {
+                let values: Vec<&str> = options.iter().map(|o| o.value()).collect();
+                Ok(ElementValue::Choice(Some(option_index(&values, value)?)))
+            }
+        },
+        Element::Multi { options, .. } => {
+            let values: Vec<&str> = options.iter().map(|o| o.value()).collect();
+            let mut selections = vec![false; options.len()];
+            for item in array(value)? {
+                selections[option_index(&values, item)?] = true;
+            }
+            Ok(ElementValue::MultiChoice(selections))
+        }
+        Element::Table {
+            columns,
+            rows,
+            key,
+            selection,
+            ..
+        } => {
+            let picked: Vec<&Value> = match value {
+                Value::String(_) => vec![value],
+                _ => array(value)?.iter().collect(),
+            };
+            if *selection == TableSelection::Single && picked.len() > 1 {
+                return Err("pick at most one row".to_string());
+            }
+            let keys = table::row_keys(columns, rows, key.as_deref());
+            let mut selected = Vec::new();
+            for item in picked {
+                let k = string(item)?;
+                if !keys.iter().any(|row_key| row_key == k) {
+                    return Err(format!("`{}` is not a row of the table", k));
+                }
+                selected.push(k.to_string());
+            }
+            Ok(ElementValue::Rows(selected))
+        }
+        Element::List {
+            elements: template,
+            max_items,
+            ..
+        } => {
+            let items = array(value)?;
+            if let Some(max) = max_items.filter(|max| items.len() > *max) {
+                return Err(format!("at most {} rows allowed", max));
+            }
+            let mut rows = Vec::new();
+            for (i, item) in items.iter().enumerate() {
+                let Value::Object(fields) = item else {
+                    return Err(format!("row {}: {}", i, expected("an object", item)));
+                };
+                let fields: HashMap<String, Value> =
+                    fields.iter().map(|(k, v)| (k.clone(), v.clone())).collect();
+                let mut row = PopupState::list_row(template);
+                let row_errors = apply(template, &mut row, &fields);
+                if let Some(e) = row_errors.first() {
+                    return Err(format!("row {}: {}", i, e));
+                }
+                rows.push(row);
+            }
+            Ok(ElementValue::List(rows))
+        }
+        Element::Kv { keys, .. } => {
+            let Value::Object(object) = value else {
+                return Err(expected("an object of strings", value));
+            };
+            if keys.is_empty() {
+                let pairs = object
+                    .iter()
+                    .map(|(k, v)| Ok((k.clone(), string(v)?.to_string())))
+                    .collect::<Result<_, String>>()?;
+                return Ok(ElementValue::Pairs(pairs));
+            }
+            // Fixed keys keep their rows; only the values come from the prefill
+            if let Some(unknown) = object.keys().find(|k| !keys.contains(k)) {
+                return Err(format!("`{}` is not one of its keys", unknown));
+            }
+            let pairs = keys
+                .iter()
+                .map(|k| {
+                    let v = object.get(k).map(string).transpose()?.unwrap_or_default();
+                    Ok((k.clone(), v.to_string()))
+                })
+                .collect::<Result<_, String>>()?;
+            Ok(ElementValue::Pairs(pairs))
+        }
+        Element::Tags {
+            max_tags, pattern, ..
+        } => {
+            let tags = array(value)?
+                .iter()
+                .map(|v| string(v).map(str::to_string))
+                .collect::<Result<Vec<_>, _>>()?;
+            if let Some(max) = max_tags.filter(|max| tags.len() > *max) {
+                return Err(format!("at most {} tags allowed", max));
+            }
+            for tag in &tags {
+                validation::check_tag(tag, pattern.as_ref())?;
+            }
+            Ok(ElementValue::Tags(tags))
+        }
+        Element::Path {
+            root,
+            mode,
+            filters,
+            multiple,
+            ..
+        } => {
+            let paths: Vec<&Value> = match value {
+                Value::Array(items) if *multiple => items.iter().collect(),
+                Value::Array(_) => return Err(expected("one path", value)),
+                _ => vec![value],
+            };
+            let mut picked = Vec::new();
+            for p in paths {
+                let p = string(p)?;
+                path::check_path(p, root.as_deref(), *mode, filters)?;
+                picked.push(p.to_string());
+            }
+            Ok(ElementValue::Paths(picked))
+        }
+        Element::Rating { max, .. } => match value {
+            Value::Null => Ok(ElementValue::Rating(None)),
+            _ => match value.as_u64() {
+                Some(score) if (1..=*max as u64).contains(&score) => {
+                    Ok(ElementValue::Rating(Some(score as u32)))
+                }
+                _ => Err(expected(&format!("a score from 1 to {}", max), value)),
+            },
+        },
+        Element::Matrix { rows, columns, .. } => {
+            let Value::Object(object) = value else {
+                return Err(expected("an object of row id to column", value));
+            };
+            if let Some(unknown) = object.keys().find(|k| !rows.iter().any(|r| &r.id == *k)) {
+                return Err(format!("`{}` is not a row of the matrix", unknown));
+            }
+            let columns: Vec<&str> = columns.iter().map(String::as_str).collect();
+            let picks = rows
+                .iter()
+                .map(|row| match object.get(&row.id) {
+                    None | Some(Value::Null) => Ok(None),
+                    Some(v) => option_index(&columns, v).map(Some),
+                })
+                .collect::<Result<_, String>>()?;
+            Ok(ElementValue::Matrix(picks))
+        }
+        // Skipped by `apply`
+        Element::Secret { .. } | Element::ConfirmPhrase { .. } | Element::Diff { .. } => {
+            Err("this element can't be prefilled".to_string())
+        }
+        Element::Text { .. }
+        | Element::Markdown { .. }
+        | Element::Image { .. }
+        | Element::Group { .. } => Err("this element has no value".to_string()),
+    }
+}
+
+/// Index of an option given by its text or by its index
+fn option_index(options: &[&str], value: &Value) -> Result<usize, String> {
+    match value {
+        Value::String(s) => options
+            .iter()
+            .position(|o| o == s)
+            .ok_or_else(|| format!("`{}` is not one of its options", s)),
+        Value::Number(n) => n
+            .as_u64()
+            .map(|i| i as usize)
+            .filter(|i| *i < options.len())
+            .ok_or_else(|| format!("option index {} is out of range", n)),
+        _ => Err(expected("an option", value)),
+    }
+}
+
+fn number(value: &Value) -> Result<f32, String> {
+    value
+        .as_f64()
+        .map(|n| n as f32)
+        .ok_or_else(|| expected("a number", value))
+}
+
+fn string(value: &Value) -> Result<&str, String> {
+    value.as_str().ok_or_else(|| expected("a string", value))
+}
+
+fn array(value: &Value) -> Result<&Vec<Value>, String> {
+    value.as_array().ok_or_else(|| expected("an array", value))
+}
+
+fn expected(what: &str, got: &Value) -> String {
+    format!("expected {}, got {}", what, got)
+}
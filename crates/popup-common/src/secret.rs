@@ -0,0 +1,78 @@
+//! Delivery of `secret` input values outside the inline result
+
+use std::fs::OpenOptions;
+use std::io::{self, Write};
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+use serde_json::{json, Map, Value};
+
+use crate::Element;
+
+/// Replace the value of each `to_file` secret with `{"file": path}`, searching
+/// nested elements and list rows. A secret that can't be written is replaced
+/// with `{"error": ...}` rather than left inline.
+pub(crate) fn deliver_to_files(elements: &[Element], values: &mut Map<String, Value>) {
+    for element in elements {
+        match element {
+            Element::Secret {
+                id, to_file: true, ..
+            } => {
+                let Some(value) = values.get_mut(id) else {
+                    continue;
+                };
+                let text = value.as_str().unwrap_or_default();
+                *value = match write_secret_file(text) {
+                    Ok(path) => json!({ "file": path }),
+                    Err(e) => {
+                        json!({ "error": format!("could not write secret to a file: {}", e) })
+                    }
+                };
+            }
+            Element::List {
+                id,
+                elements: template,
+                ..
+            } => {
+                if let Some(Value::Array(rows)) = values.get_mut(id) {
+                    for row in rows.iter_mut().filter_map(Value::as_object_mut) {
+                        deliver_to_files(template, row);
+                    }
+                }
+            }
+            _ => {
+                for children in element.children() {
+                    deliver_to_files(children, values);
+                }
+            }
+        }
+    }
+}
+
+/// Write a secret to a new file in the temp directory, readable only by the
+/// current user on Unix. The caller is responsible for deleting it.
+pub fn write_secret_file(secret: &str) -> io::Result<PathBuf> {
+    static COUNTER: AtomicUsize = AtomicUsize::new(0);
+
+    let nanos = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|d| d.subsec_nanos())
+        .unwrap_or_default();
+    let path = std::env::temp_dir().join(format!(
+        "popup-secret-{}-{}-{}",
+        std::process::id(),
+        nanos,
+        COUNTER.fetch_add(1, Ordering::Relaxed)
+    ));
+
+    let mut options = OpenOptions::new();
+    options.write(true).create_new(true);
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::OpenOptionsExt;
+        options.mode(0o600);
+    }
+    let mut file = options.open(&path)?;
+    file.write_all(secret.as_bytes())?;
+    Ok(path)
+}
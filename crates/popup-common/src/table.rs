@@ -0,0 +1,95 @@
+//! Row data and row keys for the `table` element
+
+use serde_json::Value;
+
+/// Turn JSON rows into cells, one per column.
+///
+/// Rows may be arrays (cells by position) or objects (cells by column name).
+/// Missing cells become empty; numbers and booleans are shown as written.
+pub fn normalize_rows(columns: &[String], rows: Vec<Value>) -> Result<Vec<Vec<String>>, String> {
+    rows.into_iter()
+        .enumerate()
+        .map(|(i, row)| match row {
+            Value::Array(cells) => {
+                if cells.len() > columns.len() {
+                    return Err(format!(
+                        "table row {} has {} cells but there are only {} columns",
+                        i,
+                        cells.len(),
+                        columns.len()
+                    ));
+                }
+                let mut cells: Vec<String> = cells.into_iter().map(cell_text).collect();
+                cells.resize(columns.len(), String::new());
+                Ok(cells)
+            }
+            Value::Object(mut cells) => {
+                let row: Vec<String> = columns
+                    .iter()
+                    .map(|c| cells.remove(c).map(cell_text).unwrap_or_default())
+                    .collect();
+                match cells.keys().next() {
+                    Some(unknown) => {
+                        Err(format!("table row {} has unknown column `{}`", i, unknown))
+                    }
+                    None => Ok(row),
+                }
+            }
+            _ => Err(format!("table row {} must be an array or an object", i)),
+        })
+        .collect()
+}
+
+fn cell_text(value: Value) -> String {
+    match value {
+        Value::String(s) => s,
+        Value::Null => String::new(),
+        other => other.to_string(),
+    }
+}
+
+/// Index of the key column: the named one, or the first
+pub fn key_column(columns: &[String], key: Option<&str>) -> usize {
+    key.and_then(|k| columns.iter().position(|c| c == k))
+        .unwrap_or(0)
+}
+
+/// The key of every row, in row order
+pub fn row_keys(columns: &[String], rows: &[Vec<String>], key: Option<&str>) -> Vec<String> {
+    let col = key_column(columns, key);
+    rows.iter()
+        .map(|row| row.get(col).cloned().unwrap_or_default())
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_normalize_rows() {
+        let columns = vec!["name".to_string(), "secs".to_string()];
+        let rows = vec![
+            json!(["a", 1.5]),
+            json!({"secs": 2, "name": "b"}),
+            json!(["c"]),
+        ];
+        assert_eq!(
+            normalize_rows(&columns, rows).unwrap(),
+            vec![vec!["a", "1.5"], vec!["b", "2"], vec!["c", ""]]
+        );
+
+        assert!(normalize_rows(&columns, vec![json!(["a", 1, true])]).is_err());
+        assert!(normalize_rows(&columns, vec![json!({"nmae": "a"})]).is_err());
+        assert!(normalize_rows(&columns, vec![json!("a")]).is_err());
+    }
+
+    #[test]
+    fn test_row_keys() {
+        let columns = vec!["name".to_string(), "path".to_string()];
+        let rows = vec![vec!["a".to_string(), "src/a.rs".to_string()]];
+        assert_eq!(row_keys(&columns, &rows, None), vec!["a"]);
+        assert_eq!(row_keys(&columns, &rows, Some("path")), vec!["src/a.rs"]);
+    }
+}
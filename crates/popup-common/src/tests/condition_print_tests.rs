@@ -0,0 +1,205 @@
+use crate::condition::{ArithOp, CompareOp};
+use crate::{evaluate_condition, parse_condition, ConditionExpr};
+use proptest::prelude::*;
+use serde_json::{json, Value};
+use std::collections::HashMap;
+
+/// Field ids used when checking that simplification preserves meaning
+const FIELDS: &[&str] = &["a", "b", "tags", "name"];
+
+fn boxed(e: ConditionExpr) -> Box<ConditionExpr> {
+    Box::new(e)
+}
+
+fn arb_ident() -> impl Strategy<Value = String> {
+    prop_oneof![
+        prop::sample::select(FIELDS).prop_map(str::to_string),
+        "[a-z_][a-z0-9_]{0,8}".prop_filter("keywords are not identifiers", |s| {
+            !matches!(s.as_str(), "true" | "false" | "in")
+        }),
+    ]
+}
+
+fn arb_number() -> impl Strategy<Value = f64> {
+    (-100_000i64..100_000, 0u32..3).prop_map(|(n, scale)| n as f64 / 10f64.powi(scale as i32))
+}
+
+fn arb_leaf() -> impl Strategy<Value = ConditionExpr> {
+    prop_oneof![
+        arb_ident().prop_map(ConditionExpr::Ref),
+        arb_number().prop_map(ConditionExpr::Number),
+        "[a-zA-Z0-9 _.'\"\\\\]{0,8}".prop_map(ConditionExpr::String),
+        any::<bool>().prop_map(ConditionExpr::Boolean),
+    ]
+}
+
+fn arb_compare_op() -> impl Strategy<Value = CompareOp> {
+    prop::sample::select(vec![
+        CompareOp::Greater,
+        CompareOp::Less,
+        CompareOp::GreaterEqual,
+        CompareOp::LessEqual,
+        CompareOp::Equal,
+        CompareOp::NotEqual,
+        CompareOp::In,
+    ])
+}
+
+fn arb_arith_op() -> impl Strategy<Value = ArithOp> {
+    prop::sample::select(vec![ArithOp::Add, ArithOp::Sub, ArithOp::Mul, ArithOp::Div])
+}
+
+/// Expressions in the shape the parser produces: junctions have at least two
+/// operands and unary minus is never applied directly to a number literal
+fn arb_expr() -> impl Strategy<Value = ConditionExpr> {
+    arb_leaf().prop_recursive(4, 48, 4, |inner| {
+        let pair = (inner.clone(), inner.clone());
+        prop_oneof![
+            prop::collection::vec(inner.clone(), 2..4).prop_map(ConditionExpr::Or),
+            prop::collection::vec(inner.clone(), 2..4).prop_map(ConditionExpr::And),
+            inner.clone().prop_map(|e| ConditionExpr::Not(boxed(e))),
+            inner
+                .clone()
+                .prop_filter("-<number> parses as a literal", |e| {
+                    !matches!(e, ConditionExpr::Number(_))
+                })
+                .prop_map(|e| ConditionExpr::Neg(boxed(e))),
+            (arb_compare_op(), inner.clone(), inner.clone()).prop_map(|(op, l, r)| {
+                ConditionExpr::Compare {
+                    op,
+                    left: boxed(l),
+                    right: boxed(r),
+                }
+            }),
+            (arb_arith_op(), inner.clone(), inner.clone()).prop_map(|(op, l, r)| {
+                ConditionExpr::Arith {
+                    op,
+                    left: boxed(l),
+                    right: boxed(r),
+                }
+            }),
+            prop::collection::vec(inner.clone(), 0..3).prop_map(ConditionExpr::List),
+            prop::collection::vec(inner.clone(), 1..3).prop_map(ConditionExpr::Any),
+            prop::collection::vec(inner.clone(), 1..3).prop_map(ConditionExpr::All),
+            arb_ident().prop_map(|id| ConditionExpr::Count(boxed(ConditionExpr::Ref(id)))),
+            (arb_ident(), "[a-z ]{0,6}").prop_map(|(id, option)| ConditionExpr::Selected(
+                boxed(ConditionExpr::Ref(id)),
+                boxed(ConditionExpr::String(option))
+            )),
+            pair.clone()
+                .prop_map(|(a, b)| ConditionExpr::Contains(boxed(a), boxed(b))),
+            pair.clone()
+                .prop_map(|(a, b)| ConditionExpr::StartsWith(boxed(a), boxed(b))),
+            pair.prop_map(|(a, b)| ConditionExpr::EndsWith(boxed(a), boxed(b))),
+            inner.clone().prop_map(|e| ConditionExpr::Len(boxed(e))),
+            inner.clone().prop_map(|e| ConditionExpr::Lower(boxed(e))),
+            (
+                inner.clone(),
+                prop::sample::select(vec!["^a", "b$", "a.c", "[ab]+", ""])
+            )
+                .prop_map(|(e, pattern)| {
+                    ConditionExpr::Matches(
+                        boxed(e),
+                        boxed(ConditionExpr::String(pattern.to_string())),
+                    )
+                }),
+            inner.prop_map(|e| ConditionExpr::Empty(boxed(e))),
+        ]
+    })
+}
+
+fn arb_state() -> impl Strategy<Value = HashMap<String, Value>> {
+    let value = prop_oneof![
+        Just(Value::Null),
+        any::<bool>().prop_map(Value::Bool),
+        (-5i64..5).prop_map(|n| json!(n)),
+        "[a-c]{0,3}".prop_map(Value::String),
+        prop::collection::vec("[a-c]{1,2}", 0..3).prop_map(|v| json!(v)),
+    ];
+    prop::collection::vec(value, FIELDS.len())
+        .prop_map(|values| FIELDS.iter().map(|f| f.to_string()).zip(values).collect())
+}
+
+proptest! {
+    #[test]
+    fn prop_print_parse_roundtrip(expr in arb_expr()) {
+        let printed = expr.to_string();
+        let parsed = parse_condition(&printed);
+        prop_assert_eq!(parsed.as_ref(), Ok(&expr), "printed as {}", printed);
+    }
+
+    #[test]
+    fn prop_simplify_preserves_meaning(expr in arb_expr(), state in arb_state()) {
+        let simplified = expr.simplify();
+        prop_assert_eq!(
+            evaluate_condition(&simplified, &state),
+            evaluate_condition(&expr, &state),
+            "{} simplified to {}",
+            expr,
+            simplified
+        );
+    }
+
+    #[test]
+    fn prop_simplify_is_idempotent(expr in arb_expr()) {
+        let once = expr.simplify();
+        prop_assert_eq!(once.simplify(), once);
+    }
+}
+
+#[test]
+fn test_print_canonical_form() {
+    let cases = [
+        ("a==1", "a == 1"),
+        ("(a && b) || c", "a && b || c"),
+        ("a && (b || c)", "a && (b || c)"),
+        ("a || (b || c)", "a || (b || c)"),
+        ("a - (b - c)", "a - (b - c)"),
+        ("(a - b) - c", "a - b - c"),
+        ("(a + b) * c", "(a + b) * c"),
+        ("!(a > 1)", "!(a > 1)"),
+        ("-(a + 1) > -5", "-(a + 1) > -5"),
+        ("env in [\"Prod\", 'Staging']", "env in ['Prod', 'Staging']"),
+        ("name == \"it's\"", "name == 'it\\'s'"),
+        ("selected(mode,'Fast')", "selected(mode, 'Fast')"),
+        ("count(x)>=2.5", "count(x) >= 2.5"),
+    ];
+    for (input, expected) in cases {
+        let expr = parse_condition(input).unwrap();
+        assert_eq!(expr.to_string(), expected, "printing {:?}", input);
+        assert_eq!(parse_condition(expected).unwrap(), expr);
+    }
+}
+
+#[test]
+fn test_simplify() {
+    let simplify = |input: &str| parse_condition(input).unwrap().simplify().to_string();
+
+    assert_eq!(simplify("a && (b && c)"), "a && b && c");
+    assert_eq!(simplify("a || (b || (c && d))"), "a || b || c && d");
+    assert_eq!(simplify("!!enabled"), "enabled");
+    assert_eq!(simplify("!!!enabled"), "!enabled");
+    assert_eq!(simplify("cpu > 40 + 40"), "cpu > 80");
+    assert_eq!(simplify("a && true"), "a");
+    assert_eq!(simplify("a || true"), "true");
+    assert_eq!(simplify("a && 1 > 2"), "false");
+    assert_eq!(simplify("lower('ABC') == name"), "'abc' == name");
+    // Double negation is only dropped where the operand is already boolean
+    assert_eq!(simplify("(!!a) == true"), "!!a == true");
+    assert_eq!(simplify("(!!(a > 1)) == true"), "(a > 1) == true");
+    // Division by zero has no literal form
+    assert_eq!(simplify("a > 1 / 0"), "a > 1 / 0");
+}
+
+#[test]
+fn test_condition_expr_serde() {
+    let expr = parse_condition("count(tags) >= 2 && !done").unwrap();
+    let json = serde_json::to_value(&expr).unwrap();
+    assert_eq!(json, json!("count(tags) >= 2 && !done"));
+
+    let back: ConditionExpr = serde_json::from_value(json).unwrap();
+    assert_eq!(back, expr);
+
+    let err = serde_json::from_value::<ConditionExpr>(json!("a = 1")).unwrap_err();
+    assert!(err.to_string().contains("use `==`"));
+}
@@ -2,3 +2,7 @@
 mod popup_state_tests;
 #[cfg(test)]
 mod ergonomic_v2_tests;
+#[cfg(test)]
+mod condition_print_tests;
+#[cfg(test)]
+mod page_tests;
@@ -0,0 +1,230 @@
+use crate::{Element, FieldError, PopupDefinition, PopupResult, PopupState};
+use serde_json::json;
+
+fn wizard() -> PopupDefinition {
+    serde_json::from_value(json!({
+        "title": "Wizard",
+        "pages": [
+            {
+                "title": "Basics",
+                "required": ["name", "env"],
+                "elements": [
+                    {"input": "Name", "id": "name"},
+                    {"select": "Environment", "id": "env", "options": ["Dev", "Prod"]},
+                    {"check": "Tune", "id": "tune"}
+                ]
+            },
+            {
+                "title": "Tuning",
+                "when": "tune",
+                "required": ["threads"],
+                "elements": [{"slider": "Threads", "id": "threads", "min": 1, "max": 8}]
+            },
+            {
+                "title": "Confirm",
+                "required": ["ack"],
+                "elements": [{"check": "Acknowledge", "id": "ack", "when": "env == 'Prod'"}]
+            }
+        ]
+    }))
+    .unwrap()
+}
+
+fn active_ids(def: &PopupDefinition, state: &PopupState) -> Vec<String> {
+    state.active_element_ids(&def.all_elements(), &def.condition_values(state))
+}
+
+#[test]
+fn test_pages_deserialize() {
+    let def = wizard();
+    assert!(def.elements.is_empty());
+    assert_eq!(def.pages.len(), 3);
+    assert_eq!(def.pages[1].when.as_deref(), Some("tune"));
+    assert_eq!(def.pages[0].required, vec!["name", "env"]);
+
+    // Pages appear as groups carrying the page condition
+    let all = def.all_elements();
+    assert_eq!(all.len(), 3);
+    assert!(matches!(
+        &all[1],
+        Element::Group { group, when: Some(w), .. } if group == "Tuning" && w == "tune"
+    ));
+
+    // Round-trips without an empty `elements` key getting in the way
+    let back: PopupDefinition =
+        serde_json::from_value(serde_json::to_value(&def).unwrap()).unwrap();
+    assert_eq!(back, def);
+}
+
+#[test]
+fn test_pages_and_elements_are_exclusive() {
+    let err = serde_json::from_value::<PopupDefinition>(json!({
+        "title": "Both",
+        "elements": [{"text": "hi"}],
+        "pages": [{"title": "One", "elements": []}]
+    }))
+    .unwrap_err();
+    assert!(err.to_string().contains("cannot both be set"));
+
+    let err = serde_json::from_value::<PopupDefinition>(json!({"title": "Neither"})).unwrap_err();
+    assert!(err.to_string().contains("missing field `elements`"));
+}
+
+#[test]
+fn test_state_spans_all_pages() {
+    let def = wizard();
+    let state = PopupState::new(&def);
+    for id in ["name", "env", "tune", "threads", "ack"] {
+        assert!(state.values.contains_key(id), "missing state for {}", id);
+    }
+}
+
+#[test]
+fn test_page_validation() {
+    let def = wizard();
+    let mut state = PopupState::new(&def);
+
+    let errors = def.pages[0].validate(&state, &active_ids(&def, &state));
+    let ids: Vec<&str> = errors.iter().map(|e| e.id.as_str()).collect();
+    assert_eq!(ids, vec!["name", "env"]);
+
+    // Whitespace doesn't count as an answer
+    *state.get_text_mut("name").unwrap() = "  ".to_string();
+    *state.get_choice_mut("env").unwrap() = Some(0);
+    let errors = def.pages[0].validate(&state, &active_ids(&def, &state));
+    assert_eq!(
+        errors,
+        vec![FieldError {
+            id: "name".to_string(),
+            message: "This field is required".to_string()
+        }]
+    );
+
+    *state.get_text_mut("name").unwrap() = "svc".to_string();
+    assert!(def.pages[0]
+        .validate(&state, &active_ids(&def, &state))
+        .is_empty());
+
+    // The acknowledgement is only required while it is shown (env == 'Prod')
+    assert!(def.pages[2]
+        .validate(&state, &active_ids(&def, &state))
+        .is_empty());
+    *state.get_choice_mut("env").unwrap() = Some(1);
+    assert_eq!(
+        def.pages[2]
+            .validate(&state, &active_ids(&def, &state))
+            .len(),
+        1
+    );
+}
+
+#[test]
+fn test_submit_rechecks_pages_shown_behind_the_user() {
+    let def: PopupDefinition = serde_json::from_value(json!({
+        "title": "Wizard",
+        "pages": [
+            {"title": "Start", "elements": [{"input": "Name", "id": "name"}]},
+            {
+                "title": "Details",
+                "when": "more",
+                "required": ["reason"],
+                "elements": [{"input": "Reason", "id": "reason"}]
+            },
+            {"title": "Finish", "elements": [{"check": "Tell us more", "id": "more"}]}
+        ]
+    }))
+    .unwrap();
+    let elements = def.all_elements();
+    let mut state = PopupState::new(&def);
+    assert_eq!(def.submit_errors(&state, &elements), None);
+
+    // Checking the box on the last page shows a page the user already skipped
+    *state.get_boolean_mut("more").unwrap() = true;
+    let (page, errors) = def.submit_errors(&state, &elements).unwrap();
+    assert_eq!(page, 1);
+    assert_eq!(
+        errors,
+        vec![FieldError {
+            id: "reason".to_string(),
+            message: "This field is required".to_string()
+        }]
+    );
+
+    *state.get_text_mut("reason").unwrap() = "audit".to_string();
+    assert_eq!(def.submit_errors(&state, &elements), None);
+}
+
+#[test]
+fn test_combined_result_skips_hidden_pages() {
+    let def = wizard();
+    let mut state = PopupState::new(&def);
+    *state.get_text_mut("name").unwrap() = "svc".to_string();
+    *state.get_choice_mut("env").unwrap() = Some(1);
+    *state.get_boolean_mut("ack").unwrap() = true;
+    state.button_clicked = Some("submit".to_string());
+
+    let result =
+        PopupResult::from_state_with_active_elements(&state, &def, &active_ids(&def, &state));
+    let PopupResult::Completed { values, .. } = result else {
+        panic!("Expected Completed result");
+    };
+    assert_eq!(values["name"], json!("svc"));
+    assert_eq!(values["env"], json!("Prod"));
+    assert_eq!(values["ack"], json!(true));
+    // The tuning page was skipped, so its slider isn't reported
+    assert!(!values.contains_key("threads"));
+
+    *state.get_boolean_mut("tune").unwrap() = true;
+    let result =
+        PopupResult::from_state_with_active_elements(&state, &def, &active_ids(&def, &state));
+    let PopupResult::Completed { values, .. } = result else {
+        panic!("Expected Completed result");
+    };
+    assert!(values.contains_key("threads"));
+}
+
+#[test]
+fn test_kv_duplicate_keys_block_submit() {
+    use crate::validate_elements;
+
+    let def: PopupDefinition = serde_json::from_value(json!({
+        "title": "Headers",
+        "elements": [
+            {"kv": "Headers", "placeholder": "value"},
+            {"kv": "Hidden", "when": "false"}
+        ]
+    }))
+    .unwrap();
+    let mut state = PopupState::new(&def);
+    *state.get_pairs_mut("headers").unwrap() = vec![
+        ("Accept".to_string(), "text/html".to_string()),
+        (" Accept".to_string(), "*/*".to_string()),
+        (String::new(), "ignored".to_string()),
+    ];
+    *state.get_pairs_mut("hidden").unwrap() = vec![
+        ("a".to_string(), String::new()),
+        ("a".to_string(), String::new()),
+    ];
+
+    let ids = active_ids(&def, &state);
+    assert_eq!(
+        validate_elements(&def.elements, &state, &ids),
+        vec![FieldError {
+            id: "headers".to_string(),
+            message: "Duplicate key `Accept`".to_string(),
+        }]
+    );
+
+    state.get_pairs_mut("headers").unwrap()[1].0 = "Cache-Control".to_string();
+    assert!(validate_elements(&def.elements, &state, &ids).is_empty());
+    assert!(state.values["headers"].is_answered());
+
+    let result = PopupResult::from_state_with_active_elements(&state, &def, &ids);
+    let PopupResult::Completed { values, .. } = result else {
+        panic!("Expected Completed result");
+    };
+    assert_eq!(
+        values["headers"],
+        json!({"Accept": "text/html", "Cache-Control": "*/*"})
+    );
+}
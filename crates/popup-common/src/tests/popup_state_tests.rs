@@ -1,4 +1,4 @@
-use crate::{Element, ElementValue, OptionValue, PopupDefinition, PopupState};
+use crate::{Element, ElementValue, HiddenFields, OptionValue, PopupDefinition, PopupState};
 use std::collections::HashMap;
 
 #[test]
@@ -11,8 +11,10 @@ fn test_popupstate_init_slider() {
             min: 0.0,
             max: 100.0,
             default: Some(75.0),
+            span: None,
             when: None,
         }],
+        ..Default::default()
     };
 
     let state = PopupState::new(&def);
@@ -29,8 +31,10 @@ fn test_popupstate_init_slider_default_midpoint() {
             min: 1.0,
             max: 10.0,
             default: None, // Should default to midpoint
+            span: None,
             when: None,
         }],
+        ..Default::default()
     };
 
     let state = PopupState::new(&def);
@@ -46,8 +50,10 @@ fn test_popupstate_init_checkbox() {
             id: "enable".to_string(),
             default: true,
             reveals: vec![],
+            span: None,
             when: None,
         }],
+        ..Default::default()
     };
 
     let state = PopupState::new(&def);
@@ -71,10 +77,13 @@ fn test_popupstate_init_with_reveals() {
                 min: 1.0,
                 max: 10.0,
                 default: None,
+                span: None,
                 when: None,
             }],
+            span: None,
             when: None,
         }],
+        ..Default::default()
     };
 
     let state = PopupState::new(&def);
@@ -96,6 +105,7 @@ fn test_popupstate_init_with_option_children() {
             min: 0.0,
             max: 100.0,
             default: Some(50.0),
+            span: None,
             when: None,
         }],
     );
@@ -112,8 +122,11 @@ fn test_popupstate_init_with_option_children() {
             default: None,
             option_children,
             reveals: vec![],
+            searchable: false,
+            span: None,
             when: None,
         }],
+        ..Default::default()
     };
 
     let state = PopupState::new(&def);
@@ -136,10 +149,14 @@ fn test_popupstate_init_multiselect() {
                 OptionValue::Simple("B".to_string()),
                 OptionValue::Simple("C".to_string()),
             ],
+            default: Vec::new(),
             option_children: HashMap::new(),
             reveals: vec![],
+            searchable: false,
+            span: None,
             when: None,
         }],
+        ..Default::default()
     };
 
     let state = PopupState::new(&def);
@@ -158,8 +175,11 @@ fn test_popupstate_init_textbox() {
             id: "name".to_string(),
             placeholder: None,
             rows: None,
+            default: None,
+            span: None,
             when: None,
         }],
+        ..Default::default()
     };
 
     let state = PopupState::new(&def);
@@ -169,6 +189,50 @@ fn test_popupstate_init_textbox() {
     );
 }
 
+#[test]
+fn test_popupstate_init_input_and_multi_defaults() {
+    let def: PopupDefinition = serde_json::from_value(serde_json::json!({
+        "title": "Draft",
+        "elements": [
+            {"input": "Summary", "default": "Fix flaky login test"},
+            {"input": "Body", "value": "Retries were masking a race", "rows": 4},
+            {"multi": "Labels", "options": ["bug", "ci", "docs"], "default": ["bug", "ci"]},
+            {"multi": "Owners", "options": "alice, bob", "default": "bob"}
+        ]
+    }))
+    .unwrap();
+
+    let state = PopupState::new(&def);
+    assert_eq!(state.get_text("summary").unwrap(), "Fix flaky login test");
+    assert_eq!(
+        state.get_text("body").unwrap(),
+        "Retries were masking a race"
+    );
+    assert_eq!(
+        state.values.get("labels"),
+        Some(&ElementValue::MultiChoice(vec![true, true, false]))
+    );
+    assert_eq!(
+        state.values.get("owners"),
+        Some(&ElementValue::MultiChoice(vec![false, true]))
+    );
+
+    let unknown = serde_json::json!({
+        "title": "T",
+        "elements": [{"multi": "Labels", "options": ["bug"], "default": ["feature"]}]
+    });
+    let err = serde_json::from_value::<PopupDefinition>(unknown).unwrap_err();
+    assert!(err
+        .to_string()
+        .contains("'feature' is not one of its options"));
+
+    let both = serde_json::json!({
+        "title": "T",
+        "elements": [{"input": "Name", "default": "a", "value": "b"}]
+    });
+    assert!(serde_json::from_value::<PopupDefinition>(both).is_err());
+}
+
 #[test]
 fn test_popupstate_init_group() {
     let def = PopupDefinition {
@@ -183,6 +247,7 @@ fn test_popupstate_init_group() {
                     min: 0.0,
                     max: 100.0,
                     default: Some(50.0),
+                    span: None,
                     when: None,
                 },
                 Element::Check {
@@ -190,11 +255,17 @@ fn test_popupstate_init_group() {
                     id: "mute".to_string(),
                     default: false,
                     reveals: vec![],
+                    span: None,
                     when: None,
                 },
             ],
+            layout: None,
+            collapsed: false,
+            summary: None,
+            span: None,
             when: None,
         }],
+        ..Default::default()
     };
 
     let state = PopupState::new(&def);
@@ -236,6 +307,7 @@ fn test_get_mut_methods() {
                 min: 0.0,
                 max: 100.0,
                 default: Some(50.0),
+                span: None,
                 when: None,
             },
             Element::Check {
@@ -243,9 +315,11 @@ fn test_get_mut_methods() {
                 id: "enabled".to_string(),
                 default: false,
                 reveals: vec![],
+                span: None,
                 when: None,
             },
         ],
+        ..Default::default()
     };
 
     let mut state = PopupState::new(&def);
@@ -278,6 +352,7 @@ fn test_nested_reveals_and_option_children() {
             min: 1.0,
             max: 10.0,
             default: Some(5.0),
+            span: None,
             when: None,
         }],
     );
@@ -298,10 +373,14 @@ fn test_nested_reveals_and_option_children() {
                 default: Some("Basic".to_string()),
                 option_children,
                 reveals: vec![],
+                searchable: false,
+                span: None,
                 when: None,
             }],
+            span: None,
             when: None,
         }],
+        ..Default::default()
     };
 
     let state = PopupState::new(&def);
@@ -334,6 +413,7 @@ fn test_find_element_in_option_children() {
             min: 1.0,
             max: 100.0,
             default: Some(50.0),
+            span: None,
             when: None,
         }],
     );
@@ -350,8 +430,11 @@ fn test_find_element_in_option_children() {
             default: Some("Pro".to_string()),
             option_children,
             reveals: vec![],
+            searchable: false,
+            span: None,
             when: None,
         }],
+        ..Default::default()
     };
 
     let mut state = PopupState::new(&def);
@@ -393,8 +476,10 @@ fn test_find_element_in_nested_reveals_and_option_children() {
                 min: 1.0,
                 max: 10.0,
                 default: Some(5.0),
+                span: None,
                 when: None,
             }],
+            span: None,
             when: None,
         }],
     );
@@ -411,8 +496,11 @@ fn test_find_element_in_nested_reveals_and_option_children() {
             default: Some("Advanced".to_string()),
             option_children,
             reveals: vec![],
+            searchable: false,
+            span: None,
             when: None,
         }],
+        ..Default::default()
     };
 
     let mut state = PopupState::new(&def);
@@ -434,3 +522,292 @@ fn test_find_element_in_nested_reveals_and_option_children() {
         _ => panic!("Expected Completed result"),
     }
 }
+
+/// A checkbox revealing a "danger" checkbox, plus a text shown when danger is checked
+fn stale_reveal_definition(extra: serde_json::Value) -> PopupDefinition {
+    let mut json = serde_json::json!({
+        "title": "Hidden fields",
+        "elements": [
+            {"check": "Advanced", "id": "advanced", "reveals": [
+                {"check": "Danger zone", "id": "danger"}
+            ]},
+            {"text": "Careful!", "id": "warning", "when": "danger"},
+            {"text": "Really careful!", "id": "second_warning", "when": "warning_ack"},
+            {"check": "Acknowledge", "id": "warning_ack", "when": "danger"}
+        ]
+    });
+    json.as_object_mut()
+        .unwrap()
+        .extend(extra.as_object().unwrap().clone());
+    serde_json::from_value(json).unwrap()
+}
+
+/// Advanced unchecked, but the hidden danger checkbox left checked
+fn stale_state(def: &PopupDefinition) -> PopupState {
+    let mut state = PopupState::new(def);
+    *state.get_boolean_mut("danger").unwrap() = true;
+    *state.get_boolean_mut("warning_ack").unwrap() = true;
+    state
+}
+
+#[test]
+fn test_hidden_fields_mode_defaults() {
+    let def = stale_reveal_definition(serde_json::json!({}));
+    assert_eq!(def.hidden_field_mode(), HiddenFields::Keep);
+
+    let def = stale_reveal_definition(serde_json::json!({"version": 3}));
+    assert_eq!(def.hidden_field_mode(), HiddenFields::Unset);
+
+    let def = stale_reveal_definition(serde_json::json!({"version": 3, "hidden_fields": "keep"}));
+    assert_eq!(def.hidden_field_mode(), HiddenFields::Keep);
+
+    let def = stale_reveal_definition(serde_json::json!({"hidden_fields": "unset"}));
+    assert_eq!(def.hidden_field_mode(), HiddenFields::Unset);
+}
+
+#[test]
+fn test_hidden_fields_keep_uses_stale_values() {
+    let def = stale_reveal_definition(serde_json::json!({}));
+    let state = stale_state(&def);
+
+    let values = def.condition_values(&state);
+    assert_eq!(values.get("danger"), Some(&serde_json::json!(true)));
+
+    let active = state.active_element_ids(&def.elements, &values);
+    assert!(active.contains(&"warning".to_string()));
+    assert!(active.contains(&"second_warning".to_string()));
+}
+
+#[test]
+fn test_hidden_fields_unset_reads_hidden_values_as_null() {
+    let def = stale_reveal_definition(serde_json::json!({"version": 3}));
+    let mut state = stale_state(&def);
+
+    // danger is hidden, so warning and warning_ack disappear, which in turn
+    // hides second_warning on a later pass
+    let values = def.condition_values(&state);
+    assert_eq!(values.get("danger"), None);
+    assert_eq!(values.get("warning_ack"), None);
+    assert_eq!(values.get("advanced"), Some(&serde_json::json!(false)));
+
+    let active = state.active_element_ids(&def.elements, &values);
+    assert_eq!(active, vec!["advanced".to_string()]);
+
+    // Revealing the branch brings the stale values back into play
+    *state.get_boolean_mut("advanced").unwrap() = true;
+    let values = def.condition_values(&state);
+    let active = state.active_element_ids(&def.elements, &values);
+    assert_eq!(
+        active,
+        vec![
+            "advanced",
+            "danger",
+            "warning",
+            "second_warning",
+            "warning_ack"
+        ]
+    );
+}
+
+#[test]
+fn test_hidden_fields_serde() {
+    let def = stale_reveal_definition(serde_json::json!({"version": 3, "hidden_fields": "keep"}));
+    let json = serde_json::to_value(&def).unwrap();
+    assert_eq!(json["version"], 3);
+    assert_eq!(json["hidden_fields"], "keep");
+
+    // Unset options are omitted rather than serialized as null
+    let def = stale_reveal_definition(serde_json::json!({}));
+    let json = serde_json::to_value(&def).unwrap();
+    assert!(json.get("version").is_none());
+    assert!(json.get("hidden_fields").is_none());
+}
+
+#[test]
+fn test_list_rows_have_scoped_state_and_results() {
+    use crate::PopupResult;
+    use serde_json::json;
+
+    let def: PopupDefinition = serde_json::from_value(json!({
+        "title": "Env",
+        "elements": [
+            {
+                "list": "Environment Variables",
+                "id": "env",
+                "min_items": 2,
+                "elements": [
+                    {"input": "Name", "id": "name"},
+                    {"check": "Secret", "id": "secret"},
+                    {"input": "Vault path", "id": "vault", "when": "secret"}
+                ]
+            },
+            {"text": "Many variables", "id": "many", "when": "count(env) >= 3"}
+        ]
+    }))
+    .unwrap();
+
+    let mut state = PopupState::new(&def);
+    let rows = state.get_list_mut("env").unwrap();
+    assert_eq!(rows.len(), 2);
+    assert_eq!(
+        rows[0].get("name"),
+        Some(&ElementValue::Text(String::new()))
+    );
+
+    rows[0].insert("name".to_string(), ElementValue::Text("HOME".to_string()));
+    rows[1].insert("name".to_string(), ElementValue::Text("TOKEN".to_string()));
+    rows[1].insert("secret".to_string(), ElementValue::Boolean(true));
+    rows[1].insert(
+        "vault".to_string(),
+        ElementValue::Text("kv/token".to_string()),
+    );
+    // Row 0's vault path is hidden by its own unchecked box
+    rows[0].insert("vault".to_string(), ElementValue::Text("stale".to_string()));
+
+    let values = state.condition_values(&def.elements, HiddenFields::Keep);
+    let active = state.active_element_ids(&def.elements, &values);
+    assert!(!active.contains(&"many".to_string()));
+
+    let result = PopupResult::from_state_with_active_elements(&state, &def, &active);
+    let PopupResult::Completed { values, .. } = result else {
+        panic!("Expected Completed result");
+    };
+    assert_eq!(
+        values["env"],
+        json!([
+            {"name": "HOME", "secret": false},
+            {"name": "TOKEN", "secret": true, "vault": "kv/token"}
+        ])
+    );
+
+    let rows = state.get_list_mut("env").unwrap();
+    rows.push(PopupState::list_row(match &def.elements[0] {
+        Element::List { elements, .. } => elements,
+        _ => unreachable!(),
+    }));
+    let values = state.condition_values(&def.elements, HiddenFields::Keep);
+    assert!(state
+        .active_element_ids(&def.elements, &values)
+        .contains(&"many".to_string()));
+}
+
+#[test]
+fn test_list_rows_are_validated_against_the_template() {
+    use crate::{validate_elements, FieldError};
+    use serde_json::json;
+
+    let def: PopupDefinition = serde_json::from_value(json!({
+        "title": "Team",
+        "elements": [{
+            "list": "Members",
+            "min_items": 2,
+            "max_items": 3,
+            "elements": [
+                {"input": "Name", "id": "name"},
+                {"tags": "Roles", "id": "roles", "max_tags": 1},
+                {"check": "Has color", "id": "has_color"},
+                {"color": "Color", "id": "color", "when": "has_color"}
+            ]
+        }]
+    }))
+    .unwrap();
+    let mut state = PopupState::new(&def);
+    let ids = vec!["members".to_string()];
+    assert!(validate_elements(&def.elements, &state, &ids).is_empty());
+
+    let rows = state.get_list_mut("members").unwrap();
+    rows[1].insert(
+        "roles".to_string(),
+        ElementValue::Tags(vec!["dev".to_string(), "ops".to_string()]),
+    );
+    // Bad values in fields the row hides don't count
+    rows[0].insert("color".to_string(), ElementValue::Text("nope".to_string()));
+    assert_eq!(
+        validate_elements(&def.elements, &state, &ids),
+        vec![FieldError {
+            id: "members".to_string(),
+            message: "Row 2, Roles: At most 1 tags allowed".to_string(),
+        }]
+    );
+
+    let rows = state.get_list_mut("members").unwrap();
+    rows[0].insert("has_color".to_string(), ElementValue::Boolean(true));
+    rows.truncate(1);
+    let messages: Vec<String> = validate_elements(&def.elements, &state, &ids)
+        .into_iter()
+        .map(|e| e.message)
+        .collect();
+    assert_eq!(
+        messages,
+        vec![
+            "At least 2 items needed".to_string(),
+            "Row 1, Color: `nope` isn't a hex color".to_string(),
+        ]
+    );
+}
+
+#[test]
+fn test_initial_values_seed_state_from_result_format() {
+    use crate::PopupResult;
+    use serde_json::json;
+
+    let json = json!({
+        "title": "Deploy",
+        "elements": [
+            {"select": "Env", "options": ["Dev", "Prod"], "Prod": [
+                {"check": "Notify on-call", "id": "notify"}
+            ]},
+            {"multi": "Regions", "options": ["us", "eu", "ap"]},
+            {"range": "Budget", "min": 0, "max": 100},
+            {"matrix": "Risk", "rows": ["Data loss", "Downtime"], "columns": "Low, High"},
+            {"list": "Owners", "elements": [{"input": "Name", "id": "name"}]},
+            {"kv": "Labels"}
+        ],
+        "initial_values": {
+            "env": "Prod",
+            "notify": true,
+            "regions": ["eu", 2],
+            "budget": {"low": 10, "high": 50},
+            "risk": {"downtime": "High"},
+            "owners": [{"name": "ana"}, {"name": "bo"}],
+            "labels": {"team": "infra"}
+        }
+    });
+    let def: PopupDefinition = serde_json::from_value(json.clone()).unwrap();
+    let mut state = PopupState::new(&def);
+    assert_eq!(state.values["env"], ElementValue::Choice(Some(1)));
+    assert_eq!(
+        state.values["regions"],
+        ElementValue::MultiChoice(vec![false, true, true])
+    );
+
+    // Submitting untouched gives back what was passed in
+    state.button_clicked = Some("submit".to_string());
+    let PopupResult::Completed { values, .. } = PopupResult::from_state_with_context(&state, &def)
+    else {
+        panic!("Expected completed result");
+    };
+    let mut expected = json["initial_values"].clone();
+    expected["regions"] = json!(["eu", "ap"]);
+    for (id, value) in expected.as_object().unwrap() {
+        assert_eq!(&values[id], value, "{}", id);
+    }
+
+    let bad = json!({
+        "title": "T",
+        "elements": [
+            {"select": "Env", "options": ["Dev", "Prod"]},
+            {"check": "Dry run"}
+        ],
+        "initial_values": {"env": "Staging", "dry_run": "yes", "missing": 1}
+    });
+    let def: PopupDefinition = serde_json::from_value(bad).unwrap();
+    assert_eq!(
+        def.initial_value_errors(),
+        vec![
+            "`dry_run`: expected true or false, got \"yes\"",
+            "`env`: `Staging` is not one of its options",
+            "`missing` is not a field of this popup",
+        ]
+    );
+}
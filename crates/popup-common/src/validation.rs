@@ -0,0 +1,344 @@
+//! Checks that gate moving between pages and submitting
+
+use crate::{path, Element, ElementValue, Page, PopupDefinition, PopupState};
+use serde::Serialize;
+
+/// A field whose current value blocks moving on
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct FieldError {
+    pub id: String,
+    pub message: String,
+}
+
+impl Page {
+    /// Errors for required fields on this page that are shown (in `active_ids`)
+    /// but not answered. Hidden required fields are skipped.
+    pub fn validate(&self, state: &PopupState, active_ids: &[String]) -> Vec<FieldError> {
+        self.required
+            .iter()
+            .filter(|id| active_ids.contains(id))
+            .filter(|id| !state.values.get(*id).is_some_and(ElementValue::is_answered))
+            .map(|id| FieldError {
+                id: id.clone(),
+                message: "This field is required".to_string(),
+            })
+            .collect()
+    }
+}
+
+impl PopupDefinition {
+    /// Missing required fields and invalid values on page `page`, or in the
+    /// whole popup when it has no pages
+    pub fn page_errors(
+        &self,
+        page: usize,
+        state: &PopupState,
+        active_ids: &[String],
+    ) -> Vec<FieldError> {
+        match self.pages.get(page) {
+            Some(page) => {
+                let mut errors = page.validate(state, active_ids);
+                errors.extend(validate_elements(&page.elements, state, active_ids));
+                errors
+            }
+            None => validate_elements(&self.elements, state, active_ids),
+        }
+    }
+
+    /// The first page with errors and its errors, or None when the popup can be
+    /// submitted. Every shown page is checked, not just the last one: a page can
+    /// be shown only after the user has moved past it. `elements` is
+    /// `all_elements()`, passed in so callers can build it once.
+    pub fn submit_errors(
+        &self,
+        state: &PopupState,
+        elements: &[Element],
+    ) -> Option<(usize, Vec<FieldError>)> {
+        let values = state.condition_values(elements, self.hidden_field_mode());
+        let active_ids = state.active_element_ids(elements, &values);
+        // Hidden pages have no active fields, so they never report errors
+        (0..self.pages.len().max(1))
+            .map(|page| (page, self.page_errors(page, state, &active_ids)))
+            .find(|(_, errors)| !errors.is_empty())
+    }
+}
+
+/// Errors for shown fields (in `active_ids`) whose values can't be submitted,
+/// whether or not they're required
+pub fn validate_elements(
+    elements: &[Element],
+    state: &PopupState,
+    active_ids: &[String],
+) -> Vec<FieldError> {
+    let mut errors = Vec::new();
+    collect_value_errors(elements, state, active_ids, &mut errors);
+    errors
+}
+
+fn collect_value_errors(
+    elements: &[Element],
+    state: &PopupState,
+    active_ids: &[String],
+    errors: &mut Vec<FieldError>,
+) {
+    for element in elements {
+        if let Some(id) = element.id().filter(|id| active_ids.iter().any(|a| a == id)) {
+            if let Some(message) = value_error(element, state.values.get(id)) {
+                errors.push(FieldError {
+                    id: id.to_string(),
+                    message,
+                });
+            }
+        }
+        for children in element.children() {
+            collect_value_errors(children, state, active_ids, errors);
+        }
+        if let Element::List {
+            id,
+            elements: template,
+            ..
+        } = element
+        {
+            if active_ids.contains(id) {
+                collect_row_errors(id, template, state, errors);
+            }
+        }
+    }
+}
+
+/// Errors inside each row of a shown list, checked against the list's template
+/// and reported on the list itself with the row number
+fn collect_row_errors(
+    id: &str,
+    template: &[Element],
+    state: &PopupState,
+    errors: &mut Vec<FieldError>,
+) {
+    let Some(ElementValue::List(rows)) = state.values.get(id) else {
+        return;
+    };
+    for (i, row) in rows.iter().enumerate() {
+        let row_state = PopupState {
+            values: row.clone(),
+            button_clicked: None,
+        };
+        // The fields a row contributes to the result
+        let row_active =
+            row_state.active_element_ids(template, &row_state.to_value_map(template));
+        for error in validate_elements(template, &row_state, &row_active) {
+            let label = find_label(template, &error.id).unwrap_or(&error.id);
+            errors.push(FieldError {
+                id: id.to_string(),
+                message: format!("Row {}, {}: {}", i + 1, label, error.message),
+            });
+        }
+    }
+}
+
+/// Label of the element with this id, searching nested elements
+fn find_label<'a>(elements: &'a [Element], id: &str) -> Option<&'a str> {
+    elements.iter().find_map(|element| {
+        if element.id() == Some(id) {
+            return Some(element.label());
+        }
+        element
+            .children()
+            .into_iter()
+            .find_map(|children| find_label(children, id))
+    })
+}
+
+fn value_error(element: &Element, value: Option<&ElementValue>) -> Option<String> {
+    match (element, value) {
+        (Element::Kv { .. }, Some(ElementValue::Pairs(pairs))) => {
+            duplicate_key(pairs).map(|key| format!("Duplicate key `{}`", key))
+        }
+        (
+            Element::Tags {
+                max_tags, pattern, ..
+            },
+            Some(ElementValue::Tags(tags)),
+        ) => match max_tags {
+            Some(max) if tags.len() > *max => Some(format!("At most {} tags allowed", max)),
+            _ => tags
+                .iter()
+                .find_map(|tag| check_tag(tag, pattern.as_ref()).err()),
+        },
+        (
+            Element::ConfirmPhrase {
+                confirm_phrase,
+                case_sensitive,
+                ..
+            },
+            Some(ElementValue::Text(typed)),
+        ) if !phrase_matches(typed, confirm_phrase, *case_sensitive) => {
+            Some(format!("Type `{}` to confirm", confirm_phrase))
+        }
+        (
+            Element::Path {
+                root,
+                mode,
+                filters,
+                multiple,
+                ..
+            },
+            Some(ElementValue::Paths(paths)),
+        ) => {
+            if !multiple && paths.len() > 1 {
+                return Some("Pick one path".to_string());
+            }
+            paths
+                .iter()
+                .find_map(|p| path::check_path(p, root.as_deref(), *mode, filters).err())
+        }
+        (Element::Range { min, max, .. }, Some(ElementValue::Range(low, high))) => {
+            if low > high {
+                Some(format!("Low end {} is above the high end {}", low, high))
+            } else if low < min || high > max {
+                Some(format!("Range must stay within {}..{}", min, max))
+            } else {
+                None
+            }
+        }
+        (
+            Element::List {
+                min_items,
+                max_items,
+                ..
+            },
+            Some(ElementValue::List(rows)),
+        ) => {
+            if rows.len() < *min_items {
+                Some(format!("At least {} items needed", min_items))
+            } else {
+                max_items
+                    .filter(|max| rows.len() > *max)
+                    .map(|max| format!("At most {} items allowed", max))
+            }
+        }
+        (Element::Color { .. }, Some(ElementValue::Text(hex)))
+            if !hex.is_empty() && parse_hex_color(hex).is_none() =>
+        {
+            Some(format!("`{}` isn't a hex color", hex))
+        }
+        _ => None,
+    }
+}
+
+/// Whether the typed text is the confirmation phrase, ignoring surrounding whitespace
+pub fn phrase_matches(typed: &str, phrase: &str, case_sensitive: bool) -> bool {
+    let typed = typed.trim();
+    if case_sensitive {
+        typed == phrase
+    } else {
+        typed.to_lowercase() == phrase.to_lowercase()
+    }
+}
+
+/// RGBA from "#rgb", "#rrggbb" or "#rrggbbaa" (the "#" is optional)
+pub fn parse_hex_color(hex: &str) -> Option<[u8; 4]> {
+    let digits = hex.trim().trim_start_matches('#');
+    if !digits.chars().all(|c| c.is_ascii_hexdigit()) {
+        return None;
+    }
+    let channel = |i: usize| u8::from_str_radix(&digits[i..i + 2], 16).ok();
+    match digits.len() {
+        3 => {
+            let mut rgba = [255; 4];
+            for (i, c) in digits.chars().enumerate() {
+                let v = c.to_digit(16)? as u8;
+                rgba[i] = v * 16 + v;
+            }
+            Some(rgba)
+        }
+        6 => Some([channel(0)?, channel(2)?, channel(4)?, 255]),
+        8 => Some([channel(0)?, channel(2)?, channel(4)?, channel(6)?]),
+        _ => None,
+    }
+}
+
+/// Lowercase "#rrggbb", or "#rrggbbaa" when the color element takes alpha
+pub fn format_hex_color([r, g, b, a]: [u8; 4], alpha: bool) -> String {
+    if alpha {
+        format!("#{:02x}{:02x}{:02x}{:02x}", r, g, b, a)
+    } else {
+        format!("#{:02x}{:02x}{:02x}", r, g, b)
+    }
+}
+
+/// A tags element's `pattern`, compiled once when the element is read
+#[derive(Debug, Clone)]
+pub struct TagPattern {
+    source: String,
+    /// `source` anchored at both ends, so it has to match a whole tag
+    regex: regex::Regex,
+}
+
+impl TagPattern {
+    pub fn new(source: &str) -> Result<Self, regex::Error> {
+        Ok(Self {
+            source: source.to_string(),
+            regex: regex::Regex::new(&format!("^(?:{})$", source))?,
+        })
+    }
+
+    /// The pattern as written
+    pub fn as_str(&self) -> &str {
+        &self.source
+    }
+}
+
+impl PartialEq for TagPattern {
+    fn eq(&self, other: &Self) -> bool {
+        self.source == other.source
+    }
+}
+
+/// Whether `tag` matches `pattern` in full (any tag passes without a pattern)
+pub fn check_tag(tag: &str, pattern: Option<&TagPattern>) -> Result<(), String> {
+    match pattern {
+        Some(pattern) if !pattern.regex.is_match(tag) => Err(format!(
+            "`{}` doesn't match the pattern `{}`",
+            tag,
+            pattern.as_str()
+        )),
+        _ => Ok(()),
+    }
+}
+
+/// First key (ignoring surrounding whitespace) that appears more than once
+pub fn duplicate_key(pairs: &[(String, String)]) -> Option<&str> {
+    let mut seen = std::collections::HashSet::new();
+    pairs
+        .iter()
+        .map(|(key, _)| key.trim())
+        .filter(|key| !key.is_empty())
+        .find(|key| !seen.insert(*key))
+}
+
+impl ElementValue {
+    /// Whether the value counts as an answer for `required`
+    pub fn is_answered(&self) -> bool {
+        match self {
+            ElementValue::Number(_) => true,
+            ElementValue::Range(..) => true,
+            // A required checkbox must be checked (e.g. "I understand the risks")
+            ElementValue::Boolean(checked) => *checked,
+            ElementValue::Text(text) => !text.trim().is_empty(),
+            ElementValue::MultiChoice(selections) => selections.contains(&true),
+            ElementValue::Choice(selection) => selection.is_some(),
+            // Every hunk needs a verdict
+            ElementValue::Review(reviews) => reviews.iter().all(|r| r.decision.is_some()),
+            ElementValue::Rows(keys) => !keys.is_empty(),
+            ElementValue::List(rows) => !rows.is_empty(),
+            ElementValue::Tags(tags) => !tags.is_empty(),
+            ElementValue::Paths(paths) => !paths.is_empty(),
+            ElementValue::Rating(score) => score.is_some(),
+            // Every statement needs an answer
+            ElementValue::Matrix(picks) => picks.iter().all(Option::is_some),
+            ElementValue::Pairs(pairs) => pairs
+                .iter()
+                .any(|(key, value)| !key.trim().is_empty() && !value.trim().is_empty()),
+        }
+    }
+}
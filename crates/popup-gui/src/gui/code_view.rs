@@ -0,0 +1,110 @@
+//! Rendering for the `code` element
+
+use eframe::egui;
+use egui::RichText;
+use egui_extras::syntax_highlighting::{highlight, CodeTheme};
+use popup_common::PopupState;
+
+use super::RenderContext;
+
+pub(super) struct CodeBlock<'a> {
+    pub code: &'a str,
+    pub id: Option<&'a str>,
+    pub language: &'a str,
+    pub editable: bool,
+    pub line_numbers: bool,
+}
+
+pub(super) fn render_code(
+    ui: &mut egui::Ui,
+    block: CodeBlock,
+    state: &mut PopupState,
+    ctx: &RenderContext,
+) {
+    let theme = CodeTheme::from_style(ui.style());
+    let language = block.language;
+
+    egui::Frame::NONE
+        .inner_margin(egui::Margin::symmetric(8, 6))
+        .fill(ctx.theme.deep_black)
+        .stroke(egui::Stroke::new(1.0, ctx.theme.dark_gray))
+        .show(ui, |ui| {
+            ui.set_min_width(ui.available_width());
+
+            let edited = match (block.editable, block.id) {
+                (true, Some(id)) => state.get_text_mut(id),
+                _ => None,
+            };
+            let text = edited
+                .as_deref()
+                .map_or(block.code, String::as_str)
+                .to_string();
+
+            ui.horizontal(|ui| {
+                if !language.is_empty() {
+                    ui.label(
+                        RichText::new(language)
+                            .small()
+                            .color(ctx.theme.text_secondary),
+                    );
+                }
+                ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
+                    if ui.small_button("Copy").clicked() {
+                        ui.ctx().copy_text(text.clone());
+                    }
+                });
+            });
+
+            egui::ScrollArea::horizontal()
+                .id_salt("code_scroll")
+                .show(ui, |ui| {
+                    ui.horizontal_top(|ui| {
+                        if block.line_numbers {
+                            // The editor shows a line after a trailing newline; labels don't
+                            let count = if edited.is_some() {
+                                text.split('\n').count()
+                            } else {
+                                text.lines().count().max(1)
+                            };
+                            let width = count.to_string().len();
+                            let numbers = (1..=count)
+                                .map(|n| format!("{:>width$}", n))
+                                .collect::<Vec<_>>()
+                                .join("\n");
+                            ui.add(
+                                egui::Label::new(
+                                    RichText::new(numbers)
+                                        .monospace()
+                                        .color(ctx.theme.text_secondary),
+                                )
+                                .selectable(false),
+                            );
+                        }
+
+                        match edited {
+                            Some(value) => {
+                                let mut layouter = |ui: &egui::Ui, text: &str, _wrap_width: f32| {
+                                    // No wrapping, so the gutter stays aligned with the lines
+                                    let job =
+                                        highlight(ui.ctx(), ui.style(), &theme, text, language);
+                                    ui.fonts(|fonts| fonts.layout_job(job))
+                                };
+                                ui.add(
+                                    egui::TextEdit::multiline(value)
+                                        .code_editor()
+                                        .frame(false)
+                                        .margin(egui::Margin::ZERO)
+                                        .desired_rows(1)
+                                        .desired_width(f32::INFINITY)
+                                        .layouter(&mut layouter),
+                                );
+                            }
+                            None => {
+                                let job = highlight(ui.ctx(), ui.style(), &theme, &text, language);
+                                ui.add(egui::Label::new(job).selectable(true).extend());
+                            }
+                        }
+                    });
+                });
+        });
+}
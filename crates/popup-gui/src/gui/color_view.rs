@@ -0,0 +1,96 @@
+//! Rendering for the `color` picker and its preset swatches
+
+use eframe::egui;
+use egui::color_picker::{color_edit_button_srgba, Alpha};
+use egui::{Color32, RichText, Sense};
+use popup_common::validation::{format_hex_color, parse_hex_color};
+use popup_common::PopupState;
+
+use super::RenderContext;
+
+/// Side of a palette swatch
+const SWATCH_SIZE: f32 = 20.0;
+
+pub(super) struct ColorData<'a> {
+    pub label: &'a str,
+    pub id: &'a str,
+    pub alpha: bool,
+    pub palette: &'a [String],
+}
+
+pub(super) fn render_color(
+    ui: &mut egui::Ui,
+    data: ColorData,
+    state: &mut PopupState,
+    ctx: &mut RenderContext,
+) {
+    let Some(hex) = state.get_text_mut(data.id) else {
+        return;
+    };
+    let current = parse_hex_color(hex);
+
+    ui.vertical(|ui| {
+        ui.label(
+            RichText::new(data.label)
+                .color(ctx.theme.matrix_green)
+                .strong()
+                .size(15.0),
+        );
+
+        ui.horizontal(|ui| {
+            // Nothing picked yet opens the picker on mid gray
+            let [r, g, b, a] = current.unwrap_or([128, 128, 128, 255]);
+            let mut color = Color32::from_rgba_unmultiplied(r, g, b, a);
+            let alpha = if data.alpha {
+                Alpha::OnlyBlend
+            } else {
+                Alpha::Opaque
+            };
+            let response = color_edit_button_srgba(ui, &mut color, alpha);
+            if ctx.first_widget_id.is_none() && !ctx.widget_focused {
+                *ctx.first_widget_id = Some(response.id);
+            }
+            if response.changed() {
+                *hex = format_hex_color(color.to_srgba_unmultiplied(), data.alpha);
+            }
+
+            let shown = if hex.is_empty() {
+                RichText::new("(none)").color(ctx.theme.text_secondary)
+            } else {
+                RichText::new(hex.as_str())
+                    .monospace()
+                    .color(ctx.theme.base2)
+            };
+            ui.label(shown);
+        });
+
+        if !data.palette.is_empty() {
+            ui.horizontal_wrapped(|ui| {
+                for preset in data.palette {
+                    let Some([r, g, b, a]) = parse_hex_color(preset) else {
+                        continue;
+                    };
+                    let (rect, response) = ui
+                        .allocate_exact_size(egui::vec2(SWATCH_SIZE, SWATCH_SIZE), Sense::click());
+                    let stroke = if preset == hex {
+                        egui::Stroke::new(2.0, ctx.theme.neon_cyan)
+                    } else if response.hovered() {
+                        egui::Stroke::new(1.0, ctx.theme.text_primary)
+                    } else {
+                        egui::Stroke::new(1.0, ctx.theme.text_secondary)
+                    };
+                    ui.painter().rect_filled(
+                        rect,
+                        3.0,
+                        Color32::from_rgba_unmultiplied(r, g, b, a),
+                    );
+                    ui.painter()
+                        .rect_stroke(rect, 3.0, stroke, egui::StrokeKind::Inside);
+                    if response.on_hover_text(preset.as_str()).clicked() {
+                        *hex = preset.clone();
+                    }
+                }
+            });
+        }
+    });
+}
@@ -0,0 +1,116 @@
+//! Rendering for the `confirm_phrase` element and the danger footer it adds
+
+use eframe::egui;
+use egui::RichText;
+use popup_common::{validation::phrase_matches, Element, PopupState};
+
+use super::RenderContext;
+
+pub(super) struct ConfirmData<'a> {
+    pub phrase: &'a str,
+    pub id: &'a str,
+    pub label: Option<&'a str>,
+    pub case_sensitive: bool,
+}
+
+pub(super) fn render_confirm_phrase(
+    ui: &mut egui::Ui,
+    data: ConfirmData,
+    state: &mut PopupState,
+    ctx: &mut RenderContext,
+) {
+    let Some(typed) = state.get_text_mut(data.id) else {
+        return;
+    };
+    let matched = phrase_matches(typed, data.phrase, data.case_sensitive);
+
+    egui::Frame::NONE
+        .inner_margin(egui::Margin::same(8))
+        .stroke(egui::Stroke::new(1.0, ctx.theme.warning_orange))
+        .show(ui, |ui| {
+            ui.set_min_width(ui.available_width());
+            ui.label(
+                RichText::new(format!(
+                    "⚠ {}",
+                    data.label.unwrap_or("This can't be undone")
+                ))
+                .color(ctx.theme.warning_orange)
+                .strong()
+                .size(15.0),
+            );
+            ui.horizontal_wrapped(|ui| {
+                ui.label(RichText::new("Type").color(ctx.theme.text_primary));
+                ui.label(
+                    RichText::new(data.phrase)
+                        .monospace()
+                        .color(ctx.theme.base2),
+                );
+                let note = if data.case_sensitive {
+                    "to confirm:"
+                } else {
+                    "to confirm (any case):"
+                };
+                ui.label(RichText::new(note).color(ctx.theme.text_primary));
+            });
+            ui.horizontal(|ui| {
+                let response = ui.add(
+                    egui::TextEdit::singleline(typed)
+                        .font(egui::TextStyle::Monospace)
+                        .text_color(ctx.theme.base2)
+                        .desired_width(ui.available_width().min(400.0)),
+                );
+                if ctx.first_widget_id.is_none() && !ctx.widget_focused {
+                    *ctx.first_widget_id = Some(response.id);
+                }
+                if matched {
+                    ui.label(RichText::new("✓").color(ctx.theme.matrix_green).strong());
+                }
+            });
+        });
+}
+
+/// Whether any shown confirm_phrase element exists, and whether one of them
+/// is still waiting for its phrase
+#[derive(Default)]
+pub(super) struct ConfirmStatus {
+    pub shown: bool,
+    pub pending: bool,
+}
+
+pub(super) fn confirm_status(
+    elements: &[Element],
+    state: &PopupState,
+    active_ids: &[String],
+) -> ConfirmStatus {
+    let mut status = ConfirmStatus::default();
+    collect_confirm_status(elements, state, active_ids, &mut status);
+    status
+}
+
+fn collect_confirm_status(
+    elements: &[Element],
+    state: &PopupState,
+    active_ids: &[String],
+    status: &mut ConfirmStatus,
+) {
+    for element in elements {
+        if let Element::ConfirmPhrase {
+            confirm_phrase,
+            id,
+            case_sensitive,
+            ..
+        } = element
+        {
+            if active_ids.contains(id) {
+                status.shown = true;
+                let typed = state.get_text(id).map_or("", String::as_str);
+                if !phrase_matches(typed, confirm_phrase, *case_sensitive) {
+                    status.pending = true;
+                }
+            }
+        }
+        for children in element.children() {
+            collect_confirm_status(children, state, active_ids, status);
+        }
+    }
+}
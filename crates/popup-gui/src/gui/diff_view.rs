@@ -0,0 +1,175 @@
+//! Rendering for the `diff` review element
+
+use eframe::egui;
+use egui::{Color32, RichText};
+use popup_common::diff::{parse_unified_diff, FileDiff, LineKind};
+use popup_common::{HunkDecision, PopupState};
+
+use super::RenderContext;
+
+/// Width of each line-number gutter column, in characters
+const GUTTER: usize = 4;
+
+pub(super) fn render_diff(
+    ui: &mut egui::Ui,
+    id: &str,
+    label: Option<&str>,
+    diff: &str,
+    state: &mut PopupState,
+    ctx: &mut RenderContext,
+) {
+    let files = ctx
+        .diff_cache
+        .entry(diff.to_string())
+        .or_insert_with(|| parse_unified_diff(diff))
+        .clone();
+    let Some(reviews) = state.get_review_mut(id) else {
+        return;
+    };
+
+    ui.vertical(|ui| {
+        ui.horizontal(|ui| {
+            if let Some(label) = label {
+                ui.label(
+                    RichText::new(label)
+                        .color(ctx.theme.matrix_green)
+                        .strong()
+                        .size(15.0),
+                );
+            }
+            let decided = reviews.iter().filter(|r| r.decision.is_some()).count();
+            ui.label(
+                RichText::new(format!("{} of {} hunks reviewed", decided, reviews.len()))
+                    .color(ctx.theme.text_secondary),
+            );
+            if ui.button("Accept all").clicked() {
+                reviews
+                    .iter_mut()
+                    .for_each(|r| r.decision = Some(HunkDecision::Accept));
+            }
+            if ui.button("Reject all").clicked() {
+                reviews
+                    .iter_mut()
+                    .for_each(|r| r.decision = Some(HunkDecision::Reject));
+            }
+        });
+
+        if files.iter().all(|f| f.hunks.is_empty()) {
+            ui.label(RichText::new("(empty diff)").color(ctx.theme.text_secondary));
+            return;
+        }
+
+        // Reviews are stored flat, in file then hunk order
+        let mut review_idx = 0;
+        for (file_idx, file) in files.iter().enumerate() {
+            render_file_header(ui, file, ctx);
+            for (hunk_idx, hunk) in file.hunks.iter().enumerate() {
+                let Some(review) = reviews.get_mut(review_idx) else {
+                    return;
+                };
+                review_idx += 1;
+
+                ui.push_id(("hunk", id, file_idx, hunk_idx), |ui| {
+                    egui::Frame::NONE
+                        .inner_margin(egui::Margin::same(4))
+                        .stroke(egui::Stroke::new(1.0, decision_color(review.decision, ctx)))
+                        .show(ui, |ui| {
+                            ui.set_min_width(ui.available_width());
+                            ui.horizontal(|ui| {
+                                ui.label(
+                                    RichText::new(&hunk.header)
+                                        .monospace()
+                                        .color(ctx.theme.electric_blue),
+                                );
+                                ui.with_layout(
+                                    egui::Layout::right_to_left(egui::Align::Center),
+                                    |ui| {
+                                        for (decision, text) in [
+                                            (HunkDecision::Reject, "✗ Reject"),
+                                            (HunkDecision::Accept, "✓ Accept"),
+                                        ] {
+                                            let selected = review.decision == Some(decision);
+                                            let color = decision_color(Some(decision), ctx);
+                                            let text = RichText::new(text).color(color);
+                                            if ui.selectable_label(selected, text).clicked() {
+                                                // Clicking the current verdict clears it
+                                                review.decision = (!selected).then_some(decision);
+                                            }
+                                        }
+                                    },
+                                );
+                            });
+
+                            ui.spacing_mut().item_spacing.y = 0.0;
+                            for line in &hunk.lines {
+                                render_line(ui, line, ctx);
+                            }
+                            ui.spacing_mut().item_spacing.y = 4.0;
+
+                            ui.add_space(4.0);
+                            ui.add(
+                                egui::TextEdit::singleline(&mut review.comment)
+                                    .hint_text("Comment on this hunk (optional)")
+                                    .text_color(ctx.theme.base2)
+                                    .desired_width(f32::INFINITY),
+                            );
+                        });
+                });
+                ui.add_space(4.0);
+            }
+        }
+    });
+}
+
+fn render_file_header(ui: &mut egui::Ui, file: &FileDiff, ctx: &RenderContext) {
+    let path = if file.path.is_empty() {
+        "(unnamed file)"
+    } else {
+        &file.path
+    };
+    ui.add_space(4.0);
+    ui.label(
+        RichText::new(path)
+            .monospace()
+            .strong()
+            .color(ctx.theme.neon_cyan),
+    );
+}
+
+fn render_line(ui: &mut egui::Ui, line: &popup_common::diff::DiffLine, ctx: &RenderContext) {
+    let (sign, color, fill) = match line.kind {
+        LineKind::Context => (' ', ctx.theme.text_primary, Color32::TRANSPARENT),
+        LineKind::Added => (
+            '+',
+            ctx.theme.matrix_green,
+            ctx.theme.matrix_green.gamma_multiply(0.12),
+        ),
+        LineKind::Removed => (
+            '-',
+            ctx.theme.neon_pink,
+            ctx.theme.neon_pink.gamma_multiply(0.12),
+        ),
+    };
+    let number =
+        |n: Option<u32>| n.map_or_else(|| " ".repeat(GUTTER), |n| format!("{:>GUTTER$}", n));
+    let text = format!(
+        "{} {} {}{}",
+        number(line.old_line),
+        number(line.new_line),
+        sign,
+        line.text
+    );
+
+    egui::Frame::NONE.fill(fill).show(ui, |ui| {
+        ui.set_min_width(ui.available_width());
+        ui.label(RichText::new(text).monospace().color(color));
+    });
+}
+
+fn decision_color(decision: Option<HunkDecision>, ctx: &RenderContext) -> Color32 {
+    match decision {
+        Some(HunkDecision::Accept) => ctx.theme.matrix_green,
+        Some(HunkDecision::Reject) => ctx.theme.neon_pink,
+        None => ctx.theme.text_secondary,
+    }
+}
@@ -0,0 +1,94 @@
+//! Rendering for the `kv` key/value editor
+
+use eframe::egui;
+use egui::RichText;
+use popup_common::PopupState;
+
+use super::RenderContext;
+
+pub(super) struct KvData<'a> {
+    pub label: &'a str,
+    pub id: &'a str,
+    /// Fixed keys; empty when the user picks their own
+    pub keys: &'a [String],
+    pub placeholder: Option<&'a str>,
+}
+
+pub(super) fn render_kv(
+    ui: &mut egui::Ui,
+    data: KvData,
+    state: &mut PopupState,
+    ctx: &RenderContext,
+    element_path: &str,
+) {
+    let Some(pairs) = state.get_pairs_mut(data.id) else {
+        return;
+    };
+    let fixed = !data.keys.is_empty();
+    let mut remove = None;
+
+    ui.vertical(|ui| {
+        ui.label(
+            RichText::new(data.label)
+                .color(ctx.theme.matrix_green)
+                .strong()
+                .size(15.0),
+        );
+
+        let duplicates: Vec<String> = pairs
+            .iter()
+            .map(|(key, _)| key.trim())
+            .filter(|key| !key.is_empty())
+            .filter(|key| pairs.iter().filter(|(k, _)| k.trim() == *key).count() > 1)
+            .map(str::to_string)
+            .collect();
+
+        egui::Grid::new(("kv", element_path))
+            .num_columns(if fixed { 2 } else { 3 })
+            .spacing([8.0, 4.0])
+            .show(ui, |ui| {
+                for (i, (key, value)) in pairs.iter_mut().enumerate() {
+                    if fixed {
+                        ui.label(RichText::new(key.as_str()).color(ctx.theme.neon_cyan));
+                    } else {
+                        let duplicate = duplicates.iter().any(|d| d == key.trim());
+                        let color = if duplicate {
+                            ctx.theme.neon_pink
+                        } else {
+                            ctx.theme.neon_cyan
+                        };
+                        let response = ui.add(
+                            egui::TextEdit::singleline(key)
+                                .hint_text("key")
+                                .text_color(color)
+                                .desired_width(160.0),
+                        );
+                        if duplicate {
+                            response.on_hover_text("Duplicate key");
+                        }
+                    }
+
+                    let mut value_edit = egui::TextEdit::singleline(value)
+                        .text_color(ctx.theme.base2)
+                        .desired_width(ui.available_width().clamp(160.0, 400.0));
+                    if let Some(hint) = data.placeholder {
+                        value_edit = value_edit.hint_text(hint);
+                    }
+                    ui.add(value_edit);
+
+                    if !fixed && ui.small_button("✕").on_hover_text("Remove").clicked() {
+                        remove = Some(i);
+                    }
+                    ui.end_row();
+                }
+            });
+
+        if !fixed && ui.button("+ Add").clicked() {
+            pairs.push((String::new(), String::new()));
+        }
+    });
+
+    if let Some(i) = remove {
+        pairs.remove(i);
+    }
+}
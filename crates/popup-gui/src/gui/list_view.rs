@@ -0,0 +1,132 @@
+//! Rendering for the repeatable `list` element
+
+use eframe::egui;
+use egui::RichText;
+use popup_common::{Element, Layout, PopupState};
+
+use super::{render_elements_in_grid, RenderContext};
+
+pub(super) struct ListData<'a> {
+    pub label: &'a str,
+    pub id: &'a str,
+    pub template: &'a [Element],
+    pub min_items: usize,
+    pub max_items: Option<usize>,
+}
+
+enum RowAction {
+    Up(usize),
+    Down(usize),
+    Remove(usize),
+}
+
+pub(super) fn render_list(
+    ui: &mut egui::Ui,
+    data: ListData,
+    state: &mut PopupState,
+    ctx: &mut RenderContext,
+    element_path: &str,
+) {
+    let Some(rows) = state.get_list_mut(data.id) else {
+        return;
+    };
+    let can_add = data.max_items.is_none_or(|max| rows.len() < max);
+    let can_remove = rows.len() > data.min_items;
+    let mut action = None;
+
+    ui.vertical(|ui| {
+        ui.horizontal(|ui| {
+            ui.label(
+                RichText::new(data.label)
+                    .color(ctx.theme.matrix_green)
+                    .strong()
+                    .size(15.0),
+            );
+            let limits = match (data.min_items, data.max_items) {
+                (0, None) => String::new(),
+                (min, None) => format!(", at least {}", min),
+                (0, Some(max)) => format!(", at most {}", max),
+                (min, Some(max)) => format!(", {} to {}", min, max),
+            };
+            let noun = if rows.len() == 1 { "item" } else { "items" };
+            ui.label(
+                RichText::new(format!("{} {}{}", rows.len(), noun, limits))
+                    .color(ctx.theme.text_secondary),
+            );
+        });
+
+        let count = rows.len();
+        for (i, row) in rows.iter_mut().enumerate() {
+            let row_path = format!("{}.row{}", element_path, i);
+            egui::Frame::NONE
+                .inner_margin(egui::Margin::same(6))
+                .stroke(egui::Stroke::new(1.0, ctx.theme.dark_gray))
+                .show(ui, |ui| {
+                    ui.set_min_width(ui.available_width());
+                    ui.horizontal(|ui| {
+                        ui.label(RichText::new(format!("#{}", i + 1)).color(ctx.theme.neon_purple));
+                        ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
+                            if ui
+                                .add_enabled(can_remove, egui::Button::new("✕").small())
+                                .on_hover_text("Remove")
+                                .clicked()
+                            {
+                                action = Some(RowAction::Remove(i));
+                            }
+                            if ui
+                                .add_enabled(i + 1 < count, egui::Button::new("▼").small())
+                                .on_hover_text("Move down")
+                                .clicked()
+                            {
+                                action = Some(RowAction::Down(i));
+                            }
+                            if ui
+                                .add_enabled(i > 0, egui::Button::new("▲").small())
+                                .on_hover_text("Move up")
+                                .clicked()
+                            {
+                                action = Some(RowAction::Up(i));
+                            }
+                        });
+                    });
+
+                    // Each row is its own little popup: ids, conditions and widget
+                    // state stay inside it
+                    let mut row_state = PopupState {
+                        values: std::mem::take(row),
+                        button_clicked: None,
+                    };
+                    let row_values = row_state.condition_values(data.template, ctx.hidden_fields);
+                    let outer_values = std::mem::replace(&mut ctx.state_values, row_values);
+                    ui.push_id(&row_path, |ui| {
+                        render_elements_in_grid(
+                            ui,
+                            data.template,
+                            Layout::Auto,
+                            &mut row_state,
+                            ctx,
+                            &row_path,
+                        );
+                    });
+                    ctx.state_values = outer_values;
+                    *row = row_state.values;
+                });
+        }
+
+        if ui
+            .add_enabled(can_add, egui::Button::new("+ Add"))
+            .clicked()
+        {
+            rows.push(PopupState::list_row(data.template));
+        }
+    });
+
+    match action {
+        Some(RowAction::Up(i)) => rows.swap(i, i - 1),
+        Some(RowAction::Down(i)) => rows.swap(i, i + 1),
+        Some(RowAction::Remove(i)) => {
+            rows.remove(i);
+        }
+        None => {}
+    }
+}
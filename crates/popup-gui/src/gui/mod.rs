@@ -2,12 +2,30 @@ use anyhow::Result;
 use eframe::egui;
 use egui::{CentralPanel, Color32, Context, Id, Key, Rect, RichText, ScrollArea, TopBottomPanel, Vec2};
 use egui_commonmark::{CommonMarkCache, CommonMarkViewer};
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 use std::sync::{Arc, Mutex};
 
 use crate::theme::Theme;
-use popup_common::{evaluate_condition, parse_condition};
-use popup_common::{ConditionExpr, Element, PopupDefinition, PopupResult, PopupState};
+use popup_common::diff::FileDiff;
+use popup_common::{evaluate_condition, explain_condition, parse_condition};
+use popup_common::{
+    ConditionExpr, Element, ElementValue, FieldError, HiddenFields, Layout, PopupDefinition,
+    OptionValue, PopupResult, PopupState, Span, Trace,
+};
+
+mod code_view;
+mod color_view;
+mod confirm_view;
+mod diff_view;
+mod kv_view;
+mod list_view;
+mod path_view;
+mod range_view;
+mod rating_view;
+mod search_view;
+mod secret_view;
+mod table_view;
+mod tags_view;
 
 #[cfg(test)]
 pub mod tests {
@@ -18,7 +36,95 @@ pub mod tests {
         state: &PopupState,
         all_elements: &[Element],
     ) -> Vec<String> {
-        super::collect_active_elements(elements, state, all_elements, "")
+        super::collect_active_elements(elements, state, all_elements, HiddenFields::Keep)
+    }
+
+    pub fn hidden_elements_for_test(
+        definition: &PopupDefinition,
+        state: &PopupState,
+    ) -> Vec<(String, String, String)> {
+        let state_values = definition.condition_values(state);
+        let mut hidden = Vec::new();
+        let mut secrets = HashSet::new();
+        super::collect_secret_ids(&definition.elements, &mut secrets);
+        super::collect_hidden_elements(
+            &definition.elements,
+            &state_values,
+            &secrets,
+            &mut HashMap::new(),
+            &mut hidden,
+        );
+        hidden.into_iter().map(|h| (h.name, h.when, h.reason)).collect()
+    }
+
+    pub fn column_major_for_test(items: usize, columns: usize) -> Vec<Vec<usize>> {
+        let items = (0..items).map(|idx| vec![idx]).collect();
+        super::column_major(items, columns)
+            .into_iter()
+            .map(|column| column.into_iter().flatten().collect())
+            .collect()
+    }
+
+    pub fn image_loads_for_test(src: &str) -> bool {
+        super::load_image_source(src).is_some()
+    }
+
+    pub fn answered_summary_for_test(elements: &[Element], state: &PopupState) -> String {
+        let state_values = state.condition_values(elements, HiddenFields::Keep);
+        super::answered_summary(elements, state, &state_values)
+    }
+
+    pub fn option_matches_for_test(options: &[OptionValue], query: &str) -> Vec<usize> {
+        super::search_view::matching_options(options, query)
+    }
+
+    /// Half-typed text of every tags input after one headless frame, keyed by
+    /// element path; `typed` is what the inputs held going in
+    pub fn tag_inputs_after_frame_for_test(
+        definition: PopupDefinition,
+        typed: &[(&str, &str)],
+    ) -> Vec<(String, String)> {
+        let mut app = PopupApp::new_with_result(definition, Arc::default());
+        for (path, text) in typed {
+            app.tag_inputs
+                .insert(path.to_string(), tags_view::TagInput::typed(text));
+        }
+        let _ = Context::default().run(egui::RawInput::default(), |ctx| {
+            CentralPanel::default().show(ctx, |ui| {
+                let mut render_ctx = RenderContext {
+                    theme: &app.theme,
+                    first_widget_id: &mut app.first_interactive_widget_id,
+                    widget_focused: true,
+                    markdown_cache: &mut app.markdown_cache,
+                    condition_cache: &mut app.condition_cache,
+                    hidden_fields: HiddenFields::Keep,
+                    state_values: app.state.condition_values(&app.all_elements, HiddenFields::Keep),
+                    tabs: &mut app.tabs,
+                    image_cache: &mut app.image_cache,
+                    diff_cache: &mut app.diff_cache,
+                    tables: &mut app.tables,
+                    tag_inputs: &mut app.tag_inputs,
+                    option_searches: &mut app.option_searches,
+                    revealed_secrets: &mut app.revealed_secrets,
+                    path_browsers: &mut app.path_browsers,
+                };
+                render_elements_in_grid(
+                    ui,
+                    &app.definition.elements,
+                    Layout::Auto,
+                    &mut app.state,
+                    &mut render_ctx,
+                    "",
+                );
+            });
+        });
+        let mut inputs: Vec<(String, String)> = app
+            .tag_inputs
+            .iter()
+            .map(|(path, input)| (path.clone(), input.text().to_string()))
+            .collect();
+        inputs.sort();
+        inputs
     }
 }
 
@@ -48,7 +154,7 @@ fn setup_custom_fonts(ctx: &Context) {
     );
     style.text_styles.insert(
         egui::TextStyle::Monospace,
-        egui::FontId::new(22.0, egui::FontFamily::Monospace), // was ~10, now 22
+        egui::FontId::new(14.0, egui::FontFamily::Monospace), // was ~10, now 14 (sits beside 17pt body text)
     );
 
     ctx.set_style(style);
@@ -64,10 +170,13 @@ pub fn render_popup(definition: PopupDefinition) -> Result<PopupResult> {
     let title = definition.effective_title().to_string();
     
     // Start wider if we have multiple elements to encourage 2-column layout immediately
-    let initial_size = if definition.elements.len() > 1 {
-        [650.0, 400.0]
+    let largest_page = definition.pages.iter().map(|p| p.elements.len()).max();
+    let item_count = largest_page.unwrap_or(definition.elements.len());
+    let width = preferred_width(definition.layout.unwrap_or_default(), item_count);
+    let initial_size = if item_count > 1 {
+        [width, 400.0]
     } else {
-        [400.0, 200.0]
+        [width, 200.0]
     };
 
     let options = eframe::NativeOptions {
@@ -114,6 +223,43 @@ struct PopupApp {
     frame_count: usize,
     markdown_cache: CommonMarkCache,
     condition_cache: HashMap<String, Option<ConditionExpr>>,
+    show_visibility_debug: bool,
+    /// Every element across pages (see `PopupDefinition::all_elements`)
+    all_elements: Vec<Element>,
+    /// Index into `definition.pages` (unused for single-page popups)
+    current_page: usize,
+    /// Validation errors blocking Next/Submit on the current page
+    field_errors: Vec<FieldError>,
+    /// Tab selection for each tabs group, keyed by element path
+    tabs: HashMap<String, TabState>,
+    /// Resolved image and icon sources, keyed by the source string
+    image_cache: HashMap<String, Option<egui::ImageSource<'static>>>,
+    /// Parsed diff elements, keyed by the diff text
+    diff_cache: HashMap<String, Vec<FileDiff>>,
+    /// Filter and sort state of each table, keyed by element path
+    tables: HashMap<String, table_view::TableView>,
+    /// Half-typed tag of each tags input, keyed by element path
+    tag_inputs: HashMap<String, tags_view::TagInput>,
+    /// Filter text of each searchable select/multi, keyed by element path
+    option_searches: HashMap<String, search_view::OptionSearch>,
+    /// Paths of secret inputs currently shown in clear text
+    revealed_secrets: HashSet<String>,
+    /// Folder each path picker is browsing, keyed by element path
+    path_browsers: HashMap<String, path_view::PathBrowser>,
+}
+
+/// Selected tab and tabs opened so far for one `layout: "tabs"` group
+#[derive(Default)]
+struct TabState {
+    selected: Option<usize>,
+    visited: HashSet<usize>,
+}
+
+/// Wizard navigation requested from the button bar
+enum PageNav {
+    Back,
+    Next,
+    Submit,
 }
 
 impl PopupApp {
@@ -122,6 +268,7 @@ impl PopupApp {
         result: Arc<Mutex<Option<PopupResult>>>,
     ) -> Self {
         let state = PopupState::new(&definition);
+        let all_elements = definition.all_elements().into_owned();
         Self {
             definition,
             state,
@@ -133,23 +280,145 @@ impl PopupApp {
             frame_count: 0,
             markdown_cache: CommonMarkCache::default(),
             condition_cache: HashMap::new(),
+            show_visibility_debug: false,
+            all_elements,
+            current_page: 0,
+            field_errors: Vec::new(),
+            tabs: HashMap::new(),
+            image_cache: HashMap::new(),
+            diff_cache: HashMap::new(),
+            tables: HashMap::new(),
+            tag_inputs: HashMap::new(),
+            option_searches: HashMap::new(),
+            revealed_secrets: HashSet::new(),
+            path_browsers: HashMap::new(),
         }
     }
 
+    fn is_wizard(&self) -> bool {
+        !self.definition.pages.is_empty()
+    }
+
+    /// Indices of pages whose `when` clause passes against `state_values`
+    fn visible_pages(&mut self, state_values: &HashMap<String, serde_json::Value>) -> Vec<usize> {
+        let condition_cache = &mut self.condition_cache;
+
+        (0..self.definition.pages.len())
+            .filter(|&idx| match &self.definition.pages[idx].when {
+                None => true,
+                Some(when_expr) => condition_cache
+                    .entry(when_expr.clone())
+                    .or_insert_with(|| parse_when(when_expr))
+                    .as_ref()
+                    .is_none_or(|ast| evaluate_condition(ast, state_values)),
+            })
+            .collect()
+    }
+
+    /// Check the current page (or the whole popup) for missing required fields
+    /// and invalid values, keeping any errors for display
+    fn validate_current_page(&mut self) -> bool {
+        let active_ids = collect_active_elements(
+            &self.all_elements,
+            &self.state,
+            &self.all_elements,
+            self.definition.hidden_field_mode(),
+        );
+        self.field_errors = self
+            .definition
+            .page_errors(self.current_page, &self.state, &active_ids);
+        self.field_errors.is_empty()
+    }
+
+    /// Check every shown page before submitting, since a page can appear behind
+    /// the user after they passed it; shows the first page with errors
+    fn validate_for_submit(&mut self) -> bool {
+        match self.definition.submit_errors(&self.state, &self.all_elements) {
+            Some((page, errors)) => {
+                if page != self.current_page {
+                    self.go_to_page(page);
+                }
+                self.field_errors = errors;
+                false
+            }
+            None => {
+                self.field_errors.clear();
+                true
+            }
+        }
+    }
+
+    fn go_to_page(&mut self, page: usize) {
+        self.current_page = page;
+        self.field_errors.clear();
+        // Focus the first widget of the new page
+        self.first_interactive_widget_id = None;
+        self.first_widget_focused = false;
+    }
+
+    /// Developer overlay (F12) listing elements hidden by their `when` clause
+    fn render_visibility_debug(&mut self, ctx: &Context) {
+        let state_values = self
+            .state
+            .condition_values(&self.all_elements, self.definition.hidden_field_mode());
+        let mut hidden = Vec::new();
+        let mut secrets = HashSet::new();
+        collect_secret_ids(&self.all_elements, &mut secrets);
+        collect_hidden_elements(
+            &self.all_elements,
+            &state_values,
+            &secrets,
+            &mut self.condition_cache,
+            &mut hidden,
+        );
+
+        egui::Window::new("Hidden elements (F12)")
+            .open(&mut self.show_visibility_debug)
+            .default_width(360.0)
+            .show(ctx, |ui| {
+                if hidden.is_empty() {
+                    ui.label(
+                        RichText::new("No elements hidden by conditions")
+                            .color(self.theme.text_secondary),
+                    );
+                    return;
+                }
+                ScrollArea::vertical().show(ui, |ui| {
+                    for element in &hidden {
+                        ui.label(
+                            RichText::new(&element.name).strong().color(self.theme.neon_cyan),
+                        );
+                        ui.label(
+                            RichText::new(format!("when: {}", element.when))
+                                .monospace()
+                                .color(self.theme.text_secondary),
+                        );
+                        ui.label(
+                            RichText::new(&element.reason)
+                                .monospace()
+                                .color(self.theme.warning_orange),
+                        );
+                        ui.separator();
+                    }
+                });
+            });
+    }
+
     fn send_result_and_close(&mut self, ctx: &Context) {
         // Collect only active element labels based on current state
         let active_labels = collect_active_elements(
-            &self.definition.elements,
+            &self.all_elements,
             &self.state,
-            &self.definition.elements,
-            "",
+            &self.all_elements,
+            self.definition.hidden_field_mode(),
         );
 
-        let popup_result = PopupResult::from_state_with_active_elements(
+        let mut popup_result = PopupResult::from_state_with_active_elements(
             &self.state,
             &self.definition,
             &active_labels,
         );
+        popup_result.move_secrets_to_files(&self.definition);
         *self.result.lock().unwrap() = Some(popup_result);
         // Use ViewportCommand::Close to close the window
         ctx.send_viewport_cmd(egui::ViewportCommand::Close);
@@ -168,6 +437,15 @@ impl eframe::App for PopupApp {
             self.state.button_clicked = Some("cancel".to_string());
         }
 
+        // F12 toggles the hidden-element debugger
+        if ctx.input(|i| i.key_pressed(Key::F12)) {
+            self.show_visibility_debug = !self.show_visibility_debug;
+        }
+
+        // Conditions are evaluated before rendering, and answers only change on
+        // input; after any, render again so what they show or hide catches up
+        let had_input = ctx.input(|i| !i.events.is_empty());
+
         // Check if we should close
         if self.state.button_clicked.is_some() {
             self.send_result_and_close(ctx);
@@ -176,28 +454,151 @@ impl eframe::App for PopupApp {
 
         // --- Phase 1: Render UI and Measure Size ---
 
+        // Visibility is settled once per frame; every `when` below reads these
+        let state_values = self
+            .state
+            .condition_values(&self.all_elements, self.definition.hidden_field_mode());
+
+        // Wizard pages: skip past pages whose `when` no longer holds
+        let visible_pages = self.visible_pages(&state_values);
+        if self.is_wizard() && !visible_pages.contains(&self.current_page) {
+            let next = visible_pages.iter().find(|&&p| p > self.current_page);
+            if let Some(&page) = next.or(visible_pages.last()) {
+                self.go_to_page(page);
+            }
+        }
+        let page_position = visible_pages.iter().position(|&p| p == self.current_page);
+
+        // A confirm_phrase shown on this page turns the footer into a danger zone
+        // and holds Submit until its phrase is typed. Earlier pages are checked
+        // by Next and again on Submit.
+        let active_ids = self.state.active_element_ids(&self.all_elements, &state_values);
+        let shown_elements = match self.definition.pages.get(self.current_page) {
+            Some(page) => &page.elements,
+            None => &self.definition.elements,
+        };
+        let confirm = confirm_view::confirm_status(shown_elements, &self.state, &active_ids);
+
         // Render the bottom panel and get its height
+        let mut nav = None;
         let bottom_panel_response = TopBottomPanel::bottom("submit_panel").show(ctx, |ui| {
             ui.add_space(8.0);
             ui.separator();
             ui.add_space(8.0);
-            ui.with_layout(egui::Layout::top_down(egui::Align::Center), |ui| {
+
+            for error in &self.field_errors {
+                let label = element_label(&self.all_elements, &error.id).unwrap_or(&error.id);
+                ui.label(
+                    RichText::new(format!("{}: {}", label, error.message))
+                        .color(self.theme.warning_orange),
+                );
+            }
+
+            if confirm.shown {
+                let note = if confirm.pending {
+                    "⚠ Destructive action: type the confirmation phrase to enable Submit"
+                } else {
+                    "⚠ Destructive action: Submit will go ahead"
+                };
+                ui.label(RichText::new(note).color(self.theme.warning_orange).strong());
+            }
+
+            let submit_button = |ui: &mut egui::Ui| {
                 let button_text = RichText::new("SUBMIT")
                     .size(18.0)
                     .strong()
                     .color(self.theme.base2);
+                let fill = if confirm.shown {
+                    self.theme.warning_orange.linear_multiply(0.4)
+                } else {
+                    self.theme.neon_pink.linear_multiply(0.2)
+                };
                 let button = egui::Button::new(button_text)
                     .min_size(egui::Vec2::new(120.0, 40.0))
-                    .fill(self.theme.neon_pink.linear_multiply(0.2));
+                    .fill(fill);
+                ui.add_enabled(!confirm.pending, button).clicked()
+            };
 
-                if ui.add(button).clicked() {
-                    self.state.button_clicked = Some("submit".to_string());
+            match page_position {
+                Some(position) if self.is_wizard() => {
+                    let page_count = visible_pages.len();
+                    ui.add(
+                        egui::ProgressBar::new((position + 1) as f32 / page_count as f32)
+                            .desired_height(6.0)
+                            .fill(self.theme.neon_cyan),
+                    );
+                    ui.add_space(6.0);
+                    ui.horizontal(|ui| {
+                        if position > 0 && ui.button("← Back").clicked() {
+                            nav = Some(PageNav::Back);
+                        }
+                        ui.label(
+                            RichText::new(format!("Step {} of {}", position + 1, page_count))
+                                .color(self.theme.text_secondary),
+                        );
+                        ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
+                            if position + 1 == page_count {
+                                if submit_button(ui) {
+                                    nav = Some(PageNav::Submit);
+                                }
+                            } else if ui
+                                .add(egui::Button::new(RichText::new("Next →").strong()))
+                                .clicked()
+                            {
+                                nav = Some(PageNav::Next);
+                            }
+                        });
+                    });
                 }
-            });
+                _ => {
+                    ui.with_layout(egui::Layout::top_down(egui::Align::Center), |ui| {
+                        if submit_button(ui) {
+                            nav = Some(PageNav::Submit);
+                        }
+                    });
+                }
+            }
             ui.add_space(8.0);
         });
         let bottom_panel_height = bottom_panel_response.response.rect.height();
 
+        // Each page is validated before leaving it forwards; Back never blocks
+        match nav {
+            Some(PageNav::Back) => {
+                if let Some(&page) = page_position.and_then(|p| visible_pages.get(p.checked_sub(1)?)) {
+                    self.go_to_page(page);
+                }
+            }
+            Some(PageNav::Next) if self.validate_current_page() => {
+                if let Some(&page) = page_position.and_then(|p| visible_pages.get(p + 1)) {
+                    self.go_to_page(page);
+                }
+            }
+            Some(PageNav::Submit) if self.validate_for_submit() => {
+                self.state.button_clicked = Some("submit".to_string());
+            }
+            _ => {}
+        }
+
+        // Single-page popups render `elements`; wizards render the current page
+        let (page_title, page_elements, path_prefix) = match self.definition.pages.get(self.current_page) {
+            Some(page) if page_position.is_some() => (Some(&page.title), &page.elements, format!("page{}", self.current_page)),
+            Some(_) => (None, &Vec::new(), String::new()),
+            None => (None, &self.definition.elements, String::new()),
+        };
+
+        // Calculate a "preferred" width based on the complexity of the visible elements
+        // This helps break the circular dependency where desired_width is constrained by current width.
+        let condition_cache = &mut self.condition_cache;
+        let visible_item_count = page_elements.iter().filter(|e| match e.when() {
+            Some(when_expr) => condition_cache
+                .entry(when_expr.to_string())
+                .or_insert_with(|| parse_when(when_expr))
+                .as_ref()
+                .is_none_or(|ast| evaluate_condition(ast, &state_values)),
+            None => true,
+        }).count();
+
         // Render the main content and measure its size
         CentralPanel::default()
             .show(ctx, |ui| {
@@ -215,20 +616,34 @@ impl eframe::App for PopupApp {
                 .show(ui, |ui| {
                     // Use a scope to measure the content rect
                     let content_response = ui.scope(|ui| {
+                        if let Some(title) = page_title {
+                            ui.label(RichText::new(title).heading().strong().color(self.theme.neon_cyan));
+                            ui.add_space(6.0);
+                        }
                         let mut render_ctx = RenderContext {
                             theme: &self.theme,
                             first_widget_id: &mut self.first_interactive_widget_id,
                             widget_focused: self.first_widget_focused,
                             markdown_cache: &mut self.markdown_cache,
                             condition_cache: &mut self.condition_cache,
+                            hidden_fields: self.definition.hidden_field_mode(),
+                            state_values,
+                            tabs: &mut self.tabs,
+                            image_cache: &mut self.image_cache,
+                            diff_cache: &mut self.diff_cache,
+                            tables: &mut self.tables,
+                            tag_inputs: &mut self.tag_inputs,
+                            option_searches: &mut self.option_searches,
+                            revealed_secrets: &mut self.revealed_secrets,
+                            path_browsers: &mut self.path_browsers,
                         };
                         render_elements_in_grid(
                             ui,
-                            &self.definition.elements,
+                            page_elements,
+                            self.definition.layout.unwrap_or_default(),
                             &mut self.state,
-                            &self.definition.elements,
                             &mut render_ctx,
-                            "",
+                            &path_prefix,
                         );
                     });
                     // Store the measured rect in temporary memory to access it after the panel is drawn
@@ -240,6 +655,10 @@ impl eframe::App for PopupApp {
             });
         });
 
+        if had_input {
+            ctx.request_repaint();
+        }
+
         // --- Phase 2: Calculate Desired Size and Resize ---
 
         // Retrieve the content rect from memory
@@ -253,35 +672,11 @@ impl eframe::App for PopupApp {
         let desired_width = content_rect.width() + chrome_w;
         let desired_height = content_rect.height() + chrome_h;
 
-        // Calculate a "preferred" width based on the complexity of the visible elements
-        // This helps break the circular dependency where desired_width is constrained by current width.
-        let visible_item_count = self.definition.elements.iter().filter(|e| {
-             let when = match e {
-                Element::Text { when, .. } => when,
-                Element::Markdown { when, .. } => when,
-                Element::Slider { when, .. } => when,
-                Element::Check { when, .. } => when,
-                Element::Input { when, .. } => when,
-                Element::Multi { when, .. } => when,
-                Element::Select { when, .. } => when,
-                Element::Group { when, .. } => when,
-            };
-            if let Some(w) = when {
-                let state_map = self.state.to_value_map(&self.definition.elements);
-                parse_condition(w).map(|ast| evaluate_condition(&ast, &state_map)).unwrap_or(true)
-            } else {
-                true
-            }
-        }).count();
-
-        let mut preferred_width = if visible_item_count > 1 {
-            650.0
-        } else {
-            400.0
-        };
+        let layout = self.definition.layout.unwrap_or_default();
+        let mut preferred_width = preferred_width(layout, visible_item_count);
 
         // If it's getting very tall, push the width out to encourage 2-column layout
-        if desired_height > 500.0 && visible_item_count > 2 {
+        if layout == Layout::Auto && desired_height > 500.0 && visible_item_count > 2 {
             preferred_width = 850.0;
         }
 
@@ -326,6 +721,10 @@ impl eframe::App for PopupApp {
                 self.first_widget_focused = true;
             }
         }
+
+        if self.show_visibility_debug {
+            self.render_visibility_debug(ctx);
+        }
     }
 }
 
@@ -337,43 +736,43 @@ struct RenderContext<'a> {
     widget_focused: bool,
     markdown_cache: &'a mut CommonMarkCache,
     condition_cache: &'a mut HashMap<String, Option<ConditionExpr>>,
+    hidden_fields: HiddenFields,
+    /// What `when` clauses see this frame; inside a list row, the row's values
+    state_values: HashMap<String, serde_json::Value>,
+    tabs: &'a mut HashMap<String, TabState>,
+    image_cache: &'a mut HashMap<String, Option<egui::ImageSource<'static>>>,
+    diff_cache: &'a mut HashMap<String, Vec<FileDiff>>,
+    tables: &'a mut HashMap<String, table_view::TableView>,
+    tag_inputs: &'a mut HashMap<String, tags_view::TagInput>,
+    option_searches: &'a mut HashMap<String, search_view::OptionSearch>,
+    revealed_secrets: &'a mut HashSet<String>,
+    path_browsers: &'a mut HashMap<String, path_view::PathBrowser>,
 }
 
 fn render_elements_in_grid(
     ui: &mut egui::Ui,
     elements: &[Element],
+    layout: Layout,
     state: &mut PopupState,
-    all_elements: &[Element],
     ctx: &mut RenderContext,
     path_prefix: &str,
 ) {
-    let state_values = state.to_value_map(all_elements);
+    if layout == Layout::Tabs {
+        render_tabs(ui, elements, state, ctx, path_prefix);
+        return;
+    }
 
     // 1. Identify visible elements and their original indices
     let mut visible_indices = Vec::new();
     for (idx, element) in elements.iter().enumerate() {
-        let when_clause = match element {
-            Element::Text { when, .. } => when,
-            Element::Markdown { when, .. } => when,
-            Element::Slider { when, .. } => when,
-            Element::Check { when, .. } => when,
-            Element::Input { when, .. } => when,
-            Element::Multi { when, .. } => when,
-            Element::Select { when, .. } => when,
-            Element::Group { when, .. } => when,
-        };
-
-        let is_visible = if let Some(when_expr) = when_clause {
+        let is_visible = if let Some(when_expr) = element.when() {
             let cached_expr = ctx.condition_cache
-                .entry(when_expr.clone())
-                .or_insert_with(|| parse_condition(when_expr).ok());
+                .entry(when_expr.to_string())
+                .or_insert_with(|| parse_when(when_expr));
             
             match cached_expr {
-                Some(ast) => evaluate_condition(ast, &state_values),
-                None => {
-                    log::warn!("Failed to parse when clause: {}", when_expr);
-                    true // fail-open
-                },
+                Some(ast) => evaluate_condition(ast, &ctx.state_values),
+                None => true, // fail-open
             }
         } else {
             true
@@ -417,61 +816,96 @@ fn render_elements_in_grid(
         i += 1;
     }
 
-    // 3. Render using columns if we have multiple items and enough space
-    let available_width = ui.available_width();
-    let use_columns = items.len() > 1 && available_width > 500.0;
+    // 3. Lay the items out; full-width items break the columns around them
+    let columns = match layout {
+        Layout::Row => {
+            ui.horizontal_wrapped(|ui| {
+                ui.spacing_mut().item_spacing.x = 24.0;
+                for item_indices in items {
+                    ui.vertical(|ui| {
+                        render_item_group(ui, item_indices, elements, state, ctx, path_prefix);
+                    });
+                }
+            });
+            return;
+        }
+        Layout::Single | Layout::Tabs => 1,
+        Layout::Columns(n) => n.max(1) as usize,
+        Layout::Auto if items.len() > 1 && ui.available_width() > 500.0 => 2,
+        Layout::Auto => 1,
+    };
 
-    if use_columns {
-        let mut left_items = Vec::new();
-        let mut right_items = Vec::new();
-        for (item_idx, item_indices) in items.into_iter().enumerate() {
-            if item_idx % 2 == 0 {
-                left_items.push(item_indices);
+    ui.vertical(|ui| {
+        let mut run = Vec::new();
+        for item_indices in items {
+            let full_width = columns > 1
+                && item_indices.len() == 1
+                && elements[item_indices[0]].span() == Some(Span::Full);
+            if full_width {
+                let run = column_major(std::mem::take(&mut run), columns);
+                render_columns(ui, run, elements, state, ctx, path_prefix);
+                render_item_group(ui, item_indices, elements, state, ctx, path_prefix);
+                ui.add_space(4.0);
             } else {
-                right_items.push(item_indices);
+                run.push(item_indices);
             }
         }
+        render_columns(ui, column_major(run, columns), elements, state, ctx, path_prefix);
+    });
+}
 
-        ui.horizontal_top(|ui| {
-            let total_width = ui.available_width();
-            let gap = 32.0;
-            let col_width = (total_width - gap) / 2.0;
-
-            // Left Column
-            let left_res = ui.allocate_ui_with_layout(
-                egui::vec2(col_width, 0.0),
-                egui::Layout::top_down(egui::Align::Min),
-                |ui| {
-                    for item_indices in left_items {
-                        render_item_group(
-                            ui,
-                            item_indices,
-                            elements,
-                            state,
-                            all_elements,
-                            ctx,
-                            path_prefix,
-                        );
-                        ui.add_space(4.0);
-                    }
-                },
-            );
+/// Split items into at most `columns` columns, filling each top to bottom so
+/// questions keep their reading order
+fn column_major(items: Vec<Vec<usize>>, columns: usize) -> Vec<Vec<Vec<usize>>> {
+    let per_column = items.len().div_ceil(columns.max(1)).max(1);
+    let mut items = items.into_iter().peekable();
+    let mut column_items = Vec::new();
+    while items.peek().is_some() {
+        column_items.push(items.by_ref().take(per_column).collect());
+    }
+    column_items
+}
 
-            // Divider spacing
-            let (sep_rect, _) = ui.allocate_at_least(egui::vec2(gap, 0.0), egui::Sense::hover());
+/// Render columns of items side by side with dividers between them
+fn render_columns(
+    ui: &mut egui::Ui,
+    column_items: Vec<Vec<Vec<usize>>>,
+    elements: &[Element],
+    state: &mut PopupState,
+    ctx: &mut RenderContext,
+    path_prefix: &str,
+) {
+    if column_items.len() <= 1 {
+        for item_indices in column_items.into_iter().flatten() {
+            render_item_group(ui, item_indices, elements, state, ctx, path_prefix);
+            ui.add_space(4.0);
+        }
+        return;
+    }
 
-            // Right Column
-            let right_res = ui.allocate_ui_with_layout(
+    ui.horizontal_top(|ui| {
+        let gap = 32.0;
+        let col_width = (ui.available_width() - gap * (column_items.len() - 1) as f32)
+            / column_items.len() as f32;
+        let mut height: f32 = 0.0;
+        let mut dividers = Vec::new();
+
+        for (col, column) in column_items.into_iter().enumerate() {
+            if col > 0 {
+                // Divider spacing
+                let (sep_rect, _) = ui.allocate_at_least(egui::vec2(gap, 0.0), egui::Sense::hover());
+                dividers.push(sep_rect);
+            }
+            let res = ui.allocate_ui_with_layout(
                 egui::vec2(col_width, 0.0),
                 egui::Layout::top_down(egui::Align::Min),
                 |ui| {
-                    for item_indices in right_items {
+                    for item_indices in column {
                         render_item_group(
                             ui,
                             item_indices,
                             elements,
                             state,
-                            all_elements,
                             ctx,
                             path_prefix,
                         );
@@ -479,34 +913,19 @@ fn render_elements_in_grid(
                     }
                 },
             );
+            height = height.max(res.response.rect.height());
+        }
 
-            // Vertical Divider (Solarized Violet - IDE split style)
-            let height = left_res.response.rect.height().max(right_res.response.rect.height());
-            let center_x = sep_rect.center().x;
+        // Vertical Divider (Solarized Violet - IDE split style)
+        for sep_rect in dividers {
             let top_y = sep_rect.top();
-            
             ui.painter().vline(
-                center_x,
+                sep_rect.center().x,
                 top_y..=(top_y + height),
                 egui::Stroke::new(1.0, ctx.theme.neon_purple),
             );
-        });
-    } else {
-        ui.vertical(|ui| {
-            for item_indices in items {
-                render_item_group(
-                    ui,
-                    item_indices,
-                    elements,
-                    state,
-                    all_elements,
-                    ctx,
-                    path_prefix,
-                );
-                ui.add_space(4.0);
-            }
-        });
-    }
+        }
+    });
 }
 
 fn render_item_group(
@@ -514,7 +933,6 @@ fn render_item_group(
     item_indices: Vec<usize>,
     elements: &[Element],
     state: &mut PopupState,
-    all_elements: &[Element],
     ctx: &mut RenderContext,
     path_prefix: &str,
 ) {
@@ -531,7 +949,7 @@ fn render_item_group(
                 } else {
                     format!("{}.{}", path_prefix, idx)
                 };
-                render_single_element(ui, &elements[idx], state, all_elements, ctx, &element_path);
+                render_single_element(ui, &elements[idx], state, ctx, &element_path);
             }
         });
     } else {
@@ -541,7 +959,7 @@ fn render_item_group(
             } else {
                 format!("{}.{}", path_prefix, idx)
             };
-            render_single_element(ui, &elements[idx], state, all_elements, ctx, &element_path);
+            render_single_element(ui, &elements[idx], state, ctx, &element_path);
         }
     }
 }
@@ -550,38 +968,20 @@ fn render_single_element(
     ui: &mut egui::Ui,
     element: &Element,
     state: &mut PopupState,
-    all_elements: &[Element],
     ctx: &mut RenderContext,
     element_path: &str,
 ) {
     // Check if element should be visible based on when clause
-    let when_clause = match element {
-        Element::Text { when, .. } => when,
-        Element::Markdown { when, .. } => when,
-        Element::Slider { when, .. } => when,
-        Element::Check { when, .. } => when,
-        Element::Input { when, .. } => when,
-        Element::Multi { when, .. } => when,
-        Element::Select { when, .. } => when,
-        Element::Group { when, .. } => when,
-    };
-
-    if let Some(when_expr) = when_clause {
-        let state_values = state.to_value_map(all_elements);
+    if let Some(when_expr) = element.when() {
         let cached_expr = ctx.condition_cache
-            .entry(when_expr.clone())
-            .or_insert_with(|| parse_condition(when_expr).ok());
+            .entry(when_expr.to_string())
+            .or_insert_with(|| parse_when(when_expr));
         
-        match cached_expr {
-            Some(ast) => {
-                if !evaluate_condition(ast, &state_values) {
-                    // Condition not met - don't render this element
-                    return;
-                }
-            },
-            None => {
-                // Log warning but render anyway (fail-open)
-                log::warn!("Failed to parse when clause: {}", when_expr);
+        // Unparseable conditions render anyway (fail-open)
+        if let Some(ast) = cached_expr {
+            if !evaluate_condition(ast, &ctx.state_values) {
+                // Condition not met - don't render this element
+                return;
             }
         }
     }
@@ -602,12 +1002,185 @@ fn render_single_element(
             });
         }
 
+        Element::Image {
+            image,
+            alt,
+            max_width,
+            max_height,
+            ..
+        } => {
+            ui.push_id(format!("image_{}", element_path), |ui| match cached_image(ctx, image) {
+                Some(source) => {
+                    let max_width = max_width.unwrap_or_else(|| ui.available_width());
+                    let mut widget = egui::Image::new(source)
+                        .fit_to_original_size(1.0)
+                        .max_width(max_width)
+                        .max_height(max_height.unwrap_or(400.0));
+                    if let Some(alt) = alt {
+                        widget = widget.alt_text(alt);
+                    }
+                    let response = ui.add(widget);
+                    if let Some(alt) = alt {
+                        response.on_hover_text(alt);
+                    }
+                }
+                None => {
+                    let name = alt.as_deref().unwrap_or(if image.starts_with("data:") {
+                        "inline image"
+                    } else {
+                        image
+                    });
+                    ui.label(
+                        RichText::new(format!("[image unavailable: {}]", name))
+                            .color(ctx.theme.warning_orange),
+                    );
+                }
+            });
+        }
+
+        Element::Code {
+            code,
+            id,
+            language,
+            editable,
+            line_numbers,
+            ..
+        } => {
+            ui.push_id(format!("code_{}", element_path), |ui| {
+                let block = code_view::CodeBlock {
+                    code,
+                    id: id.as_deref(),
+                    language: language.as_deref().unwrap_or(""),
+                    editable: *editable,
+                    line_numbers: *line_numbers,
+                };
+                code_view::render_code(ui, block, state, ctx);
+            });
+        }
+
+        Element::Table {
+            table,
+            id,
+            columns,
+            rows,
+            key,
+            selection,
+            ..
+        } => {
+            let data = table_view::TableData {
+                label: table,
+                id,
+                columns,
+                rows,
+                key: key.as_deref(),
+                selection: *selection,
+            };
+            table_view::render_table(ui, data, state, ctx, element_path);
+        }
+
+        Element::Rating {
+            rating,
+            id,
+            max,
+            low_label,
+            high_label,
+            style,
+            ..
+        } => {
+            let data = rating_view::RatingData {
+                label: rating,
+                id,
+                max: *max,
+                low_label: low_label.as_deref(),
+                high_label: high_label.as_deref(),
+                style: *style,
+            };
+            rating_view::render_rating(ui, data, state, ctx);
+        }
+
+        Element::Matrix {
+            matrix,
+            id,
+            rows,
+            columns,
+            ..
+        } => {
+            let data = rating_view::MatrixData {
+                label: matrix,
+                id,
+                rows,
+                columns,
+            };
+            rating_view::render_matrix(ui, data, state, ctx);
+        }
+
+        Element::Tags {
+            tags,
+            id,
+            suggestions,
+            max_tags,
+            pattern,
+            placeholder,
+            ..
+        } => {
+            let data = tags_view::TagsData {
+                label: tags,
+                id,
+                suggestions,
+                max_tags: *max_tags,
+                pattern: pattern.as_ref(),
+                placeholder: placeholder.as_deref(),
+            };
+            tags_view::render_tags(ui, data, state, ctx, element_path);
+        }
+
+        Element::Kv {
+            kv,
+            id,
+            keys,
+            placeholder,
+            ..
+        } => {
+            let data = kv_view::KvData {
+                label: kv,
+                id,
+                keys,
+                placeholder: placeholder.as_deref(),
+            };
+            kv_view::render_kv(ui, data, state, ctx, element_path);
+        }
+
+        Element::List {
+            list,
+            id,
+            elements,
+            min_items,
+            max_items,
+            ..
+        } => {
+            let data = list_view::ListData {
+                label: list,
+                id,
+                template: elements,
+                min_items: *min_items,
+                max_items: *max_items,
+            };
+            list_view::render_list(ui, data, state, ctx, element_path);
+        }
+
+        Element::Diff { diff, id, label, .. } => {
+            ui.push_id(format!("diff_{}", element_path), |ui| {
+                diff_view::render_diff(ui, id, label.as_deref(), diff, state, ctx);
+            });
+        }
+
         Element::Multi {
             multi,
             id,
             options,
             option_children,
             reveals,
+            searchable,
             ..
         } => {
             // No widget frame for Minimalist approach
@@ -632,47 +1205,66 @@ fn render_single_element(
                             if ui.button("Clear All").clicked() {
                                 selections.iter_mut().for_each(|s| *s = false);
                             }
+                            if *searchable {
+                                // Picks may be filtered out of view, so say how many there are
+                                let count = selections.iter().filter(|&&s| s).count();
+                                ui.label(
+                                    RichText::new(format!("{} of {} selected", count, selections.len()))
+                                        .color(ctx.theme.text_secondary),
+                                );
+                            }
                         });
                     });
 
                     ui.add_space(4.0);
 
-                    let available_width = ui.available_width();
-                    let num_cols = if available_width >= 800.0 { 3 } else { 2 };
-
-                    egui::Grid::new(format!("multi_grid_{}", id))
-                        .num_columns(num_cols)
-                        .spacing([20.0, 8.0])
-                        .show(ui, |ui| {
-                            for (i, option) in options.iter().enumerate() {
-                                if i < selections.len() {
-                                    // Constrain width for text wrapping
-                                    ui.push_id(format!("multi_{}_{}", id, i), |ui| {
-                                        ui.set_max_width(250.0);
-                                        ui.horizontal_wrapped(|ui| {
-                                            let mut value = selections[i];
-                                            let response = ui.checkbox(&mut value, "");
-                                            selections[i] = value;
-
-                                            // Separate wrapped label
-                                            ui.label(RichText::new(option.value()).color(ctx.theme.matrix_green));
-
-                                            if let Some(desc) = option.description() {
-                                                response.clone().on_hover_text(desc);
-                                            }
-
-                                            if ctx.first_widget_id.is_none() && !ctx.widget_focused && i == 0 {
-                                                *ctx.first_widget_id = Some(response.id);
-                                            }
+                    if *searchable {
+                        search_view::render_option_search(
+                            ui,
+                            element_path,
+                            options,
+                            search_view::Picked::Many(selections),
+                            ctx,
+                        );
+                    } else {
+                        let available_width = ui.available_width();
+                        let num_cols = if available_width >= 800.0 { 3 } else { 2 };
+
+                        egui::Grid::new(format!("multi_grid_{}", id))
+                            .num_columns(num_cols)
+                            .spacing([20.0, 8.0])
+                            .show(ui, |ui| {
+                                for (i, option) in options.iter().enumerate() {
+                                    if i < selections.len() {
+                                        // Constrain width for text wrapping
+                                        ui.push_id(format!("multi_{}_{}", id, i), |ui| {
+                                            ui.set_max_width(250.0);
+                                            ui.horizontal_wrapped(|ui| {
+                                                let mut value = selections[i];
+                                                let response = ui.checkbox(&mut value, "");
+                                                selections[i] = value;
+                                                option_icon(ui, ctx, option);
+
+                                                // Separate wrapped label
+                                                ui.label(RichText::new(option.value()).color(ctx.theme.matrix_green));
+
+                                                if let Some(desc) = option.description() {
+                                                    response.clone().on_hover_text(desc);
+                                                }
+
+                                                if ctx.first_widget_id.is_none() && !ctx.widget_focused && i == 0 {
+                                                    *ctx.first_widget_id = Some(response.id);
+                                                }
+                                            });
                                         });
-                                    });
-                                }
-                                // End row
-                                if (i + 1) % num_cols == 0 {
-                                    ui.end_row();
+                                    }
+                                    // End row
+                                    if (i + 1) % num_cols == 0 {
+                                        ui.end_row();
+                                    }
                                 }
-                            }
-                        });
+                            });
+                    }
 
                     selections.clone()
                 } else {
@@ -686,8 +1278,8 @@ fn render_single_element(
                                 render_elements_in_grid(
                                     ui,
                                     children,
+                                    Layout::Auto,
                                     state,
-                                    all_elements,
                                     ctx,
                                     &format!("{}.multiselect_{}", element_path, i),
                                 );
@@ -702,8 +1294,8 @@ fn render_single_element(
                         render_elements_in_grid(
                             ui,
                             reveals,
+                            Layout::Auto,
                             state,
-                            all_elements,
                             ctx,
                             element_path,
                         );
@@ -719,6 +1311,7 @@ fn render_single_element(
             options,
             option_children,
             reveals,
+            searchable,
             ..
         } => {
             ui.vertical(|ui| {
@@ -735,33 +1328,56 @@ fn render_single_element(
                             None => "(none selected)",
                         };
 
-                        let response = egui::ComboBox::from_id_salt(id)
-                            .selected_text(RichText::new(selected_text).color(ctx.theme.base2))
-                            .show_ui(ui, |ui| {
-                                if ui
-                                    .selectable_label(selected.is_none(), "(none selected)")
-                                    .clicked()
-                                {
-                                    *selected = None;
-                                }
-                                for (idx, option) in options.iter().enumerate() {
-                                    let response =
-                                        ui.selectable_label(*selected == Some(idx), option.value());
-                                    if let Some(desc) = option.description() {
-                                        response.clone().on_hover_text(desc);
+                        if *searchable {
+                            ui.label(RichText::new(selected_text).color(ctx.theme.base2));
+                            if selected.is_some() && ui.small_button("✕").on_hover_text("Clear").clicked() {
+                                *selected = None;
+                            }
+                        } else {
+                            let response = egui::ComboBox::from_id_salt(id)
+                                .selected_text(RichText::new(selected_text).color(ctx.theme.base2))
+                                .show_ui(ui, |ui| {
+                                    if ui
+                                        .selectable_label(selected.is_none(), "(none selected)")
+                                        .clicked()
+                                    {
+                                        *selected = None;
                                     }
-                                    if response.clicked() {
-                                        *selected = Some(idx);
+                                    for (idx, option) in options.iter().enumerate() {
+                                        let response = ui
+                                            .horizontal(|ui| {
+                                                option_icon(ui, ctx, option);
+                                                ui.selectable_label(*selected == Some(idx), option.value())
+                                            })
+                                            .inner;
+                                        if let Some(desc) = option.description() {
+                                            response.clone().on_hover_text(desc);
+                                        }
+                                        if response.clicked() {
+                                            *selected = Some(idx);
+                                        }
                                     }
-                                }
-                            });
+                                });
 
-                        if ctx.first_widget_id.is_none() && !ctx.widget_focused {
-                            *ctx.first_widget_id = Some(response.response.id);
+                            if ctx.first_widget_id.is_none() && !ctx.widget_focused {
+                                *ctx.first_widget_id = Some(response.response.id);
+                            }
                         }
                     }
                 });
 
+                if *searchable {
+                    if let Some(selected) = state.get_choice_mut(id) {
+                        search_view::render_option_search(
+                            ui,
+                            element_path,
+                            options,
+                            search_view::Picked::One(selected),
+                            ctx,
+                        );
+                    }
+                }
+
                 let selected_option = state.get_choice(id).flatten();
                 if let Some(idx) = selected_option {
                     if let Some(option_val) = options.get(idx) {
@@ -770,8 +1386,8 @@ fn render_single_element(
                                 render_elements_in_grid(
                                     ui,
                                     children,
+                                    Layout::Auto,
                                     state,
-                                    all_elements,
                                     ctx,
                                     &format!("{}.choice_{}", element_path, idx),
                                 );
@@ -782,7 +1398,7 @@ fn render_single_element(
 
                 if selected_option.is_some() && !reveals.is_empty() {
                     ui.indent(format!("choice_reveals_{}", id), |ui| {
-                        render_elements_in_grid(ui, reveals, state, all_elements, ctx, element_path);
+                        render_elements_in_grid(ui, reveals, Layout::Auto, state, ctx, element_path);
                     });
                 }
             });
@@ -804,8 +1420,8 @@ fn render_single_element(
                         render_elements_in_grid(
                             ui,
                             reveals,
+                            Layout::Auto,
                             state,
-                            all_elements,
                             ctx,
                             &format!("{}.checkbox", element_path),
                         );
@@ -900,8 +1516,115 @@ fn render_single_element(
             });
         }
 
+        Element::ConfirmPhrase {
+            confirm_phrase,
+            id,
+            label,
+            case_sensitive,
+            ..
+        } => {
+            let data = confirm_view::ConfirmData {
+                phrase: confirm_phrase,
+                id,
+                label: label.as_deref(),
+                case_sensitive: *case_sensitive,
+            };
+            confirm_view::render_confirm_phrase(ui, data, state, ctx);
+        }
+
+        Element::Color {
+            color,
+            id,
+            alpha,
+            palette,
+            ..
+        } => {
+            color_view::render_color(
+                ui,
+                color_view::ColorData {
+                    label: color,
+                    id,
+                    alpha: *alpha,
+                    palette,
+                },
+                state,
+                ctx,
+            );
+        }
+
+        Element::Range {
+            range,
+            id,
+            min,
+            max,
+            step,
+            ..
+        } => {
+            range_view::render_range(
+                ui,
+                range_view::RangeData {
+                    label: range,
+                    id,
+                    min: *min,
+                    max: *max,
+                    step: *step,
+                },
+                state,
+                ctx,
+            );
+        }
+
+        Element::Path {
+            path,
+            id,
+            root,
+            filters,
+            mode,
+            multiple,
+            ..
+        } => {
+            path_view::render_path(
+                ui,
+                path_view::PathData {
+                    label: path,
+                    id,
+                    root: root.as_deref(),
+                    filters,
+                    mode: *mode,
+                    multiple: *multiple,
+                },
+                state,
+                ctx,
+                element_path,
+            );
+        }
+
+        Element::Secret {
+            secret,
+            id,
+            placeholder,
+            ..
+        } => {
+            secret_view::render_secret(
+                ui,
+                secret_view::SecretData {
+                    label: secret,
+                    id,
+                    placeholder: placeholder.as_deref(),
+                },
+                state,
+                ctx,
+                element_path,
+            );
+        }
+
         Element::Group {
-            group, elements, ..
+            group,
+            elements,
+            layout,
+            collapsed,
+            summary,
+            ..
         } => {
             // Minimal ghost frame for Groups (from B)
             let group_frame = egui::Frame::NONE
@@ -916,187 +1639,391 @@ fn render_single_element(
                 ));
 
             group_frame.show(ui, |ui| {
-                ui.horizontal(|ui| {
+                let header_id = ui.make_persistent_id(("group", element_path));
+                let header = egui::collapsing_header::CollapsingState::load_with_default_open(
+                    ui.ctx(),
+                    header_id,
+                    !collapsed,
+                );
+                let is_open = header.is_open();
+                let header = header.show_header(ui, |ui| {
                     ui.label(
                         RichText::new(group)
                             .color(ctx.theme.matrix_green)
                             .strong()
                             .size(16.0),
                     );
+                    // Folded groups show what's been filled in so far
+                    if !is_open {
+                        let text = summary.clone().unwrap_or_else(|| {
+                            answered_summary(elements, state, &ctx.state_values)
+                        });
+                        ui.label(RichText::new(text).color(ctx.theme.text_secondary).italics());
+                    }
+                });
+                header.body(|ui| {
+                    ui.add_space(4.0);
+                    render_elements_in_grid(
+                        ui,
+                        elements,
+                        layout.unwrap_or_default(),
+                        state,
+                        ctx,
+                        &format!("{}.group", element_path),
+                    );
                 });
-                ui.add_space(4.0);
-                render_elements_in_grid(
-                    ui,
-                    elements,
-                    state,
-                    all_elements,
-                    ctx,
-                    &format!("{}.group", element_path),
-                );
             });
         }
     }
 }
 
+/// Body of a tabs group: child groups become tabs, anything else stays above the tab bar
+fn render_tabs(
+    ui: &mut egui::Ui,
+    elements: &[Element],
+    state: &mut PopupState,
+    ctx: &mut RenderContext,
+    element_path: &str,
+) {
+    let visible_tabs: Vec<usize> = elements
+        .iter()
+        .enumerate()
+        .filter(|(_, element)| matches!(element, Element::Group { .. }))
+        .filter(|(_, element)| match element.when() {
+            Some(when_expr) => ctx
+                .condition_cache
+                .entry(when_expr.to_string())
+                .or_insert_with(|| parse_when(when_expr))
+                .as_ref()
+                .is_none_or(|ast| evaluate_condition(ast, &ctx.state_values)),
+            None => true,
+        })
+        .map(|(idx, _)| idx)
+        .collect();
+
+    // Fall back to the first visible tab when the selected one gets hidden
+    let tab_state = ctx.tabs.entry(element_path.to_string()).or_default();
+    let selected = match tab_state.selected {
+        Some(idx) if visible_tabs.contains(&idx) => Some(idx),
+        _ => visible_tabs.first().copied(),
+    };
+    if let Some(idx) = selected {
+        tab_state.selected = Some(idx);
+        tab_state.visited.insert(idx);
+    }
+
+    for (idx, element) in elements.iter().enumerate() {
+        if !matches!(element, Element::Group { .. }) {
+            let path = format!("{}.tabs.{}", element_path, idx);
+            render_single_element(ui, element, state, ctx, &path);
+        }
+    }
+
+    if visible_tabs.is_empty() {
+        return;
+    }
+
+    // Tab bar; visited tabs get a check mark so nothing is left unseen
+    ui.horizontal_wrapped(|ui| {
+        let tab_state = ctx.tabs.entry(element_path.to_string()).or_default();
+        for &idx in &visible_tabs {
+            let is_selected = tab_state.selected == Some(idx);
+            let title = elements[idx].label();
+            let text = if tab_state.visited.contains(&idx) && !is_selected {
+                RichText::new(format!("✓ {}", title)).color(ctx.theme.text_secondary)
+            } else if is_selected {
+                RichText::new(title).color(ctx.theme.matrix_green).strong()
+            } else {
+                RichText::new(title).color(ctx.theme.text_primary)
+            };
+            if ui.selectable_label(is_selected, text).clicked() {
+                tab_state.selected = Some(idx);
+                tab_state.visited.insert(idx);
+            }
+        }
+    });
+    ui.separator();
+
+    let Some(idx) = ctx.tabs.get(element_path).and_then(|t| t.selected) else {
+        return;
+    };
+    if let Element::Group { elements: tab_elements, layout, .. } = &elements[idx] {
+        let path = format!("{}.tab{}", element_path, idx);
+        let layout = layout.unwrap_or_default();
+        render_elements_in_grid(ui, tab_elements, layout, state, ctx, &path);
+    }
+}
+
 // Helper functions
 
-/// Collect only the active elements based on current state (evaluating when clauses)
-fn collect_active_elements(
+/// Largest side of an option's icon thumbnail
+const ICON_SIZE: f32 = 48.0;
+
+/// Thumbnail for an option with an `icon`; unreadable icons are skipped
+fn option_icon(ui: &mut egui::Ui, ctx: &mut RenderContext, option: &OptionValue) {
+    if let Some(source) = option.icon().and_then(|icon| cached_image(ctx, icon)) {
+        ui.add(
+            egui::Image::new(source)
+                .fit_to_original_size(1.0)
+                .max_width(ICON_SIZE)
+                .max_height(ICON_SIZE),
+        );
+    }
+}
+
+fn cached_image(ctx: &mut RenderContext, src: &str) -> Option<egui::ImageSource<'static>> {
+    ctx.image_cache
+        .entry(src.to_string())
+        .or_insert_with(|| load_image_source(src))
+        .clone()
+}
+
+/// Resolve a local path or base64 data URI for egui. Nothing is fetched over
+/// the network: remote URLs are rejected when the definition is parsed, and
+/// missing files or bad data give None so the alt text is shown instead.
+fn load_image_source(src: &str) -> Option<egui::ImageSource<'static>> {
+    use base64::Engine;
+    use std::hash::{Hash, Hasher};
+
+    if let Some(data) = src.strip_prefix("data:") {
+        let (header, payload) = data.split_once(',')?;
+        let mime = header.strip_suffix(";base64")?;
+        let bytes = base64::engine::general_purpose::STANDARD
+            .decode(payload.trim())
+            .ok()?;
+        // The extension lets egui_extras pick the svg or raster loader
+        let extension = match mime {
+            "image/svg+xml" => "svg",
+            "image/jpeg" => "jpg",
+            other => other.strip_prefix("image/")?,
+        };
+        let mut hasher = std::collections::hash_map::DefaultHasher::new();
+        payload.hash(&mut hasher);
+        return Some(egui::ImageSource::Bytes {
+            uri: format!("bytes://popup-image-{:x}.{}", hasher.finish(), extension).into(),
+            bytes: bytes.into(),
+        });
+    }
+
+    let path = std::fs::canonicalize(src.strip_prefix("file://").unwrap_or(src)).ok()?;
+    Some(egui::ImageSource::Uri(format!("file://{}", path.display()).into()))
+}
+
+/// Window width that suits the popup's layout with this many top-level items
+fn preferred_width(layout: Layout, item_count: usize) -> f32 {
+    match layout {
+        // Room for the requested columns rather than a guess
+        Layout::Columns(n) => (320.0 * n.clamp(1, 3) as f32).max(400.0),
+        Layout::Single => 450.0,
+        _ if item_count > 1 => 650.0,
+        _ => 400.0,
+    }
+}
+
+/// One-line digest of the answered fields in a collapsed group
+fn answered_summary(
     elements: &[Element],
     state: &PopupState,
-    all_elements: &[Element],
-    _path_prefix: &str,
-) -> Vec<String> {
-    let mut active_ids = Vec::new();
-    let state_values = state.to_value_map(all_elements);
-
-    // Helper to check if an element's when clause is satisfied
-    let is_visible = |when: &Option<String>| -> bool {
-        match when {
-            None => true, // No when clause means always visible
-            Some(when_expr) => {
-                // Parse and evaluate when clause
-                match parse_condition(when_expr) {
-                    Ok(ast) => evaluate_condition(&ast, &state_values),
-                    Err(_) => {
-                        // If parsing fails, default to visible (fail-open)
-                        log::warn!("Failed to parse when clause: {}", when_expr);
-                        true
-                    }
-                }
-            }
+    state_values: &HashMap<String, serde_json::Value>,
+) -> String {
+    const SHOWN: usize = 3;
+    let answers: Vec<String> = state
+        .active_element_ids(elements, state_values)
+        .iter()
+        .filter_map(|id| answer_text(find_element(elements, id)?, state))
+        .collect();
+
+    match answers.len() {
+        0 => "nothing answered yet".to_string(),
+        n if n <= SHOWN => answers.join(" · "),
+        n => format!("{} · +{} more", answers[..SHOWN].join(" · "), n - SHOWN),
+    }
+}
+
+/// "Label: value" for an answered field, or None if it's still empty
+fn answer_text(element: &Element, state: &PopupState) -> Option<String> {
+    let id = element.id()?;
+    let value = state.values.get(id).filter(|v| v.is_answered())?;
+    let text = match (element, value) {
+        (Element::Check { .. }, _) => return Some(element.label().to_string()),
+        (Element::Secret { .. }, _) => "••••••".to_string(),
+        (_, ElementValue::Number(n)) => format!("{}", n),
+        (_, ElementValue::Range(low, high)) => format!("{}–{}", low, high),
+        (_, ElementValue::Text(text)) if text.chars().count() > 24 => {
+            format!("{}…", text.chars().take(24).collect::<String>())
+        }
+        (_, ElementValue::Text(text)) => text.clone(),
+        (_, ElementValue::Rows(keys)) => keys.join(", "),
+        (_, ElementValue::Tags(tags)) => tags.join(", "),
+        // File names only; full paths crowd the summary
+        (_, ElementValue::Paths(paths)) => paths
+            .iter()
+            .map(|p| {
+                std::path::Path::new(p)
+                    .file_name()
+                    .map_or(p.clone(), |n| n.to_string_lossy().into_owned())
+            })
+            .collect::<Vec<_>>()
+            .join(", "),
+        (Element::Rating { max, .. }, ElementValue::Rating(Some(score))) => {
+            format!("{}/{}", score, max)
         }
+        (Element::Matrix { rows, .. }, ElementValue::Matrix(picks)) => format!(
+            "{}/{} answered",
+            picks.iter().filter(|p| p.is_some()).count(),
+            rows.len()
+        ),
+        (_, ElementValue::List(rows)) => format!("{} items", rows.len()),
+        (_, ElementValue::Pairs(pairs)) => pairs
+            .iter()
+            .filter(|(key, _)| !key.trim().is_empty())
+            .map(|(key, value)| format!("{}={}", key.trim(), value))
+            .collect::<Vec<_>>()
+            .join(", "),
+        (_, ElementValue::Review(reviews)) => format!(
+            "{}/{} hunks reviewed",
+            reviews.iter().filter(|r| r.decision.is_some()).count(),
+            reviews.len()
+        ),
+        (Element::Select { options, .. }, ElementValue::Choice(Some(idx))) => {
+            options.get(*idx)?.value().to_string()
+        }
+        (Element::Multi { options, .. }, ElementValue::MultiChoice(selections)) => options
+            .iter()
+            .zip(selections)
+            .filter(|(_, selected)| **selected)
+            .map(|(option, _)| option.value())
+            .collect::<Vec<_>>()
+            .join(", "),
+        _ => return None,
     };
+    Some(format!("{}: {}", element.label(), text))
+}
 
-    for element in elements {
-        match element {
-            Element::Slider { id, when, .. } | Element::Input { id, when, .. } => {
-                if is_visible(when) {
-                    active_ids.push(id.clone());
-                }
-            }
-            Element::Check {
-                id, reveals, when, ..
-            } => {
-                if is_visible(when) {
-                    active_ids.push(id.clone());
-                    // If checkbox is checked and has reveals, collect from it
-                    if state.get_boolean(id) && !reveals.is_empty() {
-                        active_ids.extend(collect_active_elements(
-                            reveals,
-                            state,
-                            all_elements,
-                            "",
-                        ));
-                    }
-                }
-            }
-            Element::Multi {
-                id,
-                options,
-                option_children,
-                reveals,
-                when,
-                ..
-            } => {
-                if is_visible(when) {
-                    active_ids.push(id.clone());
-                    // For each checked option with children, collect from it
-                    if let Some(selections) = state.get_multichoice(id) {
-                        let has_selection = selections.iter().any(|&s| s);
-
-                        for (i, option) in options.iter().enumerate() {
-                            if i < selections.len() && selections[i] {
-                                if let Some(children) = option_children.get(option.value()) {
-                                    active_ids.extend(collect_active_elements(
-                                        children,
-                                        state,
-                                        all_elements,
-                                        "",
-                                    ));
-                                }
-                            }
-                        }
+/// Element with the given id, searching nested elements
+fn find_element<'a>(elements: &'a [Element], id: &str) -> Option<&'a Element> {
+    elements.iter().find_map(|element| {
+        if element.id() == Some(id) {
+            Some(element)
+        } else {
+            element
+                .children()
+                .into_iter()
+                .find_map(|children| find_element(children, id))
+        }
+    })
+}
 
-                        // Collect from reveals only if any option is selected
-                        if has_selection && !reveals.is_empty() {
-                            active_ids.extend(collect_active_elements(
-                                reveals,
-                                state,
-                                all_elements,
-                                "",
-                            ));
-                        }
-                    }
-                }
-            }
-            Element::Select {
-                id,
-                options,
-                option_children,
-                reveals,
-                when,
-                ..
-            } => {
-                if is_visible(when) {
-                    active_ids.push(id.clone());
-
-                    let has_selection = state
-                        .get_choice(id)
-                        .map(|opt| opt.is_some())
-                        .unwrap_or(false);
-
-                    // If there's a selected option with children, collect from it
-                    if let Some(Some(idx)) = state.get_choice(id) {
-                        if let Some(option_text) = options.get(idx) {
-                            if let Some(children) = option_children.get(option_text.value()) {
-                                active_ids.extend(collect_active_elements(
-                                    children,
-                                    state,
-                                    all_elements,
-                                    "",
-                                ));
-                            }
+/// Label of the element with the given id, searching nested elements
+fn element_label<'a>(elements: &'a [Element], id: &str) -> Option<&'a str> {
+    find_element(elements, id).map(Element::label)
+}
+
+/// An element hidden by its `when` clause, for the visibility debugger
+struct HiddenElement {
+    name: String,
+    when: String,
+    /// The failing sub-expression and the values it saw
+    reason: String,
+}
+
+/// Walk the whole definition (including reveals and option children) collecting
+/// elements whose `when` clause is false. Children of a hidden element are skipped.
+/// Values read from `secrets` are masked.
+fn collect_hidden_elements(
+    elements: &[Element],
+    state_values: &HashMap<String, serde_json::Value>,
+    secrets: &HashSet<String>,
+    condition_cache: &mut HashMap<String, Option<ConditionExpr>>,
+    hidden: &mut Vec<HiddenElement>,
+) {
+    for element in elements {
+        if let Some(when_expr) = element.when() {
+            let cached_expr = condition_cache
+                .entry(when_expr.to_string())
+                .or_insert_with(|| parse_when(when_expr));
+
+            // Unparseable conditions fail open, so the element is shown
+            if let Some(ast) = cached_expr {
+                let trace = explain_condition(ast, state_values);
+                if let Some(failing) = trace.failing() {
+                    // A verdict is fine to show; what a secret holds isn't
+                    let shown = |t: &Trace| {
+                        if !t.value.is_boolean() && reads_secret(&t.expr, secrets) {
+                            "••••••".to_string()
+                        } else {
+                            t.value.to_string()
                         }
+                    };
+                    let mut reason = format!("{} is {}", failing.expr, shown(failing));
+                    let inputs: Vec<String> = failing
+                        .children
+                        .iter()
+                        .filter(|c| c.expr.references_fields())
+                        .map(|c| format!("{} = {}", c.expr, shown(c)))
+                        .collect();
+                    if !inputs.is_empty() {
+                        reason.push_str(&format!(" ({})", inputs.join(", ")));
                     }
 
-                    // Collect from reveals only if an option is selected
-                    if has_selection && !reveals.is_empty() {
-                        active_ids.extend(collect_active_elements(
-                            reveals,
-                            state,
-                            all_elements,
-                            "",
-                        ));
-                    }
-                }
-            }
-            Element::Group { elements, when, .. } => {
-                if is_visible(when) {
-                    // Recursively collect from group
-                    active_ids.extend(collect_active_elements(elements, state, all_elements, ""));
-                }
-            }
-            Element::Text { id, when, .. } => {
-                // Text elements are included in active list if visible
-                if is_visible(when) {
-                    if let Some(text_id) = id {
-                        active_ids.push(text_id.clone());
-                    }
-                }
-            }
-            Element::Markdown { id, when, .. } => {
-                // Markdown elements are included in active list if visible
-                if is_visible(when) {
-                    if let Some(md_id) = id {
-                        active_ids.push(md_id.clone());
-                    }
+                    hidden.push(HiddenElement {
+                        name: element.id().unwrap_or(element.label()).to_string(),
+                        when: when_expr.to_string(),
+                        reason,
+                    });
+                    continue;
                 }
             }
         }
+
+        for children in element.children() {
+            collect_hidden_elements(children, state_values, secrets, condition_cache, hidden);
+        }
+    }
+}
+
+/// Ids of every secret input, nested ones included
+fn collect_secret_ids(elements: &[Element], secrets: &mut HashSet<String>) {
+    for element in elements {
+        if let Element::Secret { id, .. } = element {
+            secrets.insert(id.clone());
+        }
+        for children in element.children() {
+            collect_secret_ids(children, secrets);
+        }
+    }
+}
+
+/// Whether an expression reads one of `secrets`, directly or by a dotted path
+fn reads_secret(expr: &ConditionExpr, secrets: &HashSet<String>) -> bool {
+    match expr {
+        ConditionExpr::Ref(id) => {
+            secrets.contains(id) || id.split('.').next().is_some_and(|root| secrets.contains(root))
+        }
+        _ => expr.children().into_iter().any(|c| reads_secret(c, secrets)),
+    }
+}
+
+/// Parse a when clause for the condition cache, logging failures once rather than every frame
+fn parse_when(when_expr: &str) -> Option<ConditionExpr> {
+    match parse_condition(when_expr) {
+        Ok(ast) => Some(ast),
+        Err(e) => {
+            log::warn!("Failed to parse when clause `{}`: {}", when_expr, e);
+            None
+        }
     }
+}
 
-    active_ids
+/// Collect only the active elements based on current state (evaluating when clauses)
+fn collect_active_elements(
+    elements: &[Element],
+    state: &PopupState,
+    all_elements: &[Element],
+    hidden_fields: HiddenFields,
+) -> Vec<String> {
+    let state_values = state.condition_values(all_elements, hidden_fields);
+    state.active_element_ids(elements, &state_values)
 }
 
 
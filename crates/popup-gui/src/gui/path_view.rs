@@ -0,0 +1,226 @@
+//! Built-in file browser for the `path` element
+
+use std::path::{Path, PathBuf};
+
+use eframe::egui;
+use egui::RichText;
+use popup_common::path::{matches_filters, resolve_root};
+use popup_common::{PathMode, PopupState};
+
+use super::RenderContext;
+
+/// Height of the visible part of the listing; longer directories scroll
+const LIST_HEIGHT: f32 = 200.0;
+
+/// Where one picker's browser is, kept across frames
+pub(super) struct PathBrowser {
+    /// Canonical root, or why it couldn't be opened
+    root: Result<PathBuf, String>,
+    dir: PathBuf,
+    /// Listing of `dir`: sub-directories first, then files passing the filters
+    entries: Vec<Entry>,
+    /// Why `dir` couldn't be listed
+    error: Option<String>,
+}
+
+struct Entry {
+    name: String,
+    is_dir: bool,
+}
+
+impl PathBrowser {
+    fn open(root: Option<&str>, mode: PathMode, filters: &[String]) -> Self {
+        let root = resolve_root(root).map_err(|e| match root {
+            Some(root) => format!("Can't open {}: {}", root, e),
+            None => format!("Can't open the working directory: {}", e),
+        });
+        let dir = root.clone().unwrap_or_default();
+        let mut browser = PathBrowser {
+            root,
+            dir,
+            entries: Vec::new(),
+            error: None,
+        };
+        browser.list(mode, filters);
+        browser
+    }
+
+    /// Move to `dir` and read its listing
+    fn enter(&mut self, dir: PathBuf, mode: PathMode, filters: &[String]) {
+        self.dir = dir;
+        self.list(mode, filters);
+    }
+
+    fn list(&mut self, mode: PathMode, filters: &[String]) {
+        self.entries.clear();
+        self.error = None;
+        if self.root.is_err() {
+            return;
+        }
+        let read = match std::fs::read_dir(&self.dir) {
+            Ok(read) => read,
+            Err(e) => {
+                self.error = Some(format!("Can't read {}: {}", self.dir.display(), e));
+                return;
+            }
+        };
+        for entry in read.flatten() {
+            let name = entry.file_name().to_string_lossy().into_owned();
+            if name.starts_with('.') {
+                continue;
+            }
+            // Follows symlinks, so a link to a directory can be entered
+            let is_dir = entry.path().is_dir();
+            let shown = is_dir || (mode != PathMode::Dir && matches_filters(filters, &name));
+            if shown {
+                self.entries.push(Entry { name, is_dir });
+            }
+        }
+        self.entries
+            .sort_by_key(|e| (!e.is_dir, e.name.to_lowercase()));
+    }
+}
+
+pub(super) struct PathData<'a> {
+    pub label: &'a str,
+    pub id: &'a str,
+    pub root: Option<&'a str>,
+    pub filters: &'a [String],
+    pub mode: PathMode,
+    pub multiple: bool,
+}
+
+pub(super) fn render_path(
+    ui: &mut egui::Ui,
+    data: PathData,
+    state: &mut PopupState,
+    ctx: &mut RenderContext,
+    element_path: &str,
+) {
+    let Some(picked) = state.get_paths_mut(data.id) else {
+        return;
+    };
+    let browser = ctx
+        .path_browsers
+        .entry(element_path.to_string())
+        .or_insert_with(|| PathBrowser::open(data.root, data.mode, data.filters));
+
+    ui.vertical(|ui| {
+        ui.label(
+            RichText::new(data.label)
+                .color(ctx.theme.matrix_green)
+                .strong()
+                .size(15.0),
+        );
+
+        let root = match &browser.root {
+            Ok(root) => root.clone(),
+            Err(e) => {
+                ui.label(RichText::new(e).color(ctx.theme.warning_orange));
+                return;
+            }
+        };
+
+        let mut go_to = None;
+        ui.horizontal(|ui| {
+            let up = ui
+                .add_enabled(browser.dir != root, egui::Button::new("▲"))
+                .on_hover_text("Parent folder");
+            if ctx.first_widget_id.is_none() && !ctx.widget_focused {
+                *ctx.first_widget_id = Some(up.id);
+            }
+            if up.clicked() {
+                go_to = browser.dir.parent().map(Path::to_path_buf);
+            }
+            ui.label(
+                RichText::new(browser.dir.display().to_string())
+                    .monospace()
+                    .color(ctx.theme.text_secondary),
+            );
+            if data.mode != PathMode::File
+                && ui
+                    .small_button("Use this folder")
+                    .on_hover_text("Pick the folder shown")
+                    .clicked()
+            {
+                pick(picked, &browser.dir, data.multiple);
+            }
+        });
+
+        if let Some(error) = &browser.error {
+            ui.label(RichText::new(error).color(ctx.theme.warning_orange));
+        }
+
+        egui::ScrollArea::vertical()
+            .id_salt(("path_browser", element_path))
+            .max_height(LIST_HEIGHT)
+            .auto_shrink([false, true])
+            .show(ui, |ui| {
+                if browser.entries.is_empty() && browser.error.is_none() {
+                    ui.label(RichText::new("(empty)").color(ctx.theme.text_secondary));
+                }
+                for entry in &browser.entries {
+                    let full = browser.dir.join(&entry.name);
+                    let is_picked = picked.iter().any(|p| Path::new(p) == full);
+                    ui.horizontal(|ui| {
+                        if entry.is_dir {
+                            let open = ui
+                                .selectable_label(is_picked, format!("📁 {}/", entry.name))
+                                .on_hover_text("Open");
+                            if open.clicked() {
+                                go_to = Some(full.clone());
+                            }
+                            if data.mode != PathMode::File {
+                                let (icon, hover) = if is_picked {
+                                    ("✓", "Unpick")
+                                } else {
+                                    ("+", "Pick this folder")
+                                };
+                                if ui.small_button(icon).on_hover_text(hover).clicked() {
+                                    pick(picked, &full, data.multiple);
+                                }
+                            }
+                        } else if ui
+                            .selectable_label(is_picked, format!("📄 {}", entry.name))
+                            .clicked()
+                        {
+                            pick(picked, &full, data.multiple);
+                        }
+                    });
+                }
+            });
+
+        if let Some(dir) = go_to {
+            // Never leave the root, even through a symlink
+            let dir = dir.canonicalize().unwrap_or(dir);
+            if dir.starts_with(&root) {
+                browser.enter(dir, data.mode, data.filters);
+            }
+        }
+
+        let mut removed = None;
+        for (idx, path) in picked.iter().enumerate() {
+            ui.horizontal(|ui| {
+                if ui.small_button("✕").on_hover_text("Remove").clicked() {
+                    removed = Some(idx);
+                }
+                ui.label(RichText::new(path).monospace().color(ctx.theme.base2));
+            });
+        }
+        if let Some(idx) = removed {
+            picked.remove(idx);
+        }
+    });
+}
+
+/// Toggle `path` in a multi-pick, or make it the only pick
+fn pick(picked: &mut Vec<String>, path: &Path, multiple: bool) {
+    let path = path.display().to_string();
+    match picked.iter().position(|p| *p == path) {
+        Some(idx) => {
+            picked.remove(idx);
+        }
+        None if multiple => picked.push(path),
+        None => *picked = vec![path],
+    }
+}
@@ -0,0 +1,120 @@
+//! Two-handle slider for the `range` element
+
+use eframe::egui;
+use egui::{pos2, vec2, RichText, Sense, Stroke};
+use popup_common::PopupState;
+
+use super::RenderContext;
+
+const HANDLE_RADIUS: f32 = 7.0;
+
+pub(super) struct RangeData<'a> {
+    pub label: &'a str,
+    pub id: &'a str,
+    pub min: f32,
+    pub max: f32,
+    pub step: f32,
+}
+
+pub(super) fn render_range(
+    ui: &mut egui::Ui,
+    data: RangeData,
+    state: &mut PopupState,
+    ctx: &mut RenderContext,
+) {
+    let Some((low, high)) = state.get_range_mut(data.id) else {
+        return;
+    };
+    let decimals = if data.step.fract() == 0.0 && data.min.fract() == 0.0 {
+        0
+    } else {
+        2
+    };
+
+    ui.horizontal(|ui| {
+        ui.set_min_height(24.0);
+        ui.add_sized(
+            [140.0, 24.0],
+            egui::Label::new(
+                RichText::new(data.label)
+                    .color(ctx.theme.warning_orange)
+                    .strong()
+                    .size(15.0),
+            ),
+        );
+
+        let value_width = 150.0;
+        let width = (ui.available_width() - value_width).max(100.0);
+        let (rect, response) = ui.allocate_exact_size(vec2(width, 24.0), Sense::click_and_drag());
+        if ctx.first_widget_id.is_none() && !ctx.widget_focused {
+            *ctx.first_widget_id = Some(response.id);
+        }
+
+        let track = rect.shrink2(vec2(HANDLE_RADIUS, 0.0));
+        let x_of = |v: f32| track.left() + (v - data.min) / (data.max - data.min) * track.width();
+        let value_at = |x: f32| {
+            let t = ((x - track.left()) / track.width()).clamp(0.0, 1.0);
+            snap(data.min + t * (data.max - data.min), &data)
+        };
+
+        // Whichever handle is nearer takes the drag, and keeps it until release
+        let grab_id = response.id.with("high_handle");
+        if let Some(pos) = response.interact_pointer_pos() {
+            if response.drag_started() || response.clicked() {
+                let nearer_high = (pos.x - x_of(*high)).abs() < (pos.x - x_of(*low)).abs()
+                    || (*low == *high && pos.x > x_of(*high));
+                ui.data_mut(|d| d.insert_temp(grab_id, nearer_high));
+            }
+            let on_high = ui.data(|d| d.get_temp::<bool>(grab_id)).unwrap_or(false);
+            let v = value_at(pos.x);
+            if on_high {
+                *high = v.max(*low);
+            } else {
+                *low = v.min(*high);
+            }
+        }
+
+        let painter = ui.painter();
+        let y = rect.center().y;
+        painter.line_segment(
+            [pos2(track.left(), y), pos2(track.right(), y)],
+            Stroke::new(4.0, ctx.theme.dark_gray),
+        );
+        painter.line_segment(
+            [pos2(x_of(*low), y), pos2(x_of(*high), y)],
+            Stroke::new(4.0, ctx.theme.neon_cyan),
+        );
+        let stroke = if response.hovered() || response.dragged() {
+            Stroke::new(2.0, ctx.theme.base2)
+        } else {
+            Stroke::new(1.0, ctx.theme.text_secondary)
+        };
+        for v in [*low, *high] {
+            painter.circle(
+                pos2(x_of(v), y),
+                HANDLE_RADIUS,
+                ctx.theme.deep_black,
+                stroke,
+            );
+        }
+
+        // Typed values for precision; each end is bounded by the other
+        let low_edit = egui::DragValue::new(low)
+            .range(data.min..=*high)
+            .speed(data.step)
+            .max_decimals(decimals);
+        ui.add(low_edit);
+        ui.label(RichText::new("–").color(ctx.theme.text_secondary));
+        let high_edit = egui::DragValue::new(high)
+            .range(*low..=data.max)
+            .speed(data.step)
+            .max_decimals(decimals);
+        ui.add(high_edit);
+    });
+}
+
+/// Nearest step from `min`, kept inside the range
+fn snap(value: f32, data: &RangeData) -> f32 {
+    let steps = ((value - data.min) / data.step).round();
+    (data.min + steps * data.step).clamp(data.min, data.max)
+}
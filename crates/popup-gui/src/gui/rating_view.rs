@@ -0,0 +1,150 @@
+//! Rendering for the `rating` scale and the `matrix` question grid
+
+use eframe::egui;
+use egui::RichText;
+use popup_common::{MatrixRow, PopupState, RatingStyle};
+
+use super::RenderContext;
+
+pub(super) struct RatingData<'a> {
+    pub label: &'a str,
+    pub id: &'a str,
+    pub max: u32,
+    pub low_label: Option<&'a str>,
+    pub high_label: Option<&'a str>,
+    pub style: RatingStyle,
+}
+
+pub(super) fn render_rating(
+    ui: &mut egui::Ui,
+    data: RatingData,
+    state: &mut PopupState,
+    ctx: &mut RenderContext,
+) {
+    let Some(score) = state.get_rating_mut(data.id) else {
+        return;
+    };
+
+    ui.vertical(|ui| {
+        ui.horizontal(|ui| {
+            ui.label(
+                RichText::new(data.label)
+                    .color(ctx.theme.matrix_green)
+                    .strong()
+                    .size(15.0),
+            );
+            if let Some(score) = score {
+                ui.label(
+                    RichText::new(format!("{}/{}", score, data.max))
+                        .color(ctx.theme.text_secondary),
+                );
+            }
+        });
+
+        ui.horizontal(|ui| {
+            if let Some(low) = data.low_label {
+                ui.label(RichText::new(low).color(ctx.theme.text_secondary).small());
+            }
+
+            let mut clicked = None;
+            for point in 1..=data.max {
+                let response = match data.style {
+                    RatingStyle::Stars => {
+                        let lit = score.is_some_and(|s| point <= s);
+                        let (star, color) = if lit {
+                            ("★", ctx.theme.warning_orange)
+                        } else {
+                            ("☆", ctx.theme.text_secondary)
+                        };
+                        ui.add(
+                            egui::Button::new(RichText::new(star).color(color).size(22.0))
+                                .frame(false),
+                        )
+                    }
+                    RatingStyle::Points => {
+                        ui.selectable_label(*score == Some(point), point.to_string())
+                    }
+                };
+                if ctx.first_widget_id.is_none() && !ctx.widget_focused {
+                    *ctx.first_widget_id = Some(response.id);
+                }
+                if response
+                    .on_hover_text(format!("{} of {}", point, data.max))
+                    .clicked()
+                {
+                    clicked = Some(point);
+                }
+            }
+            // Clicking the current score again clears it
+            if let Some(point) = clicked {
+                *score = if *score == Some(point) {
+                    None
+                } else {
+                    Some(point)
+                };
+            }
+
+            if let Some(high) = data.high_label {
+                ui.label(RichText::new(high).color(ctx.theme.text_secondary).small());
+            }
+        });
+    });
+}
+
+pub(super) struct MatrixData<'a> {
+    pub label: &'a str,
+    pub id: &'a str,
+    pub rows: &'a [MatrixRow],
+    pub columns: &'a [String],
+}
+
+pub(super) fn render_matrix(
+    ui: &mut egui::Ui,
+    data: MatrixData,
+    state: &mut PopupState,
+    ctx: &mut RenderContext,
+) {
+    let Some(picks) = state.get_matrix_mut(data.id) else {
+        return;
+    };
+
+    ui.vertical(|ui| {
+        ui.label(
+            RichText::new(data.label)
+                .color(ctx.theme.matrix_green)
+                .strong()
+                .size(15.0),
+        );
+
+        egui::Grid::new(("matrix", data.id))
+            .num_columns(data.columns.len() + 1)
+            .spacing([16.0, 6.0])
+            .striped(true)
+            .show(ui, |ui| {
+                ui.label("");
+                for column in data.columns {
+                    ui.label(RichText::new(column).color(ctx.theme.neon_cyan).small());
+                }
+                ui.end_row();
+
+                for (row, pick) in data.rows.iter().zip(picks.iter_mut()) {
+                    ui.add(
+                        egui::Label::new(RichText::new(&row.label).color(ctx.theme.text_primary))
+                            .wrap(),
+                    );
+                    for (col, column) in data.columns.iter().enumerate() {
+                        let response = ui
+                            .radio(*pick == Some(col), "")
+                            .on_hover_text(column.as_str());
+                        if ctx.first_widget_id.is_none() && !ctx.widget_focused {
+                            *ctx.first_widget_id = Some(response.id);
+                        }
+                        if response.clicked() {
+                            *pick = Some(col);
+                        }
+                    }
+                    ui.end_row();
+                }
+            });
+    });
+}
@@ -0,0 +1,234 @@
+//! Type-to-filter option list for `searchable` select and multi
+
+use eframe::egui;
+use egui::{Key, Modifiers, RichText};
+use popup_common::OptionValue;
+
+use super::{option_icon, RenderContext};
+
+/// Height of the visible part of the list; longer lists scroll
+const LIST_HEIGHT: f32 = 240.0;
+
+/// Filter text and keyboard cursor of one searchable list, kept across frames
+#[derive(Default)]
+pub(super) struct OptionSearch {
+    query: String,
+    /// Position in the filtered list, not an option index
+    cursor: usize,
+    /// Scroll offset of the list as of the last frame
+    offset: f32,
+}
+
+/// Where a pick from the list goes
+pub(super) enum Picked<'a> {
+    One(&'a mut Option<usize>),
+    Many(&'a mut Vec<bool>),
+}
+
+impl Picked<'_> {
+    fn is_picked(&self, idx: usize) -> bool {
+        match self {
+            Picked::One(selected) => **selected == Some(idx),
+            Picked::Many(selections) => selections.get(idx).copied().unwrap_or(false),
+        }
+    }
+
+    fn pick(&mut self, idx: usize) {
+        match self {
+            Picked::One(selected) => **selected = Some(idx),
+            Picked::Many(selections) => {
+                if let Some(s) = selections.get_mut(idx) {
+                    *s = !*s;
+                }
+            }
+        }
+    }
+}
+
+pub(super) fn render_option_search(
+    ui: &mut egui::Ui,
+    element_path: &str,
+    options: &[OptionValue],
+    mut picked: Picked,
+    ctx: &mut RenderContext,
+) {
+    let mut search = ctx.option_searches.remove(element_path).unwrap_or_default();
+    let edit_id = ui.make_persistent_id(("option_search", element_path));
+
+    // Arrow keys move through the matches while the filter has focus; take
+    // them before the text edit sees them
+    let mut moved = 0i32;
+    if ui.memory(|m| m.has_focus(edit_id)) {
+        ui.input_mut(|i| {
+            if i.consume_key(Modifiers::NONE, Key::ArrowDown) {
+                moved += 1;
+            }
+            if i.consume_key(Modifiers::NONE, Key::ArrowUp) {
+                moved -= 1;
+            }
+        });
+    }
+
+    let response = ui.add(
+        egui::TextEdit::singleline(&mut search.query)
+            .id(edit_id)
+            .hint_text("Type to filter...")
+            .text_color(ctx.theme.base2)
+            .desired_width(ui.available_width().min(400.0)),
+    );
+    if ctx.first_widget_id.is_none() && !ctx.widget_focused {
+        *ctx.first_widget_id = Some(response.id);
+    }
+    if response.changed() {
+        search.cursor = 0;
+        search.offset = 0.0;
+    }
+
+    let matches = matching_options(options, &search.query);
+    if matches.is_empty() {
+        search.cursor = 0;
+    } else {
+        let last = matches.len() as i32 - 1;
+        search.cursor = (search.cursor as i32 + moved).clamp(0, last) as usize;
+    }
+
+    // Enter picks the highlighted option and keeps the filter focused
+    if response.lost_focus() && ui.input(|i| i.key_pressed(Key::Enter)) {
+        if let Some(&idx) = matches.get(search.cursor) {
+            picked.pick(idx);
+        }
+        response.request_focus();
+    }
+
+    if matches.is_empty() {
+        ui.label(RichText::new("(no options match)").color(ctx.theme.text_secondary));
+        ctx.option_searches.insert(element_path.to_string(), search);
+        return;
+    }
+
+    let row_height = ui.spacing().interact_size.y;
+    let row_stride = row_height + ui.spacing().item_spacing.y;
+    if moved != 0 {
+        // Keep the cursor row in view
+        let top = search.cursor as f32 * row_stride;
+        if top < search.offset {
+            search.offset = top;
+        } else if top + row_stride > search.offset + LIST_HEIGHT {
+            search.offset = top + row_stride - LIST_HEIGHT;
+        }
+    }
+
+    let mut clicked = None;
+    let output = egui::ScrollArea::vertical()
+        .id_salt(("option_search_list", element_path))
+        .max_height(LIST_HEIGHT)
+        .auto_shrink([false, true])
+        .vertical_scroll_offset(search.offset)
+        .show_rows(ui, row_height, matches.len(), |ui, range| {
+            for row in range {
+                let idx = matches[row];
+                let option = &options[idx];
+                let fill = if row == search.cursor {
+                    ctx.theme.neon_purple.gamma_multiply(0.25)
+                } else {
+                    egui::Color32::TRANSPARENT
+                };
+                egui::Frame::NONE.fill(fill).show(ui, |ui| {
+                    ui.set_min_width(ui.available_width());
+                    ui.horizontal(|ui| {
+                        ui.set_height(row_height);
+                        let is_picked = picked.is_picked(idx);
+                        let response = match picked {
+                            Picked::One(_) => {
+                                option_icon(ui, ctx, option);
+                                ui.selectable_label(is_picked, option.value())
+                            }
+                            Picked::Many(_) => {
+                                let mut checked = is_picked;
+                                let response = ui.checkbox(&mut checked, "");
+                                option_icon(ui, ctx, option);
+                                response
+                                    | ui.label(
+                                        RichText::new(option.value()).color(ctx.theme.matrix_green),
+                                    )
+                            }
+                        };
+                        if let Some(desc) = option.description() {
+                            ui.add(
+                                egui::Label::new(
+                                    RichText::new(desc).color(ctx.theme.text_secondary).small(),
+                                )
+                                .truncate(),
+                            );
+                        }
+                        if response.clicked() {
+                            clicked = Some((row, idx));
+                        }
+                    });
+                });
+            }
+        });
+    search.offset = output.state.offset.y;
+
+    if let Some((row, idx)) = clicked {
+        search.cursor = row;
+        picked.pick(idx);
+    }
+    ctx.option_searches.insert(element_path.to_string(), search);
+}
+
+/// Indices of options matching the query, best match first.
+///
+/// Every character of the query has to appear in order in the option's value
+/// or description. A match on the value counts double; an empty query keeps
+/// every option in its given order.
+pub(super) fn matching_options(options: &[OptionValue], query: &str) -> Vec<usize> {
+    let query = query.trim();
+    let mut scored: Vec<(i32, usize)> = options
+        .iter()
+        .enumerate()
+        .filter_map(|(idx, option)| {
+            let on_value = fuzzy_score(query, option.value()).map(|s| s * 2);
+            let on_desc = option.description().and_then(|d| fuzzy_score(query, d));
+            on_value.max(on_desc).map(|score| (score, idx))
+        })
+        .collect();
+    // Stable, so ties stay in the given order
+    scored.sort_by_key(|&(score, _)| std::cmp::Reverse(score));
+    scored.into_iter().map(|(_, idx)| idx).collect()
+}
+
+/// Case-insensitive subsequence match; None if `query` isn't in `text`.
+///
+/// Runs of consecutive characters and characters at the start of a word
+/// score higher, so "gh" ranks "GitHub" above "Hugh".
+fn fuzzy_score(query: &str, text: &str) -> Option<i32> {
+    let mut score = 0;
+    let mut prev: Option<char> = None;
+    let mut chars = text.chars().enumerate();
+    let mut last_match = None;
+
+    for q in query.chars().flat_map(char::to_lowercase) {
+        if q.is_whitespace() {
+            continue;
+        }
+        loop {
+            let (pos, c) = chars.next()?;
+            let before = prev.replace(c);
+            if !c.to_lowercase().eq(std::iter::once(q)) {
+                continue;
+            }
+            score += 1;
+            if before.is_none_or(|p| !p.is_alphanumeric() || (p.is_lowercase() && c.is_uppercase()))
+            {
+                score += 3;
+            }
+            if last_match.is_some_and(|last| last + 1 == pos) {
+                score += 2;
+            }
+            last_match = Some(pos);
+            break;
+        }
+    }
+    Some(score)
+}
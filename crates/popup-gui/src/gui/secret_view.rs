@@ -0,0 +1,68 @@
+//! Rendering for the masked `secret` input
+
+use eframe::egui;
+use egui::RichText;
+use popup_common::PopupState;
+
+use super::RenderContext;
+
+pub(super) struct SecretData<'a> {
+    pub label: &'a str,
+    pub id: &'a str,
+    pub placeholder: Option<&'a str>,
+}
+
+pub(super) fn render_secret(
+    ui: &mut egui::Ui,
+    data: SecretData,
+    state: &mut PopupState,
+    ctx: &mut RenderContext,
+    element_path: &str,
+) {
+    let Some(value) = state.get_text_mut(data.id) else {
+        return;
+    };
+    let revealed = ctx.revealed_secrets.contains(element_path);
+
+    let widget_frame = egui::Frame::NONE
+        .inner_margin(egui::Margin::symmetric(8, 4))
+        .fill(ctx.theme.dark_gray);
+
+    widget_frame.show(ui, |ui| {
+        ui.vertical(|ui| {
+            ui.label(
+                RichText::new(data.label)
+                    .color(ctx.theme.neon_purple)
+                    .strong()
+                    .size(15.0),
+            );
+
+            ui.horizontal(|ui| {
+                let mut text_edit = egui::TextEdit::singleline(value)
+                    .password(!revealed)
+                    .text_color(ctx.theme.base2)
+                    .desired_width(ui.available_width().min(400.0));
+                if let Some(hint) = data.placeholder {
+                    text_edit = text_edit.hint_text(hint);
+                }
+                let response = ui.add(text_edit);
+                if ctx.first_widget_id.is_none() && !ctx.widget_focused {
+                    *ctx.first_widget_id = Some(response.id);
+                }
+
+                let (icon, hover) = if revealed {
+                    ("🙈", "Hide")
+                } else {
+                    ("👁", "Show")
+                };
+                if ui.small_button(icon).on_hover_text(hover).clicked() {
+                    if revealed {
+                        ctx.revealed_secrets.remove(element_path);
+                    } else {
+                        ctx.revealed_secrets.insert(element_path.to_string());
+                    }
+                }
+            });
+        });
+    });
+}
@@ -0,0 +1,221 @@
+//! Rendering for the `table` element
+
+use std::cmp::Ordering;
+
+use eframe::egui;
+use egui::RichText;
+use egui_extras::{Column, TableBuilder};
+use popup_common::{table::row_keys, PopupState, TableSelection};
+
+use super::RenderContext;
+
+const ROW_HEIGHT: f32 = 24.0;
+
+/// Filter text and sort order of one table, kept across frames
+#[derive(Default)]
+pub(super) struct TableView {
+    filter: String,
+    /// Column index and whether it's ascending
+    sort: Option<(usize, bool)>,
+}
+
+pub(super) struct TableData<'a> {
+    pub label: &'a str,
+    pub id: &'a str,
+    pub columns: &'a [String],
+    pub rows: &'a [Vec<String>],
+    pub key: Option<&'a str>,
+    pub selection: TableSelection,
+}
+
+pub(super) fn render_table(
+    ui: &mut egui::Ui,
+    data: TableData,
+    state: &mut PopupState,
+    ctx: &mut RenderContext,
+    element_path: &str,
+) {
+    let view = ctx.tables.entry(element_path.to_string()).or_default();
+    let keys = row_keys(data.columns, data.rows, data.key);
+    let mut selected = state.get_rows_mut(data.id);
+
+    ui.horizontal(|ui| {
+        ui.label(
+            RichText::new(data.label)
+                .color(ctx.theme.matrix_green)
+                .strong()
+                .size(15.0),
+        );
+        if let Some(selected) = &selected {
+            ui.label(
+                RichText::new(format!(
+                    "{} of {} selected",
+                    selected.len(),
+                    data.rows.len()
+                ))
+                .color(ctx.theme.text_secondary),
+            );
+        }
+    });
+
+    let visible = visible_rows(data.rows, view);
+
+    ui.horizontal(|ui| {
+        ui.add(
+            egui::TextEdit::singleline(&mut view.filter)
+                .hint_text("Filter rows...")
+                .desired_width(200.0),
+        );
+        if data.selection == TableSelection::Multi {
+            if let Some(selected) = selected.as_deref_mut() {
+                if ui.small_button("All").clicked() {
+                    let mut all: Vec<&String> = selected.iter().collect();
+                    all.extend(visible.iter().map(|&r| &keys[r]));
+                    *selected = in_row_order(&keys, |k| all.contains(&k));
+                }
+                if ui.small_button("None").clicked() {
+                    let shown: Vec<&String> = visible.iter().map(|&r| &keys[r]).collect();
+                    selected.retain(|k| !shown.contains(&k));
+                }
+            }
+        }
+    });
+
+    let selectable = selected.is_some();
+    let mut clicked_row = None;
+    let mut clicked_header = None;
+    let last = data.columns.len().saturating_sub(1);
+
+    ui.push_id(("table", element_path), |ui| {
+        let mut builder = TableBuilder::new(ui)
+            .striped(true)
+            .resizable(true)
+            .max_scroll_height(300.0)
+            .cell_layout(egui::Layout::left_to_right(egui::Align::Center));
+        if selectable {
+            builder = builder.sense(egui::Sense::click());
+        }
+        for i in 0..data.columns.len() {
+            let column = if i == last {
+                Column::remainder()
+            } else {
+                Column::auto().at_least(40.0)
+            };
+            builder = builder.column(column);
+        }
+
+        builder
+            .header(ROW_HEIGHT, |mut header| {
+                for (i, name) in data.columns.iter().enumerate() {
+                    header.col(|ui| {
+                        let arrow = match view.sort {
+                            Some((col, true)) if col == i => " ▲",
+                            Some((col, false)) if col == i => " ▼",
+                            _ => "",
+                        };
+                        let text = RichText::new(format!("{}{}", name, arrow))
+                            .color(ctx.theme.neon_cyan)
+                            .strong();
+                        if ui.add(egui::Button::new(text).frame(false)).clicked() {
+                            clicked_header = Some(i);
+                        }
+                    });
+                }
+            })
+            .body(|body| {
+                body.rows(ROW_HEIGHT, visible.len(), |mut row| {
+                    let r = visible[row.index()];
+                    let is_selected = selected.as_deref().is_some_and(|s| s.contains(&keys[r]));
+                    row.set_selected(is_selected);
+                    for cell in &data.rows[r] {
+                        row.col(|ui| {
+                            ui.add(
+                                egui::Label::new(RichText::new(cell).color(ctx.theme.text_primary))
+                                    .selectable(false)
+                                    .truncate(),
+                            );
+                        });
+                    }
+                    if row.response().clicked() {
+                        clicked_row = Some(r);
+                    }
+                });
+            });
+    });
+
+    if visible.is_empty() {
+        let text = if data.rows.is_empty() {
+            "(no rows)"
+        } else {
+            "(no rows match the filter)"
+        };
+        ui.label(RichText::new(text).color(ctx.theme.text_secondary));
+    }
+
+    if let Some(col) = clicked_header {
+        // Ascending, then descending, then back to the given order
+        view.sort = match view.sort {
+            Some((c, true)) if c == col => Some((col, false)),
+            Some((c, false)) if c == col => None,
+            _ => Some((col, true)),
+        };
+    }
+
+    if let (Some(r), Some(selected)) = (clicked_row, selected) {
+        let key = &keys[r];
+        let was_selected = selected.contains(key);
+        match data.selection {
+            TableSelection::Single if was_selected => selected.clear(),
+            TableSelection::Single => *selected = vec![key.clone()],
+            _ => {
+                *selected = in_row_order(&keys, |k| {
+                    if k == key {
+                        !was_selected
+                    } else {
+                        selected.contains(k)
+                    }
+                })
+            }
+        }
+    }
+}
+
+/// Indices of rows passing the filter, in display order
+fn visible_rows(rows: &[Vec<String>], view: &TableView) -> Vec<usize> {
+    let filter = view.filter.trim().to_lowercase();
+    let mut visible: Vec<usize> = (0..rows.len())
+        .filter(|&r| {
+            filter.is_empty()
+                || rows[r]
+                    .iter()
+                    .any(|cell| cell.to_lowercase().contains(&filter))
+        })
+        .collect();
+
+    if let Some((col, ascending)) = view.sort {
+        visible.sort_by(|&a, &b| {
+            let ord = compare_cells(
+                rows[a].get(col).map_or("", String::as_str),
+                rows[b].get(col).map_or("", String::as_str),
+            );
+            if ascending {
+                ord
+            } else {
+                ord.reverse()
+            }
+        });
+    }
+    visible
+}
+
+/// Numbers sort numerically, everything else case-insensitively
+fn compare_cells(a: &str, b: &str) -> Ordering {
+    match (a.trim().parse::<f64>(), b.trim().parse::<f64>()) {
+        (Ok(x), Ok(y)) => x.partial_cmp(&y).unwrap_or(Ordering::Equal),
+        _ => a.to_lowercase().cmp(&b.to_lowercase()),
+    }
+}
+
+fn in_row_order(keys: &[String], keep: impl Fn(&String) -> bool) -> Vec<String> {
+    keys.iter().filter(|k| keep(k)).cloned().collect()
+}
@@ -0,0 +1,168 @@
+//! Rendering for the `tags` chip input
+
+use eframe::egui;
+use egui::RichText;
+use popup_common::{validation::check_tag, PopupState, TagPattern};
+
+use super::RenderContext;
+
+/// Suggestions shown at once under the input
+const MAX_SUGGESTIONS: usize = 8;
+
+/// Text being typed into a tags input, and why the last entry was refused
+#[derive(Default)]
+pub(super) struct TagInput {
+    text: String,
+    error: Option<String>,
+}
+
+#[cfg(test)]
+impl TagInput {
+    pub(super) fn typed(text: &str) -> Self {
+        Self {
+            text: text.to_string(),
+            error: None,
+        }
+    }
+
+    pub(super) fn text(&self) -> &str {
+        &self.text
+    }
+}
+
+pub(super) struct TagsData<'a> {
+    pub label: &'a str,
+    pub id: &'a str,
+    pub suggestions: &'a [String],
+    pub max_tags: Option<usize>,
+    pub pattern: Option<&'a TagPattern>,
+    pub placeholder: Option<&'a str>,
+}
+
+pub(super) fn render_tags(
+    ui: &mut egui::Ui,
+    data: TagsData,
+    state: &mut PopupState,
+    ctx: &mut RenderContext,
+    element_path: &str,
+) {
+    let Some(tags) = state.get_tags_mut(data.id) else {
+        return;
+    };
+    let input = ctx.tag_inputs.entry(element_path.to_string()).or_default();
+    let full = data.max_tags.is_some_and(|max| tags.len() >= max);
+
+    ui.vertical(|ui| {
+        ui.horizontal(|ui| {
+            ui.label(
+                RichText::new(data.label)
+                    .color(ctx.theme.matrix_green)
+                    .strong()
+                    .size(15.0),
+            );
+            if let Some(max) = data.max_tags {
+                ui.label(
+                    RichText::new(format!("{}/{}", tags.len(), max))
+                        .color(ctx.theme.text_secondary),
+                );
+            }
+        });
+
+        // Chips, each removable
+        let mut remove = None;
+        ui.horizontal_wrapped(|ui| {
+            for (i, tag) in tags.iter().enumerate() {
+                egui::Frame::NONE
+                    .inner_margin(egui::Margin::symmetric(6, 2))
+                    .corner_radius(8.0)
+                    .fill(ctx.theme.neon_purple.gamma_multiply(0.25))
+                    .show(ui, |ui| {
+                        ui.spacing_mut().item_spacing.x = 4.0;
+                        ui.label(RichText::new(tag).color(ctx.theme.base2));
+                        if ui
+                            .add(egui::Button::new("✕").small().frame(false))
+                            .on_hover_text("Remove")
+                            .clicked()
+                        {
+                            remove = Some(i);
+                        }
+                    });
+            }
+        });
+        if let Some(i) = remove {
+            tags.remove(i);
+        }
+
+        if full {
+            return;
+        }
+
+        let hint = data.placeholder.unwrap_or("Type and press Enter");
+        let response = ui.add(
+            egui::TextEdit::singleline(&mut input.text)
+                .hint_text(hint)
+                .text_color(ctx.theme.base2)
+                .desired_width(ui.available_width().min(400.0)),
+        );
+
+        // Enter or a comma commits what's been typed
+        let mut entered = Vec::new();
+        if response.changed() {
+            input.error = None;
+        }
+        if input.text.contains(',') {
+            entered.extend(input.text.split(',').map(str::to_string));
+            input.text.clear();
+        } else if response.lost_focus() && ui.input(|i| i.key_pressed(egui::Key::Enter)) {
+            entered.push(std::mem::take(&mut input.text));
+            response.request_focus();
+        }
+
+        let typed = input.text.trim().to_lowercase();
+        let matches: Vec<&String> = data
+            .suggestions
+            .iter()
+            .filter(|s| !tags.contains(s))
+            .filter(|s| typed.is_empty() || s.to_lowercase().contains(&typed))
+            .take(MAX_SUGGESTIONS)
+            .collect();
+        if !matches.is_empty() {
+            ui.horizontal_wrapped(|ui| {
+                for suggestion in matches {
+                    let text = RichText::new(format!("+ {}", suggestion))
+                        .color(ctx.theme.neon_cyan)
+                        .small();
+                    if ui.add(egui::Button::new(text).frame(false)).clicked() {
+                        entered.push(suggestion.clone());
+                        input.text.clear();
+                    }
+                }
+            });
+        }
+
+        for tag in entered {
+            let tag = tag.trim().to_string();
+            if tag.is_empty() || tags.contains(&tag) {
+                continue;
+            }
+            if data.max_tags.is_some_and(|max| tags.len() >= max) {
+                break;
+            }
+            match check_tag(&tag, data.pattern) {
+                Ok(()) => tags.push(tag),
+                Err(message) => {
+                    // Put the refused text back so it can be fixed
+                    input.text = tag;
+                    input.error = Some(message);
+                }
+            }
+        }
+        if let Some(message) = &input.error {
+            ui.label(
+                RichText::new(message)
+                    .color(ctx.theme.warning_orange)
+                    .small(),
+            );
+        }
+    });
+}
@@ -11,8 +11,16 @@ pub fn parse_popup_json(input: &str) -> Result<PopupDefinition> {
 }
 
 /// Validate popup JSON without parsing into PopupDefinition
+///
+/// Also checks that every `when` clause parses, reporting each failure with its position.
 pub fn validate_popup_json(input: &str) -> Result<()> {
-    parse_popup_json(input).map(|_| ())
+    let popup = parse_popup_json(input)?;
+    let errors = popup.condition_errors();
+    if !errors.is_empty() {
+        let messages: Vec<String> = errors.iter().map(|e| e.to_string()).collect();
+        anyhow::bail!(messages.join("\n"));
+    }
+    Ok(())
 }
 
 #[cfg(test)]
@@ -62,4 +70,22 @@ mod tests {
         assert!(result.is_err());
         assert!(result.unwrap_err().to_string().contains("missing field `elements`"));
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn test_validate_reports_invalid_when_clause() {
+        let json = r#"{
+            "title": "Bad Condition",
+            "elements": [
+                {"check": "Enable", "id": "enabled"},
+                {"group": "Settings", "elements": [
+                    {"input": "Name", "id": "name", "when": "enabled = true"}
+                ]}
+            ]
+        }"#;
+
+        let err = validate_popup_json(json).unwrap_err().to_string();
+        assert!(err.contains("'name'"));
+        assert!(err.contains("position 8"));
+        assert!(err.contains("use `==`"));
+    }
+}
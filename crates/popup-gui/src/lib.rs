@@ -1,3 +1,6 @@
+// The element schema in schema.rs is one large json! literal
+#![recursion_limit = "256"]
+
 pub mod gui;
 pub mod json_parser;
 pub mod mcp_server;
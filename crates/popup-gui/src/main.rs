@@ -1,6 +1,8 @@
 use anyhow::Result;
 use clap::Parser;
+use popup_common::PopupDefinition;
 use popup_gui::{inject_other_options, mcp_server, parse_popup_json, render_popup};
+use std::collections::HashMap;
 use std::fs;
 use std::io::{self, Read};
 
@@ -16,6 +18,10 @@ struct Args {
     #[arg(long, value_name = "PATH")]
     file: Option<String>,
 
+    /// Seed the popup from a JSON object of element id to value (with --stdin or --file)
+    #[arg(long, value_name = "PATH")]
+    prefill: Option<String>,
+
     /// Include only these templates (comma-separated)
     #[arg(long, value_delimiter = ',')]
     include_only: Option<Vec<String>>,
@@ -29,14 +35,40 @@ struct Args {
     list_templates: bool,
 }
 
-fn run_stdin_mode() -> Result<()> {
+/// Merge the `--prefill` file into the definition's `initial_values`, which
+/// take precedence over the file's entries
+fn apply_prefill(mut definition: PopupDefinition, path: Option<&str>) -> Result<PopupDefinition> {
+    let Some(path) = path else {
+        return Ok(definition);
+    };
+    let mut values: HashMap<String, serde_json::Value> =
+        serde_json::from_str(&fs::read_to_string(path)?)
+            .map_err(|e| anyhow::anyhow!("prefill file {}: {}", path, e))?;
+    values.extend(definition.initial_values);
+    definition.initial_values = values;
+    Ok(definition)
+}
+
+/// Check `initial_values` once they're complete: after the `--prefill` merge,
+/// and after "Other" options exist for a past result to select
+fn check_initial_values(definition: PopupDefinition) -> Result<PopupDefinition> {
+    let errors = definition.initial_value_errors();
+    if !errors.is_empty() {
+        anyhow::bail!("invalid initial_values: {}", errors.join("; "));
+    }
+    Ok(definition)
+}
+
+fn run_stdin_mode(prefill: Option<&str>) -> Result<()> {
     // Read JSON from stdin
     let mut input = String::new();
     io::stdin().read_to_string(&mut input)?;
 
     // Parse JSON, inject "Other" options, and render popup
     match parse_popup_json(&input)
+        .and_then(|definition| apply_prefill(definition, prefill))
         .map(inject_other_options)
+        .and_then(check_initial_values)
         .and_then(render_popup)
     {
         Ok(result) => {
@@ -51,13 +83,15 @@ fn run_stdin_mode() -> Result<()> {
     }
 }
 
-fn run_file_mode(path: &str) -> Result<()> {
+fn run_file_mode(path: &str, prefill: Option<&str>) -> Result<()> {
     // Read JSON from file
     let input = fs::read_to_string(path)?;
 
     // Parse JSON, inject "Other" options, and render popup
     match parse_popup_json(&input)
+        .and_then(|definition| apply_prefill(definition, prefill))
         .map(inject_other_options)
+        .and_then(check_initial_values)
         .and_then(render_popup)
     {
         Ok(result) => {
@@ -77,10 +111,10 @@ fn main() -> Result<()> {
 
     if args.stdin {
         // Read JSON from stdin and show popup
-        run_stdin_mode()
+        run_stdin_mode(args.prefill.as_deref())
     } else if let Some(file_path) = args.file {
         // Read JSON from file and show popup
-        run_file_mode(&file_path)
+        run_file_mode(&file_path, args.prefill.as_deref())
     } else {
         // MCP server mode (default)
         let server_args = mcp_server::ServerArgs {
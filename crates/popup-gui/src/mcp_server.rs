@@ -4,6 +4,7 @@ use crate::templates;
 use crate::transform::inject_other_options;
 use anyhow::Result;
 use mcpr::schema::json_rpc::{JSONRPCMessage, JSONRPCResponse};
+use popup_common::{PopupDefinition, WhenClauseError};
 use serde::Serialize;
 use serde_json::Value;
 use std::collections::HashMap;
@@ -12,15 +13,48 @@ use std::io::{self, BufRead, Write};
 #[derive(Serialize)]
 struct ErrorResponse {
     error: String,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    condition_errors: Vec<WhenClauseError>,
 }
 
 fn error(msg: impl std::fmt::Display) -> Value {
     serde_json::to_value(ErrorResponse {
         error: msg.to_string(),
+        condition_errors: Vec::new(),
     })
     .unwrap()
 }
 
+/// Reject definitions with unparseable `when` clauses before showing anything,
+/// returning an error listing each clause with its span and suggestions
+fn check_conditions(definition: &PopupDefinition) -> Result<(), Value> {
+    let condition_errors = definition.condition_errors();
+    if condition_errors.is_empty() {
+        return Ok(());
+    }
+
+    let summary: Vec<String> = condition_errors.iter().map(|e| e.to_string()).collect();
+    Err(serde_json::to_value(ErrorResponse {
+        error: summary.join("\n"),
+        condition_errors,
+    })
+    .unwrap())
+}
+
+/// What the logs say about a popup request: its title and size. The definition
+/// itself stays out since it can carry secrets (`initial_values`, confirmation
+/// phrases, secret elements).
+fn describe_popup(args: &Value) -> String {
+    let title = args.get("title").and_then(Value::as_str).unwrap_or("(untitled)");
+    let count = |key: &str| args.get(key).and_then(Value::as_array).map_or(0, Vec::len);
+    format!(
+        "{:?} with {} elements and {} pages",
+        title,
+        count("elements"),
+        count("pages")
+    )
+}
+
 fn spawn_popup_subprocess(json_str: &str) -> Result<Value, String> {
     // Try to find the popup binary:
     // 1. Current executable (self-spawning)
@@ -75,10 +109,10 @@ fn spawn_popup_subprocess(json_str: &str) -> Result<Value, String> {
     // Small delay to ensure window cleanup
     std::thread::sleep(std::time::Duration::from_millis(100));
 
-    // Parse the output
+    // Parse the output. It isn't echoed on failure since it may hold secret inputs.
     if output.status.success() || !stdout_str.trim().is_empty() {
         serde_json::from_str::<Value>(&stdout_str)
-            .map_err(|e| format!("Invalid JSON from popup: {}. Output was: {}", e, stdout_str))
+            .map_err(|e| format!("Invalid JSON from popup: {}", e))
     } else {
         Err(format!(
             "Popup process failed with status: {}. Stderr: {}",
@@ -196,7 +230,8 @@ pub fn run(args: ServerArgs) -> Result<()> {
             continue;
         }
 
-        log::debug!("Received: {}", line);
+        // Only the size: tool calls can carry secrets
+        log::debug!("Received {} bytes", line.len());
 
         // Parse the JSON-RPC message
         match serde_json::from_str::<JSONRPCMessage>(&line) {
@@ -224,10 +259,24 @@ pub fn run(args: ServerArgs) -> Result<()> {
                                         "element_types": {
                                             "text": "Static text display",
                                             "slider": "Numeric range selector with min/max/default",
+                                            "range": "Two-handle slider returning {low, high}",
                                             "checkbox": "Boolean toggle with default state and reveals",
-                                            "choice": "Single selection dropdown with option-as-key nesting",
-                                            "multiselect": "Multiple selection with option-as-key nesting",
+                                            "choice": "Single selection dropdown with option-as-key nesting, optionally searchable",
+                                            "multiselect": "Multiple selection with option-as-key nesting, optionally searchable",
                                             "textbox": "Text input with optional placeholder and multiline",
+                                            "path": "File/directory browser returning absolute paths",
+                                            "color": "Color picker with palette presets, returns a hex string",
+                                            "confirm_phrase": "Typed-phrase confirmation that gates submit for destructive actions",
+                                            "secret": "Masked input kept out of logs, optionally returned via a temp file",
+                                            "image": "Local image file or base64 data URI with alt text",
+                                            "code": "Syntax-highlighted code block with line numbers, optionally editable",
+                                            "rating": "Star or numbered-point scale with end labels",
+                                            "matrix": "Statements by shared choice columns, returned as an object keyed by row",
+                                            "tags": "Free-form tag chips with suggestions, returned as a string array",
+                                            "kv": "Key-value editor with optional fixed keys, returned as a JSON object",
+                                            "list": "Repeatable rows of template fields, returned as an array of objects",
+                                            "table": "Sortable, filterable data table with single or multi row selection",
+                                            "diff": "Unified diff with per-hunk accept/reject and comments",
                                             "group": "Collapsible container for related elements"
                                         },
                                         "version": version
@@ -283,25 +332,29 @@ pub fn run(args: ServerArgs) -> Result<()> {
                         JSONRPCResponse::new(req.id, serde_json::json!({"prompts": []}))
                     }
                     "tools/call" => {
-                        log::debug!("Tool call params: {:?}", req.params);
-
                         let params = req.params.unwrap_or(Value::Null);
                         let tool_name = params.get("name").and_then(|n| n.as_str()).unwrap_or("");
                         let tool_args = params.get("arguments").cloned().unwrap_or(Value::Null);
 
                         let result = if tool_name == "popup" {
                             // tool_args IS the popup definition (title, elements, etc.)
-                            log::info!("Showing popup with args: {:?}", tool_args);
+                            log::info!("Showing popup {}", describe_popup(&tool_args));
 
                             let json_str = serde_json::to_string(&tool_args).unwrap_or_else(|e| {
                                 log::error!("Failed to serialize JSON: {}", e);
                                 "{}".to_string()
                             });
 
+                            // Malformed definitions are reported by the subprocess itself
+                            let checked = serde_json::from_value::<PopupDefinition>(tool_args)
+                                .map_or(Ok(()), |def| check_conditions(&def));
+
                             // Spawn popup subprocess and get result
-                            match spawn_popup_subprocess(&json_str) {
+                            match checked
+                                .and_then(|_| spawn_popup_subprocess(&json_str).map_err(error))
+                            {
                                 Ok(popup_result) => popup_result,
-                                Err(e) => error(e),
+                                Err(e) => e,
                             }
                         } else {
                             // Check if it's a template tool
@@ -334,9 +387,11 @@ pub fn run(args: ServerArgs) -> Result<()> {
                                             });
 
                                         // Spawn popup subprocess and get result
-                                        match spawn_popup_subprocess(&json_str) {
+                                        match check_conditions(&transformed_def).and_then(|_| {
+                                            spawn_popup_subprocess(&json_str).map_err(error)
+                                        }) {
                                             Ok(popup_result) => popup_result,
-                                            Err(e) => error(e),
+                                            Err(e) => e,
                                         }
                                     }
                                     Err(e) => {
@@ -390,3 +445,24 @@ pub fn run(args: ServerArgs) -> Result<()> {
     log::info!("Server exiting");
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_describe_popup_leaves_out_secrets() {
+        let args = serde_json::json!({
+            "title": "Rotate key",
+            "elements": [
+                {"secret": "Token", "id": "token"},
+                {"confirm_phrase": "drop-prod-db"}
+            ],
+            "initial_values": {"token": "hunter2"}
+        });
+        let described = describe_popup(&args);
+        assert_eq!(described, "\"Rotate key\" with 2 elements and 0 pages");
+        assert!(!described.contains("hunter2"));
+        assert!(!described.contains("drop-prod-db"));
+    }
+}
@@ -12,7 +12,7 @@ use serde_json::json;
 pub fn get_popup_tool_schema() -> serde_json::Value {
     json!({
         "name": "popup",
-        "description": "Create a rich, branching GUI interaction that captures a full decision tree in a single turn.\n\nPHILOSOPHY: Stop the back-and-forth. Don't ask a question, wait for an answer, and then ask another. Instead, anticipate the user's choices and pre-load the appropriate follow-up questions into the interface.\n\nCORE MECHANIC: Deeply nested conditional logic.\n- If asking 'Deployment Environment', immediately nest 'Production' and 'Staging' specific config fields under those respective options.\n\nCRITICAL STRUCTURAL RULE: Branch definitions must be INSIDE the parent widget object, NOT as the next item in the list.\n\n✅ CORRECT (Nested):\n[\n  { \"select\": \"Mode\", \"options\": \"A, B\", \"A\": [{...}], \"B\": [{...}] }\n]\n\n❌ INCORRECT (Sibling):\n[\n  { \"select\": \"Mode\", \"options\": \"A, B\" },\n  { \"A\": [{...}] } // Error: This is a standalone object\n]\n\nBRANCHING SYNTAX:\n- Option-Specific Children: \"Prod\": [{...prod_fields...}] (Preferred)\n- Checkbox/Reveal: \"reveals\": [{...config...}]\n- Complex Logic: \"when\": \"env == 'Prod' && !use_existing_key\"\n\nAUTO-INJECTED 'OTHER':\n'select' and 'multi' widgets automatically get an 'Other (please specify)' option. Do NOT add it manually.\n\nRETURNS: {\"status\": \"completed\", \"button\": \"submit\", \"field_id\": value}\n- select/multi return the text value (e.g., \"Prod\")",
+        "description": "Create a rich, branching GUI interaction that captures a full decision tree in a single turn.\n\nPHILOSOPHY: Stop the back-and-forth. Don't ask a question, wait for an answer, and then ask another. Instead, anticipate the user's choices and pre-load the appropriate follow-up questions into the interface.\n\nCORE MECHANIC: Deeply nested conditional logic.\n- If asking 'Deployment Environment', immediately nest 'Production' and 'Staging' specific config fields under those respective options.\n\nCRITICAL STRUCTURAL RULE: Branch definitions must be INSIDE the parent widget object, NOT as the next item in the list.\n\n✅ CORRECT (Nested):\n[\n  { \"select\": \"Mode\", \"options\": \"A, B\", \"A\": [{...}], \"B\": [{...}] }\n]\n\n❌ INCORRECT (Sibling):\n[\n  { \"select\": \"Mode\", \"options\": \"A, B\" },\n  { \"A\": [{...}] } // Error: This is a standalone object\n]\n\nBRANCHING SYNTAX:\n- Option-Specific Children: \"Prod\": [{...prod_fields...}] (Preferred)\n- Checkbox/Reveal: \"reveals\": [{...config...}]\n- Complex Logic: \"when\": \"env == 'Prod' && !use_existing_key\"\n- Expressions: arithmetic (cpu + mem > 80), membership (env in ['Prod', 'Staging']), contains/starts_with/ends_with/len/lower/matches(id, 'regex')/empty(id), dotted keys (survey.row_id == 'Agree')\n\nTABS: {\"group\": \"Settings\", \"layout\": \"tabs\", \"elements\": [{\"group\": \"General\", ...}, {\"group\": \"Advanced\", ...}]} shows each child group as a tab, keeping settings-style popups short.\n\nIMAGES: {\"image\": \"/abs/path.png\" or \"data:image/png;base64,...\", \"alt\": \"...\", \"max_width\": 300}. Options can carry thumbnails: {\"value\": \"A\", \"description\": \"...\", \"icon\": \"/path/a.png\"}. Remote URLs are rejected.\n\nDRAFTS: propose an answer the user only has to edit: \"default\" on an input prefills its text (\"value\" works too), on a multi it checks a list of option values, on a select it picks one option. To reopen a popup with earlier answers, pass the previous result as top-level \"initial_values\": {\"env\": \"Prod\", \"budget\": {\"low\": 10, \"high\": 50}}. Secrets, confirmation phrases and diff reviews are never prefilled; the user answers them again.\n\nSEARCHABLE: add \"searchable\": true to a select or multi with dozens of options to get a filter box with fuzzy matching and keyboard navigation.\n\nRANGES: {\"range\": \"Acceptable latency (ms)\", \"id\": \"latency\", \"min\": 0, \"max\": 1000, \"step\": 50, \"low\": 100, \"high\": 400} is one slider with two handles that can't cross. Returns {\"low\": 100, \"high\": 400}; use latency.low / latency.high in conditions. Prefer it over two separate sliders.\n\nRATINGS: {\"rating\": \"Energy\", \"max\": 5, \"low_label\": \"Drained\", \"high_label\": \"Buzzing\", \"style\": \"stars\"|\"points\"} returns the score (1..max).\n\nMATRIX: {\"matrix\": \"How far do you agree?\", \"id\": \"survey\", \"rows\": [\"Pace was right\", {\"id\": \"scope\", \"label\": \"Scope was clear\"}], \"columns\": [\"Disagree\", \"Neutral\", \"Agree\"]} takes one choice per row and returns {\"pace_was_right\": \"Agree\", ...}; use survey.scope == 'Agree' in conditions.\n\nPATHS: {\"path\": \"Config file\", \"root\": \"~/project\", \"filters\": [\"*.toml\", \"*.json\"], \"mode\": \"file\"|\"dir\"|\"both\", \"multiple\": false} shows a file browser instead of a free-text box, so no typos. Returns the absolute path (an array with multiple); picks that no longer exist block submit.\n\nCOLORS: {\"color\": \"Faction color\", \"default\": \"#3366ff\", \"palette\": [\"#b22222\", \"#daa520\"], \"alpha\": false} opens a color picker with preset swatches and returns a lowercase hex string (\"#rrggbb\", or \"#rrggbbaa\" with alpha).\n\nCONFIRM PHRASE: {\"confirm_phrase\": \"prod-db-main\", \"label\": \"This deletes the production database\"} keeps Submit disabled until the phrase is typed exactly (\"case_sensitive\": false to relax) and marks the footer as dangerous. Use instead of a checkbox for irreversible actions; returns true.\n\nSECRETS: {\"secret\": \"API token\", \"to_file\": true} shows a masked input with a reveal toggle. The value is never logged; with to_file it comes back as {\"file\": \"/tmp/...\"} (readable only by you, delete it after use) instead of inline.\n\nCODE: {\"code\": \"fn main() {}\", \"language\": \"rust\"} shows highlighted code with line numbers; add \"editable\": true and an \"id\" to get the edited text back.\n\nTAGS: {\"tags\": \"Labels\", \"suggestions\": [\"bug\", \"docs\"], \"max_tags\": 5, \"pattern\": \"[a-z-]+\"} collects free-form tokens as chips and returns a string array; count(id) and selected(id, 'bug') work in conditions.\n\nKEY-VALUE: {\"kv\": \"Headers\", \"keys\": [\"Accept\", \"Authorization\"], \"placeholder\": \"value\"} edits key/value pairs (omit keys to let the user add their own) and returns a JSON object. Duplicate keys block submit.\n\nLISTS: {\"list\": \"Reviewers\", \"id\": \"reviewers\", \"min_items\": 1, \"max_items\": 5, \"elements\": [{\"input\": \"Name\", \"id\": \"name\"}]} lets the user add, remove and reorder rows of the template. Returns an array of objects, one per row, keyed by the template IDs; count(id) gives the number of rows.\n\nTABLES: {\"table\": \"Failing tests\", \"columns\": [\"name\", \"secs\"], \"rows\": [[\"test_a\", 1.2]], \"selection\": \"multi\"} shows sortable, filterable rows and returns the selected rows' key column values (first column unless \"key\" is set) as an array; count(id) works in conditions.\n\nDIFF REVIEW: {\"diff\": \"<unified diff>\", \"id\": \"review\", \"label\": \"...\"} lets the user accept/reject and comment on each hunk. Returns {\"path\": [{\"hunk\": 0, \"decision\": \"accept\"|\"reject\"|null, \"comment\": \"...\"}]}.\n\nLAYOUT: \"layout\": \"single\" | \"row\" | {\"columns\": N} | \"auto\" (default) on the popup or a group; \"span\": \"full\" makes an element cross all columns. Columns fill top to bottom, so dependent questions stay in order.\n\nWIZARDS: For long interviews, use \"pages\": [{\"title\": \"Step\", \"elements\": [...], \"when\": \"...\", \"required\": [\"id\"]}] instead of \"elements\". Pages show one at a time with Back/Next; skipped pages are left out of the result.\n\nAUTO-INJECTED 'OTHER':\n'select' and 'multi' widgets automatically get an 'Other (please specify)' option. Do NOT add it manually.\n\nRETURNS: {\"status\": \"completed\", \"button\": \"submit\", \"field_id\": value}\n- select/multi return the text value (e.g., \"Prod\")",
         "inputSchema": get_input_schema()
     })
 }
@@ -22,7 +22,7 @@ pub fn get_popup_tool_schema() -> serde_json::Value {
 /// Use this if you want to customize the name or description
 /// but need the proper schema for the popup structure.
 pub fn get_input_schema() -> serde_json::Value {
-    json!({
+    let mut schema = json!({
         "type": "object",
         "properties": {
             "title": {
@@ -31,7 +31,7 @@ pub fn get_input_schema() -> serde_json::Value {
             },
             "elements": {
                 "type": "array",
-                "description": "Array of UI elements to display",
+                "description": "Array of UI elements to display (required unless `pages` is used)",
                 "items": {
                     "oneOf": [
                         // Text element (V2: element-as-key, id optional)
@@ -86,6 +86,47 @@ pub fn get_input_schema() -> serde_json::Value {
                             "required": ["slider", "id", "min", "max"],
                             "additionalProperties": false
                         },
+                        // Range element (V2: label is the key)
+                        {
+                            "type": "object",
+                            "properties": {
+                                "range": {
+                                    "type": "string",
+                                    "description": "Label for the two-handle slider"
+                                },
+                                "id": {
+                                    "type": "string",
+                                    "description": "Element ID (default: snake_case of the label)"
+                                },
+                                "min": {
+                                    "type": "number",
+                                    "description": "Lowest value either handle can take"
+                                },
+                                "max": {
+                                    "type": "number",
+                                    "description": "Highest value either handle can take"
+                                },
+                                "step": {
+                                    "type": "number",
+                                    "exclusiveMinimum": 0,
+                                    "description": "Snap interval from min (default 1)"
+                                },
+                                "low": {
+                                    "type": "number",
+                                    "description": "Starting low handle (default: min)"
+                                },
+                                "high": {
+                                    "type": "number",
+                                    "description": "Starting high handle (default: max)"
+                                },
+                                "when": {
+                                    "type": "string",
+                                    "description": "Condition for visibility. Syntax: id (truthy check), selected(id, \"value\"), count(id) > N, with &&/||/! operators"
+                                }
+                            },
+                            "required": ["range", "min", "max"],
+                            "additionalProperties": false
+                        },
                         // Checkbox element (V2: checkbox is the key, id required)
                         {
                             "type": "object",
@@ -138,6 +179,14 @@ pub fn get_input_schema() -> serde_json::Value {
                                     "minimum": 1,
                                     "description": "Number of rows for multiline input (optional)"
                                 },
+                                "default": {
+                                    "type": "string",
+                                    "description": "Draft text the input starts with; submitted as-is unless the user edits it"
+                                },
+                                "value": {
+                                    "type": "string",
+                                    "description": "Same as default (use one or the other)"
+                                },
                                 "when": {
                                     "type": "string",
                                     "description": "Condition for visibility. Syntax: id (truthy check), selected(id, \"value\"), count(id) > N, with &&/||/! operators"
@@ -146,6 +195,135 @@ pub fn get_input_schema() -> serde_json::Value {
                             "required": ["input", "id"],
                             "additionalProperties": false
                         },
+                        // Color picker (V2: label is the key)
+                        {
+                            "type": "object",
+                            "properties": {
+                                "color": {
+                                    "type": "string",
+                                    "description": "Label for the color picker"
+                                },
+                                "id": {
+                                    "type": "string",
+                                    "description": "Element ID (default: snake_case of the label)"
+                                },
+                                "default": {
+                                    "type": "string",
+                                    "description": "Initial color as hex: #rgb, #rrggbb or #rrggbbaa"
+                                },
+                                "alpha": {
+                                    "type": "boolean",
+                                    "description": "Allow transparency and return #rrggbbaa (default false: #rrggbb)"
+                                },
+                                "palette": {
+                                    "type": "array",
+                                    "items": { "type": "string" },
+                                    "description": "Preset swatches as hex colors, shown under the picker"
+                                },
+                                "when": {
+                                    "type": "string",
+                                    "description": "Condition for visibility. Syntax: id (truthy check), selected(id, \"value\"), count(id) > N, with &&/||/! operators"
+                                }
+                            },
+                            "required": ["color"],
+                            "additionalProperties": false
+                        },
+                        // File/directory picker (V2: label is the key)
+                        {
+                            "type": "object",
+                            "properties": {
+                                "path": {
+                                    "type": "string",
+                                    "description": "Label for the path picker"
+                                },
+                                "id": {
+                                    "type": "string",
+                                    "description": "Element ID (default: snake_case of the label)"
+                                },
+                                "root": {
+                                    "type": "string",
+                                    "description": "Directory the browser opens in and stays within; ~ expands to home (default: the working directory)"
+                                },
+                                "filters": {
+                                    "oneOf": [
+                                        { "type": "array", "items": { "type": "string" } },
+                                        { "type": "string", "description": "Comma-separated globs" }
+                                    ],
+                                    "description": "File name globs such as '*.rs' (* and ?, case-insensitive). Directories are always listed"
+                                },
+                                "mode": {
+                                    "type": "string",
+                                    "enum": ["file", "dir", "both"],
+                                    "description": "What can be picked (default: file)"
+                                },
+                                "multiple": {
+                                    "type": "boolean",
+                                    "description": "Allow several picks and return an array (default false: one path)"
+                                },
+                                "when": {
+                                    "type": "string",
+                                    "description": "Condition for visibility. Syntax: id (truthy check), selected(id, \"value\"), count(id) > N, with &&/||/! operators"
+                                }
+                            },
+                            "required": ["path"],
+                            "additionalProperties": false
+                        },
+                        // Typed-phrase confirmation (V2: the phrase is the key)
+                        {
+                            "type": "object",
+                            "properties": {
+                                "confirm_phrase": {
+                                    "type": "string",
+                                    "description": "Exact text the user must type before Submit enables, e.g. the resource name"
+                                },
+                                "id": {
+                                    "type": "string",
+                                    "description": "Element ID (default: confirm_<phrase>)"
+                                },
+                                "label": {
+                                    "type": "string",
+                                    "description": "Warning shown above the input, e.g. 'This permanently deletes prod-db'"
+                                },
+                                "case_sensitive": {
+                                    "type": "boolean",
+                                    "description": "Require matching case (default true)"
+                                },
+                                "when": {
+                                    "type": "string",
+                                    "description": "Condition for visibility. Syntax: id (truthy check), selected(id, \"value\"), count(id) > N, with &&/||/! operators"
+                                }
+                            },
+                            "required": ["confirm_phrase"],
+                            "additionalProperties": false
+                        },
+                        // Secret input (V2: label is the key)
+                        {
+                            "type": "object",
+                            "properties": {
+                                "secret": {
+                                    "type": "string",
+                                    "description": "Label for the masked input (tokens, passphrases)"
+                                },
+                                "id": {
+                                    "type": "string",
+                                    "description": "Element ID (derived from label if omitted)"
+                                },
+                                "placeholder": {
+                                    "type": "string",
+                                    "description": "Hint shown in the empty input"
+                                },
+                                "to_file": {
+                                    "type": "boolean",
+                                    "description": "Return {\"file\": path} to a temp file readable only by the current user instead of the value itself. Delete the file once read."
+                                },
+                                "when": {
+                                    "type": "string",
+                                    "description": "Condition for visibility. Syntax: id (truthy check), selected(id, \"value\"), count(id) > N, with &&/||/! operators"
+                                }
+                            },
+                            "required": ["secret"],
+                            "additionalProperties": false
+                        },
                         // Multiselect element (V2: multiselect is the key, id required, option-as-key for children)
                         {
                             "type": "object",
@@ -162,7 +340,24 @@ pub fn get_input_schema() -> serde_json::Value {
                                     "oneOf": [
                                         {
                                             "type": "array",
-                                            "items": { "type": "string" },
+                                            "items": {
+                                                "oneOf": [
+                                                    { "type": "string" },
+                                                    {
+                                                        "type": "object",
+                                                        "properties": {
+                                                            "value": { "type": "string" },
+                                                            "description": { "type": "string" },
+                                                            "icon": {
+                                                                "type": "string",
+                                                                "description": "Thumbnail: local file path or base64 data URI (no remote URLs)"
+                                                            }
+                                                        },
+                                                        "required": ["value", "description"],
+                                                        "additionalProperties": false
+                                                    }
+                                                ]
+                                            },
                                             "minItems": 1
                                         },
                                         {
@@ -183,10 +378,21 @@ pub fn get_input_schema() -> serde_json::Value {
                                     ],
                                     "description": "Child elements shown when any option is selected. Can be an array or a single element object."
                                 },
+                                "searchable": {
+                                    "type": "boolean",
+                                    "description": "Show a type-to-filter list (fuzzy match on value and description, arrow keys + Enter) instead of a checkbox grid. Use for long option lists."
+                                },
+                                "default": {
+                                    "oneOf": [
+                                        { "type": "array", "items": { "type": "string" } },
+                                        { "type": "string", "description": "Comma-separated option values" }
+                                    ],
+                                    "description": "Option values checked at the start (each must match an option)"
+                                },
                             },
                             "required": ["multi", "options"],
                             "patternProperties": {
-                                "^(?!multi|id|options|when|reveals).*$": {
+                                "^(?!multi|id|options|when|reveals|searchable|default).*$": {
                                     "oneOf": [
                                         { "$ref": "#/properties/elements" },
                                         { "$ref": "#/properties/elements/items" },
@@ -213,7 +419,24 @@ pub fn get_input_schema() -> serde_json::Value {
                                     "oneOf": [
                                         {
                                             "type": "array",
-                                            "items": { "type": "string" },
+                                            "items": {
+                                                "oneOf": [
+                                                    { "type": "string" },
+                                                    {
+                                                        "type": "object",
+                                                        "properties": {
+                                                            "value": { "type": "string" },
+                                                            "description": { "type": "string" },
+                                                            "icon": {
+                                                                "type": "string",
+                                                                "description": "Thumbnail: local file path or base64 data URI (no remote URLs)"
+                                                            }
+                                                        },
+                                                        "required": ["value", "description"],
+                                                        "additionalProperties": false
+                                                    }
+                                                ]
+                                            },
                                             "minItems": 1
                                         },
                                         {
@@ -238,10 +461,14 @@ pub fn get_input_schema() -> serde_json::Value {
                                     ],
                                     "description": "Child elements shown when any option is selected. Can be an array or a single element object."
                                 },
+                                "searchable": {
+                                    "type": "boolean",
+                                    "description": "Show a type-to-filter list (fuzzy match on value and description, arrow keys + Enter) instead of a dropdown. Use for long option lists."
+                                },
                             },
                             "required": ["select", "options"],
                             "patternProperties": {
-                                "^(?!select|id|options|default|when|reveals).*$": {
+                                "^(?!select|id|options|default|when|reveals|searchable).*$": {
                                     "oneOf": [
                                         { "$ref": "#/properties/elements" },
                                         { "$ref": "#/properties/elements/items" },
@@ -252,6 +479,330 @@ pub fn get_input_schema() -> serde_json::Value {
                             },
                             "additionalProperties": false
                         },
+                        // Image element (V2: image source is the key, id optional)
+                        {
+                            "type": "object",
+                            "properties": {
+                                "image": {
+                                    "type": "string",
+                                    "description": "Local file path or base64 data URI (data:image/png;base64,...). Remote URLs are rejected"
+                                },
+                                "id": {
+                                    "type": "string",
+                                    "description": "Optional element ID"
+                                },
+                                "alt": {
+                                    "type": "string",
+                                    "description": "Alternative text, shown on hover or if the image can't be loaded"
+                                },
+                                "max_width": {
+                                    "type": "number",
+                                    "description": "Maximum displayed width in points"
+                                },
+                                "max_height": {
+                                    "type": "number",
+                                    "description": "Maximum displayed height in points (default 400)"
+                                },
+                                "when": {
+                                    "type": "string",
+                                    "description": "Condition for visibility. Syntax: id (truthy check), selected(id, \"value\"), count(id) > N, with &&/||/! operators"
+                                }
+                            },
+                            "required": ["image"],
+                            "additionalProperties": false
+                        },
+                        // Code element (V2: source text is the key)
+                        {
+                            "type": "object",
+                            "properties": {
+                                "code": {
+                                    "type": "string",
+                                    "description": "Source code to show, syntax highlighted"
+                                },
+                                "id": {
+                                    "type": "string",
+                                    "description": "Element ID (required when editable)"
+                                },
+                                "language": {
+                                    "type": "string",
+                                    "description": "Language for highlighting, e.g. rust, python, c, cpp, toml"
+                                },
+                                "editable": {
+                                    "type": "boolean",
+                                    "description": "Let the user edit the code; the edited text is returned under id"
+                                },
+                                "line_numbers": {
+                                    "type": "boolean",
+                                    "description": "Show a line number gutter (default true)"
+                                },
+                                "when": {
+                                    "type": "string",
+                                    "description": "Condition for visibility. Syntax: id (truthy check), selected(id, \"value\"), count(id) > N, with &&/||/! operators"
+                                }
+                            },
+                            "required": ["code"],
+                            "additionalProperties": false
+                        },
+                        // Table element (V2: label is the key)
+                        {
+                            "type": "object",
+                            "properties": {
+                                "table": {
+                                    "type": "string",
+                                    "description": "Label shown above the table"
+                                },
+                                "id": {
+                                    "type": "string",
+                                    "description": "Element ID (derived from label if omitted)"
+                                },
+                                "columns": {
+                                    "type": "array",
+                                    "items": { "type": "string" },
+                                    "description": "Column names"
+                                },
+                                "rows": {
+                                    "type": "array",
+                                    "items": {
+                                        "oneOf": [
+                                            { "type": "array", "description": "Cells by position" },
+                                            { "type": "object", "description": "Cells by column name" }
+                                        ]
+                                    }
+                                },
+                                "key": {
+                                    "type": "string",
+                                    "description": "Column whose values identify rows in the result (default: first column)"
+                                },
+                                "selection": {
+                                    "type": "string",
+                                    "enum": ["none", "single", "multi"],
+                                    "description": "Let the user select rows; returns an array of row keys (default none)"
+                                },
+                                "when": {
+                                    "type": "string",
+                                    "description": "Condition for visibility. Syntax: id (truthy check), selected(id, \"value\"), count(id) > N, with &&/||/! operators"
+                                }
+                            },
+                            "required": ["table", "columns"],
+                            "additionalProperties": false
+                        },
+                        // Rating scale (V2: label is the key)
+                        {
+                            "type": "object",
+                            "properties": {
+                                "rating": {
+                                    "type": "string",
+                                    "description": "Label for the rating"
+                                },
+                                "id": {
+                                    "type": "string",
+                                    "description": "Element ID (derived from label if omitted)"
+                                },
+                                "max": {
+                                    "type": "integer",
+                                    "minimum": 2,
+                                    "maximum": 10,
+                                    "description": "Number of stars/points (default 5)"
+                                },
+                                "default": {
+                                    "type": "integer",
+                                    "minimum": 1,
+                                    "description": "Initial score (omit for none)"
+                                },
+                                "low_label": {
+                                    "type": "string",
+                                    "description": "Caption at the low end, e.g. 'Not at all'"
+                                },
+                                "high_label": {
+                                    "type": "string",
+                                    "description": "Caption at the high end, e.g. 'Extremely'"
+                                },
+                                "style": {
+                                    "type": "string",
+                                    "enum": ["stars", "points"],
+                                    "description": "Stars (default) or numbered points for Likert-style scales"
+                                },
+                                "when": {
+                                    "type": "string",
+                                    "description": "Condition for visibility. Syntax: id (truthy check), selected(id, \"value\"), count(id) > N, with &&/||/! operators"
+                                }
+                            },
+                            "required": ["rating"],
+                            "additionalProperties": false
+                        },
+                        // Matrix question (V2: label is the key)
+                        {
+                            "type": "object",
+                            "properties": {
+                                "matrix": {
+                                    "type": "string",
+                                    "description": "Label for the question grid"
+                                },
+                                "id": {
+                                    "type": "string",
+                                    "description": "Element ID (derived from label if omitted)"
+                                },
+                                "rows": {
+                                    "type": "array",
+                                    "items": {
+                                        "oneOf": [
+                                            { "type": "string" },
+                                            {
+                                                "type": "object",
+                                                "properties": {
+                                                    "id": { "type": "string" },
+                                                    "label": { "type": "string" }
+                                                },
+                                                "required": ["label"],
+                                                "additionalProperties": false
+                                            }
+                                        ]
+                                    },
+                                    "minItems": 1,
+                                    "description": "Statements, one per row. Row ids are derived from the text unless given"
+                                },
+                                "columns": {
+                                    "oneOf": [
+                                        { "type": "array", "items": { "type": "string" }, "minItems": 2 },
+                                        { "type": "string", "description": "Comma-separated choices" }
+                                    ],
+                                    "description": "Choices shared by every row, e.g. Disagree, Neutral, Agree"
+                                },
+                                "when": {
+                                    "type": "string",
+                                    "description": "Condition for visibility. Syntax: id (truthy check), selected(id, \"value\"), count(id) > N, with &&/||/! operators"
+                                }
+                            },
+                            "required": ["matrix", "rows", "columns"],
+                            "additionalProperties": false
+                        },
+                        // Tags input (V2: label is the key)
+                        {
+                            "type": "object",
+                            "properties": {
+                                "tags": {
+                                    "type": "string",
+                                    "description": "Label for the tag input"
+                                },
+                                "id": {
+                                    "type": "string",
+                                    "description": "Element ID (derived from label if omitted)"
+                                },
+                                "suggestions": {
+                                    "oneOf": [
+                                        { "type": "array", "items": { "type": "string" } },
+                                        { "type": "string", "description": "Comma-separated suggestions" }
+                                    ],
+                                    "description": "Autocomplete suggestions. Users may still type other tags"
+                                },
+                                "max_tags": {
+                                    "type": "integer",
+                                    "minimum": 1,
+                                    "description": "Most tags allowed"
+                                },
+                                "pattern": {
+                                    "type": "string",
+                                    "description": "Regex every tag must match in full, e.g. [a-z0-9-]+"
+                                },
+                                "placeholder": {
+                                    "type": "string",
+                                    "description": "Hint shown in the empty input"
+                                },
+                                "when": {
+                                    "type": "string",
+                                    "description": "Condition for visibility. Syntax: id (truthy check), selected(id, \"value\"), count(id) > N, with &&/||/! operators"
+                                }
+                            },
+                            "required": ["tags"],
+                            "additionalProperties": false
+                        },
+                        // Key-value editor (V2: label is the key)
+                        {
+                            "type": "object",
+                            "properties": {
+                                "kv": {
+                                    "type": "string",
+                                    "description": "Label for the key-value editor"
+                                },
+                                "id": {
+                                    "type": "string",
+                                    "description": "Element ID (derived from label if omitted)"
+                                },
+                                "keys": {
+                                    "type": "array",
+                                    "items": { "type": "string" },
+                                    "description": "Fixed set of keys. Omit to let the user add their own"
+                                },
+                                "placeholder": {
+                                    "type": "string",
+                                    "description": "Hint shown in empty value boxes"
+                                },
+                                "when": {
+                                    "type": "string",
+                                    "description": "Condition for visibility. Syntax: id (truthy check), selected(id, \"value\"), count(id) > N, with &&/||/! operators"
+                                }
+                            },
+                            "required": ["kv"],
+                            "additionalProperties": false
+                        },
+                        // List element (V2: label is the key)
+                        {
+                            "type": "object",
+                            "properties": {
+                                "list": {
+                                    "type": "string",
+                                    "description": "Label for the repeatable list"
+                                },
+                                "id": {
+                                    "type": "string",
+                                    "description": "Element ID (derived from label if omitted)"
+                                },
+                                "elements": {
+                                    "$ref": "#/properties/elements",
+                                    "description": "Template for each row. IDs and when clauses refer to fields in the same row"
+                                },
+                                "min_items": {
+                                    "type": "integer",
+                                    "minimum": 0,
+                                    "description": "Fewest rows allowed (default 0)"
+                                },
+                                "max_items": {
+                                    "type": "integer",
+                                    "minimum": 1,
+                                    "description": "Most rows allowed"
+                                },
+                                "when": {
+                                    "type": "string",
+                                    "description": "Condition for visibility. Syntax: id (truthy check), selected(id, \"value\"), count(id) > N, with &&/||/! operators"
+                                }
+                            },
+                            "required": ["list", "elements"],
+                            "additionalProperties": false
+                        },
+                        // Diff element (V2: unified diff text is the key)
+                        {
+                            "type": "object",
+                            "properties": {
+                                "diff": {
+                                    "type": "string",
+                                    "description": "Unified diff text (git diff / diff -u). Each hunk gets accept/reject and a comment box"
+                                },
+                                "id": {
+                                    "type": "string",
+                                    "description": "Element ID (derived from label if omitted)"
+                                },
+                                "label": {
+                                    "type": "string",
+                                    "description": "Heading shown above the diff"
+                                },
+                                "when": {
+                                    "type": "string",
+                                    "description": "Condition for visibility. Syntax: id (truthy check), selected(id, \"value\"), count(id) > N, with &&/||/! operators"
+                                }
+                            },
+                            "required": ["diff"],
+                            "additionalProperties": false
+                        },
                         // Group element (V2: group is the key)
                         {
                             "type": "object",
@@ -264,6 +815,18 @@ pub fn get_input_schema() -> serde_json::Value {
                                     "$ref": "#/properties/elements",
                                     "description": "Nested elements within the group"
                                 },
+                                "layout": {
+                                    "$ref": "#/properties/layout",
+                                    "description": "Arrangement of the group's elements. 'tabs' shows child groups as tabs, one at a time; fields in hidden tabs (failing `when`) are left out of the result"
+                                },
+                                "collapsed": {
+                                    "type": "boolean",
+                                    "description": "Start folded shut (default false). Fields in collapsed groups are still returned"
+                                },
+                                "summary": {
+                                    "type": "string",
+                                    "description": "Header text while collapsed; defaults to a digest of the answered fields"
+                                },
                                 "when": {
                                     "type": "string",
                                     "description": "Condition for visibility. Syntax: id (truthy check), selected(id, \"value\"), count(id) > N, with &&/||/! operators"
@@ -274,9 +837,34 @@ pub fn get_input_schema() -> serde_json::Value {
                         }
                     ]
                 }
+            },
+            "version": {
+                "type": "integer",
+                "description": "Schema version. 3 and later evaluate `when` clauses with hidden fields unset (null) by default; omit for v2 behavior"
+            },
+            "initial_values": {
+                "type": "object",
+                "description": "Starting values by element id, in the result format (option text for select/multi, {low, high} for range, ...). Feed a previous result back in to let the user revise it; its status and button keys are ignored, and secrets, confirm phrases and diffs are never prefilled. Unknown ids or wrongly typed values are rejected"
+            },
+            "hidden_fields": {
+                "type": "string",
+                "enum": ["keep", "unset"],
+                "description": "How `when` clauses see fields of hidden elements: 'unset' reads them as null (default for version 3+), 'keep' uses their last value (default before version 3)"
+            },
+            "layout": {
+                "oneOf": [
+                    {"type": "string", "enum": ["auto", "single", "row", "tabs"]},
+                    {
+                        "type": "object",
+                        "properties": {"columns": {"type": "integer", "minimum": 1}},
+                        "required": ["columns"],
+                        "additionalProperties": false
+                    }
+                ],
+                "description": "How elements are arranged: 'auto' (default) uses two columns when wide enough, 'single' one per line, 'row' side by side, {\"columns\": N} N columns filled top to bottom"
             }
         },
-        "required": ["title", "elements"],
+        "required": ["title"],
         "additionalProperties": false,
         "examples": [
             {
@@ -313,7 +901,44 @@ pub fn get_input_schema() -> serde_json::Value {
                 ]
             }
         ]
-    })
+    });
+
+    // Any element can take the full width in a multi-column layout
+    if let Some(variants) = schema["properties"]["elements"]["items"]["oneOf"].as_array_mut() {
+        for variant in variants {
+            variant["properties"]["span"] = json!({
+                "type": "string",
+                "enum": ["full"],
+                "description": "'full' spans every column instead of taking one cell"
+            });
+        }
+    }
+
+    // Pages hold the same element types as the top-level list
+    let element_items = schema["properties"]["elements"]["items"].clone();
+    schema["properties"]["pages"] = json!({
+        "type": "array",
+        "description": "Multi-page wizard: pages are shown one at a time with Back/Next and a progress bar, and all answers come back in one result. Use instead of `elements` for long interviews",
+        "items": {
+            "type": "object",
+            "properties": {
+                "title": {"type": "string", "description": "Page heading"},
+                "elements": {"type": "array", "items": element_items},
+                "when": {
+                    "type": "string",
+                    "description": "Condition for showing the page; skipped pages are left out of the result"
+                },
+                "required": {
+                    "type": "array",
+                    "items": {"type": "string"},
+                    "description": "Ids that must be answered before moving past the page"
+                }
+            },
+            "required": ["title", "elements"],
+            "additionalProperties": false
+        }
+    });
+    schema
 }
 
 /// Get a human-readable description of the popup schema
@@ -334,9 +959,9 @@ STRUCTURE:
     {\"text\": \"Display text\"},
     {\"slider\": \"Label\", \"id\": \"x\", \"min\": 0, \"max\": 100},
     {\"check\": \"Label\", \"id\": \"x\", \"reveals\": [...]},
-    {\"input\": \"Label\", \"id\": \"x\", \"placeholder\": \"...\"},
+    {\"input\": \"Label\", \"id\": \"x\", \"placeholder\": \"...\", \"default\": \"draft text\"},
     {\"select\": \"Label\", \"id\": \"x\", \"options\": [\"A\", \"B\"], \"A\": [...]},
-    {\"multi\": \"Label\", \"id\": \"x\", \"options\": [\"A\", \"B\"], \"A\": [...]},
+    {\"multi\": \"Label\", \"id\": \"x\", \"options\": [\"A\", \"B\"], \"default\": [\"A\"], \"A\": [...]},
     {\"group\": \"Label\", \"elements\": [...]}
   ]
 }
@@ -345,6 +970,64 @@ BRANCHING SYNTAX:
 - Option-Specific Children: \"Prod\": [{...prod_fields...}] (Preferred)
 - Checkbox/Reveal: \"reveals\": [{...config...}]
 - Complex Logic: \"when\": \"env == 'Prod' && !use_existing_key\"
+- Expressions: arithmetic (cpu + mem > 80), membership (env in ['Prod', 'Staging']),
+  contains/starts_with/ends_with/len/lower/matches(id, 'regex')/empty(id),
+  dotted keys of object values (survey.row_id == 'Agree', budget.high - budget.low > 100)
+- Hidden fields: with \"version\": 3 (or \"hidden_fields\": \"unset\"), fields inside
+  hidden branches read as null in conditions
+
+TABS: {\"group\": \"Settings\", \"layout\": \"tabs\", \"elements\": [{\"group\": \"General\", ...}]}
+  shows each child group as a tab instead of stacking them
+
+IMAGES: {\"image\": \"/path.png\" or \"data:image/png;base64,...\", \"alt\": \"...\"};
+  option icons: {\"value\": \"A\", \"description\": \"...\", \"icon\": \"/path/a.png\"}
+
+SEARCHABLE: \"searchable\": true on a select or multi for long option lists
+  (type-to-filter, fuzzy match on value and description, arrow keys + Enter)
+
+RANGE: {\"range\": \"Latency (ms)\", \"id\": \"latency\", \"min\": 0, \"max\": 1000, \"step\": 50, \"low\": 100, \"high\": 400}
+  two handles, low <= high; returns {\"low\": 100, \"high\": 400}; latency.low in conditions
+
+RATINGS: {\"rating\": \"Energy\", \"max\": 5, \"low_label\": \"...\", \"high_label\": \"...\"}
+  stars, or \"style\": \"points\" for numbered Likert points; returns the score
+
+MATRIX: {\"matrix\": \"Agree?\", \"id\": \"survey\", \"rows\": [\"Pace was right\", ...], \"columns\": [...]}
+  one choice per row; returns {\"pace_was_right\": \"Agree\"}; survey.pace_was_right in conditions
+
+PATH: {\"path\": \"Label\", \"root\": \"~/project\", \"filters\": [\"*.rs\"], \"mode\": \"file\"|\"dir\"|\"both\", \"multiple\": true (optional)}
+  Returns an absolute path, or an array of them with multiple
+
+COLOR: {\"color\": \"Label\", \"default\": \"#3366ff\", \"palette\": [\"#b22222\", ...], \"alpha\": true (optional)}
+  Returns \"#rrggbb\" (or \"#rrggbbaa\" with alpha)
+
+CONFIRM PHRASE: {\"confirm_phrase\": \"prod-db-main\", \"label\": \"Deletes the database\"}
+  Submit stays disabled until the phrase is typed; \"case_sensitive\": false to relax
+
+SECRETS: {\"secret\": \"API token\", \"to_file\": true (optional)} masked input, never logged;
+  with to_file returns {\"file\": \"/tmp/...\"} instead of the value (delete it after use)
+
+CODE: {\"code\": \"...\", \"language\": \"rust\"}; add \"editable\": true and an \"id\" to return edits
+
+TAGS: {\"tags\": \"Labels\", \"suggestions\": [...], \"max_tags\": 5, \"pattern\": \"[a-z-]+\"}
+  free-form chips returned as a string array
+
+KEY-VALUE: {\"kv\": \"Env\", \"keys\": [\"A\", \"B\"] (optional), \"placeholder\": \"...\"}
+  returns {\"A\": \"...\"}; duplicate keys block submit
+
+LISTS: {\"list\": \"Env vars\", \"min_items\": 1, \"elements\": [{\"input\": \"Name\", \"id\": \"name\"}]}
+  repeatable rows; returns [{\"name\": ...}, ...]; ids and conditions are scoped to each row
+
+TABLES: {\"table\": \"Files\", \"columns\": [\"path\", \"size\"], \"rows\": [[...]], \"selection\": \"multi\"}
+  returns the selected rows' first-column values (or \"key\" column) as an array
+
+DIFF REVIEW: {\"diff\": \"<unified diff>\", \"id\": \"review\"} returns per-file hunk verdicts:
+  {\"path\": [{\"hunk\": 0, \"decision\": \"accept\" | \"reject\" | null, \"comment\": \"...\"}]}
+
+LAYOUT: \"layout\": \"single\" | \"row\" | {\"columns\": N} | \"auto\" on the popup or a group;
+  \"span\": \"full\" on any element to cross all columns
+
+WIZARDS: replace \"elements\" with \"pages\": [{\"title\": \"Step\", \"elements\": [...],
+  \"when\": \"...\", \"required\": [\"id\"]}] for long interviews shown one page at a time
 
 RETURNS: {\"status\": \"completed\", \"<id>\": value, ...}
 - select/multi: selected text
@@ -370,5 +1053,15 @@ mod tests {
         assert_eq!(schema["type"], "object");
         assert!(schema["properties"]["title"].is_object());
         assert!(schema["properties"]["elements"].is_object());
+        assert_eq!(
+            schema["properties"]["pages"]["items"]["properties"]["elements"]["items"],
+            schema["properties"]["elements"]["items"]
+        );
+        for variant in schema["properties"]["elements"]["items"]["oneOf"]
+            .as_array()
+            .unwrap()
+        {
+            assert!(variant["properties"]["span"].is_object());
+        }
     }
 }
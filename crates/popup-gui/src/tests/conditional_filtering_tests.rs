@@ -813,4 +813,147 @@ mod tests {
         assert!(active_ids.contains(&"selection_notice".to_string()));
         assert_eq!(active_ids.len(), 2);
     }
+
+    #[test]
+    fn test_hidden_elements_report_failing_subexpression() {
+        let json = json!({
+            "title": "Debug Visibility",
+            "elements": [
+                {"check": "Enabled", "id": "enabled", "default": true},
+                {"select": "Mode", "id": "mode", "options": ["Basic", "Advanced"], "default": "Basic"},
+                {
+                    "group": "Advanced",
+                    "when": "enabled && mode == 'Advanced'",
+                    "elements": [
+                        {"input": "Nested", "id": "nested", "when": "false"}
+                    ]
+                },
+                {"input": "Always", "id": "always", "when": "enabled"}
+            ]
+        });
+
+        let popup: PopupDefinition = serde_json::from_value(json).unwrap();
+        let state = PopupState::new(&popup);
+
+        let hidden = crate::gui::tests::hidden_elements_for_test(&popup, &state);
+
+        // Only the group is reported; its children are hidden along with it
+        assert_eq!(hidden.len(), 1);
+        let (name, when, reason) = &hidden[0];
+        assert_eq!(name, "Advanced");
+        assert_eq!(when, "enabled && mode == 'Advanced'");
+        assert_eq!(reason, "mode == 'Advanced' is false (mode = \"Basic\")");
+    }
+
+    #[test]
+    fn test_hidden_elements_mask_secret_values() {
+        let json = json!({
+            "title": "Deploy",
+            "elements": [
+                {"secret": "API key", "id": "api_key"},
+                {"input": "Note", "id": "note", "when": "api_key == 'letmein'"},
+                {"input": "Hint", "id": "hint", "when": "len(api_key) > 10"}
+            ]
+        });
+
+        let popup: PopupDefinition = serde_json::from_value(json).unwrap();
+        let mut state = PopupState::new(&popup);
+        *state.get_text_mut("api_key").unwrap() = "hunter2".to_string();
+
+        let hidden = crate::gui::tests::hidden_elements_for_test(&popup, &state);
+        let reasons: Vec<&str> = hidden.iter().map(|(_, _, reason)| reason.as_str()).collect();
+        assert_eq!(
+            reasons,
+            vec![
+                "api_key == 'letmein' is false (api_key = ••••••)",
+                "len(api_key) > 10 is false (len(api_key) = ••••••)",
+            ]
+        );
+        assert!(!reasons.concat().contains("hunter2"));
+    }
+
+    #[test]
+    fn test_tabs_only_report_visible_tabs() {
+        let json = json!({
+            "title": "Settings",
+            "elements": [{
+                "group": "Settings",
+                "layout": "tabs",
+                "elements": [
+                    {"check": "Expert mode", "id": "expert"},
+                    {"group": "General", "elements": [{"input": "Name", "id": "name"}]},
+                    {"group": "Display", "elements": [{"slider": "Zoom", "id": "zoom", "min": 50, "max": 200}]},
+                    {
+                        "group": "Expert",
+                        "when": "expert",
+                        "elements": [{"input": "Flags", "id": "flags"}]
+                    }
+                ]
+            }]
+        });
+
+        let popup: PopupDefinition = serde_json::from_value(json).unwrap();
+        let mut state = PopupState::new(&popup);
+
+        // Unopened tabs still count; the hidden Expert tab doesn't
+        let active_ids = crate::gui::tests::collect_active_elements_for_test(
+            &popup.elements,
+            &state,
+            &popup.elements,
+        );
+        assert_eq!(active_ids, vec!["expert", "name", "zoom"]);
+
+        *state.get_boolean_mut("expert").unwrap() = true;
+        let active_ids = crate::gui::tests::collect_active_elements_for_test(
+            &popup.elements,
+            &state,
+            &popup.elements,
+        );
+        assert_eq!(active_ids, vec!["expert", "name", "zoom", "flags"]);
+    }
+
+    #[test]
+    fn test_collapsed_group_fields_stay_active() {
+        let json = json!({
+            "title": "Deploy",
+            "elements": [
+                {"input": "Service", "id": "service"},
+                {
+                    "group": "Advanced",
+                    "collapsed": true,
+                    "elements": [
+                        {"slider": "Replicas", "id": "replicas", "min": 1, "max": 10, "default": 3},
+                        {"select": "Region", "id": "region", "options": ["us-east", "eu-west"]},
+                        {"check": "Canary", "id": "canary", "default": true},
+                        {"input": "Notes", "id": "notes"}
+                    ]
+                }
+            ]
+        });
+
+        let popup: PopupDefinition = serde_json::from_value(json).unwrap();
+        let mut state = PopupState::new(&popup);
+
+        // Folding a group away doesn't drop its fields from the result
+        let active_ids = crate::gui::tests::collect_active_elements_for_test(
+            &popup.elements,
+            &state,
+            &popup.elements,
+        );
+        assert_eq!(
+            active_ids,
+            vec!["service", "replicas", "region", "canary", "notes"]
+        );
+
+        let summary = crate::gui::tests::answered_summary_for_test(&popup.elements, &state);
+        assert_eq!(summary, "Replicas: 3 · Canary");
+
+        *state.get_text_mut("service").unwrap() = "api".to_string();
+        *state.get_choice_mut("region").unwrap() = Some(1);
+        let summary = crate::gui::tests::answered_summary_for_test(&popup.elements, &state);
+        assert_eq!(
+            summary,
+            "Service: api · Replicas: 3 · Region: eu-west · +1 more"
+        );
+    }
 }
@@ -37,6 +37,15 @@ fn test_parse_example_files() {
                 path
             );
 
+            // Every when clause must parse; a malformed one would silently fail open
+            let condition_errors = popup.condition_errors();
+            assert!(
+                condition_errors.is_empty(),
+                "Invalid when clauses in {:?}: {:#?}",
+                path,
+                condition_errors
+            );
+
             // Create state and verify it initializes correctly
             let state = PopupState::new(&popup);
             let result = PopupResult::from_state(&state);
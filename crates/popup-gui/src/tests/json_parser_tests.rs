@@ -395,3 +395,813 @@ fn test_choice_state_initialization() {
         _ => panic!("Expected Choice(Some(2)) for with_default"),
     }
 }
+
+#[test]
+fn test_layout_and_span() {
+    use popup_common::{Layout, Span};
+
+    let json = r#"{
+        "title": "Layout",
+        "layout": {"columns": 3},
+        "elements": [
+            {"markdown": "Pick carefully", "span": "full"},
+            {"group": "Solo", "layout": "single", "elements": [{"input": "Name", "id": "name"}]},
+            {"group": "Inline", "layout": "row", "elements": []}
+        ]
+    }"#;
+
+    let popup = parse_popup_json(json).unwrap();
+    assert_eq!(popup.layout, Some(Layout::Columns(3)));
+    assert_eq!(popup.elements[0].span(), Some(Span::Full));
+    assert_eq!(popup.elements[1].span(), None);
+    assert!(matches!(
+        &popup.elements[1],
+        Element::Group {
+            layout: Some(Layout::Single),
+            ..
+        }
+    ));
+    assert!(matches!(
+        &popup.elements[2],
+        Element::Group {
+            layout: Some(Layout::Row),
+            ..
+        }
+    ));
+
+    // Unknown layouts and spans are rejected rather than ignored
+    let bad_layout = r#"{"title": "T", "layout": "grid", "elements": []}"#;
+    assert!(parse_popup_json(bad_layout).is_err());
+    let bad_span = r#"{"title": "T", "elements": [{"text": "hi", "span": "half"}]}"#;
+    assert!(parse_popup_json(bad_span).is_err());
+}
+
+#[test]
+fn test_columns_fill_top_to_bottom() {
+    use crate::gui::tests::column_major_for_test;
+
+    assert_eq!(column_major_for_test(5, 2), vec![vec![0, 1, 2], vec![3, 4]]);
+    assert_eq!(column_major_for_test(4, 3), vec![vec![0, 1], vec![2, 3]]);
+    assert_eq!(column_major_for_test(2, 4), vec![vec![0], vec![1]]);
+    assert_eq!(column_major_for_test(3, 1), vec![vec![0, 1, 2]]);
+}
+
+#[test]
+fn test_image_element_and_option_icons() {
+    let json = r#"{
+        "title": "Mockups",
+        "elements": [
+            {"image": "/tmp/mockup.png", "alt": "Current design", "max_width": 320},
+            {"select": "Pick one", "id": "pick", "options": [
+                {"value": "A", "description": "Sidebar", "icon": "data:image/png;base64,iVBORw0KGgo="},
+                "B"
+            ]}
+        ]
+    }"#;
+
+    let popup = parse_popup_json(json).unwrap();
+    match &popup.elements[0] {
+        Element::Image {
+            image,
+            alt,
+            max_width,
+            max_height,
+            ..
+        } => {
+            assert_eq!(image, "/tmp/mockup.png");
+            assert_eq!(alt.as_deref(), Some("Current design"));
+            assert_eq!(*max_width, Some(320.0));
+            assert_eq!(*max_height, None);
+        }
+        _ => panic!("Expected image element"),
+    }
+    assert_eq!(popup.elements[0].label(), "Current design");
+    match &popup.elements[1] {
+        Element::Select { options, .. } => {
+            assert_eq!(
+                options[0].icon(),
+                Some("data:image/png;base64,iVBORw0KGgo=")
+            );
+            assert_eq!(options[1].icon(), None);
+        }
+        _ => panic!("Expected select element"),
+    }
+}
+
+#[test]
+fn test_remote_images_rejected() {
+    let remote_image = r#"{"title": "T", "elements": [{"image": "https://example.com/a.png"}]}"#;
+    let err = parse_popup_json(remote_image).unwrap_err().to_string();
+    assert!(err.contains("remote image"), "unexpected error: {}", err);
+
+    let bad_data = r#"{"title": "T", "elements": [{"image": "data:text/html,<b>hi</b>"}]}"#;
+    assert!(parse_popup_json(bad_data).is_err());
+
+    let remote_icon = r#"{"title": "T", "elements": [{"select": "S", "options": [
+        {"value": "A", "description": "d", "icon": "http://example.com/a.png"}
+    ]}]}"#;
+    assert!(parse_popup_json(remote_icon).is_err());
+
+    // file:// URLs are local
+    let file_url = r#"{"title": "T", "elements": [{"image": "file:///tmp/a.png"}]}"#;
+    assert!(parse_popup_json(file_url).is_ok());
+}
+
+#[test]
+fn test_image_sources_resolve_locally() {
+    use crate::gui::tests::image_loads_for_test;
+
+    assert!(image_loads_for_test("data:image/png;base64,iVBORw0KGgo="));
+    assert!(!image_loads_for_test("data:image/png;base64,%%%"));
+    // Local files only need to exist here; egui decodes them when drawn
+    assert!(image_loads_for_test(concat!(
+        env!("CARGO_MANIFEST_DIR"),
+        "/Cargo.toml"
+    )));
+    assert!(!image_loads_for_test("/definitely/not/here.png"));
+}
+
+#[test]
+fn test_diff_review_element() {
+    use popup_common::{HunkDecision, PopupResult, PopupState};
+
+    let json = r#"{
+        "title": "Review",
+        "elements": [
+            {"diff": "--- a/src/main.rs\n+++ b/src/main.rs\n@@ -1,2 +1,2 @@\n fn main() {\n-    old();\n+    new();\n@@ -9 +9 @@\n-a\n+b\n", "label": "Proposed Change"}
+        ]
+    }"#;
+    let popup = parse_popup_json(json).unwrap();
+    match &popup.elements[0] {
+        Element::Diff { id, label, .. } => {
+            assert_eq!(id, "proposed_change");
+            assert_eq!(label.as_deref(), Some("Proposed Change"));
+        }
+        other => panic!("Expected diff element, got {:?}", other),
+    }
+
+    let mut state = PopupState::new(&popup);
+    state.button_clicked = Some("submit".to_string());
+    {
+        let reviews = state.get_review_mut("proposed_change").unwrap();
+        assert_eq!(reviews.len(), 2);
+        reviews[0].decision = Some(HunkDecision::Accept);
+        reviews[1].decision = Some(HunkDecision::Reject);
+        reviews[1].comment = "keep a".to_string();
+    }
+
+    let active_ids = crate::gui::tests::collect_active_elements_for_test(
+        &popup.elements,
+        &state,
+        &popup.elements,
+    );
+    let values = match PopupResult::from_state_with_active_elements(&state, &popup, &active_ids) {
+        PopupResult::Completed { values, .. } => values,
+        _ => panic!("Expected Completed result"),
+    };
+    assert_eq!(
+        values["proposed_change"],
+        serde_json::json!({
+            "src/main.rs": [
+                {"hunk": 0, "decision": "accept"},
+                {"hunk": 1, "decision": "reject", "comment": "keep a"}
+            ]
+        })
+    );
+
+    let missing_id = r#"{"title": "T", "elements": [{"diff": "@@ -1 +1 @@\n-a\n+b"}]}"#;
+    assert!(parse_popup_json(missing_id).is_err());
+}
+
+#[test]
+fn test_code_element() {
+    use popup_common::{PopupResult, PopupState};
+
+    let json = r#"{
+        "title": "Edit",
+        "elements": [
+            {"code": "print('hi')", "language": "python"},
+            {"code": "fn main() {}", "id": "snippet", "language": "rust", "editable": true}
+        ]
+    }"#;
+    let popup = parse_popup_json(json).unwrap();
+    match &popup.elements[0] {
+        Element::Code {
+            language,
+            editable,
+            line_numbers,
+            ..
+        } => {
+            assert_eq!(language.as_deref(), Some("python"));
+            assert!(!editable);
+            assert!(line_numbers);
+        }
+        other => panic!("Expected code element, got {:?}", other),
+    }
+
+    let mut state = PopupState::new(&popup);
+    state.button_clicked = Some("submit".to_string());
+    *state.get_text_mut("snippet").unwrap() = "fn main() { run(); }".to_string();
+
+    let active_ids = crate::gui::tests::collect_active_elements_for_test(
+        &popup.elements,
+        &state,
+        &popup.elements,
+    );
+    let values = match PopupResult::from_state_with_active_elements(&state, &popup, &active_ids) {
+        PopupResult::Completed { values, .. } => values,
+        _ => panic!("Expected Completed result"),
+    };
+    assert_eq!(values.len(), 1);
+    assert_eq!(values["snippet"], "fn main() { run(); }");
+
+    let no_id = r#"{"title": "T", "elements": [{"code": "x = 1", "editable": true}]}"#;
+    assert!(parse_popup_json(no_id).is_err());
+}
+
+#[test]
+fn test_table_element() {
+    use popup_common::{PopupResult, PopupState, TableSelection};
+
+    let json = r#"{
+        "title": "Failures",
+        "elements": [
+            {
+                "table": "Failing Tests",
+                "columns": ["name", "secs"],
+                "rows": [["test_a", 1.5], {"name": "test_b", "secs": 30}],
+                "selection": "multi"
+            },
+            {"text": "Rerun them all?", "id": "note", "when": "count(failing_tests) > 1"}
+        ]
+    }"#;
+    let popup = parse_popup_json(json).unwrap();
+    match &popup.elements[0] {
+        Element::Table {
+            id,
+            rows,
+            selection,
+            ..
+        } => {
+            assert_eq!(id, "failing_tests");
+            assert_eq!(rows[1], vec!["test_b", "30"]);
+            assert_eq!(*selection, TableSelection::Multi);
+        }
+        other => panic!("Expected table element, got {:?}", other),
+    }
+
+    let mut state = PopupState::new(&popup);
+    state.button_clicked = Some("submit".to_string());
+    let active = |state: &PopupState| {
+        crate::gui::tests::collect_active_elements_for_test(&popup.elements, state, &popup.elements)
+    };
+    assert!(!active(&state).contains(&"note".to_string()));
+
+    *state.get_rows_mut("failing_tests").unwrap() =
+        vec!["test_a".to_string(), "test_b".to_string()];
+    let active_ids = active(&state);
+    assert!(active_ids.contains(&"note".to_string()));
+
+    let values = match PopupResult::from_state_with_active_elements(&state, &popup, &active_ids) {
+        PopupResult::Completed { values, .. } => values,
+        _ => panic!("Expected Completed result"),
+    };
+    assert_eq!(
+        values["failing_tests"],
+        serde_json::json!(["test_a", "test_b"])
+    );
+
+    // Row keys must be unique once rows can be selected
+    let duplicate = r#"{"title": "T", "elements": [{"table": "T", "columns": ["a"],
+        "rows": [["x"], ["x"]], "selection": "single"}]}"#;
+    assert!(parse_popup_json(duplicate).is_err());
+
+    let bad_key = r#"{"title": "T", "elements": [{"table": "T", "columns": ["a"], "key": "b"}]}"#;
+    assert!(parse_popup_json(bad_key).is_err());
+}
+
+#[test]
+fn test_tags_element() {
+    use popup_common::{validate_elements, PopupState};
+
+    let json = r#"{
+        "title": "Labels",
+        "elements": [
+            {"tags": "Labels", "suggestions": "bug, docs, perf", "max_tags": 2, "pattern": "[a-z]+"},
+            {"text": "Needs a reproducer", "id": "repro", "when": "selected(labels, 'bug') && count(labels) == 2"}
+        ]
+    }"#;
+    let popup = parse_popup_json(json).unwrap();
+    match &popup.elements[0] {
+        Element::Tags {
+            id, suggestions, ..
+        } => {
+            assert_eq!(id, "labels");
+            assert_eq!(suggestions, &vec!["bug", "docs", "perf"]);
+        }
+        other => panic!("Expected tags element, got {:?}", other),
+    }
+
+    let mut state = PopupState::new(&popup);
+    let active = |state: &PopupState| {
+        crate::gui::tests::collect_active_elements_for_test(&popup.elements, state, &popup.elements)
+    };
+    *state.get_tags_mut("labels").unwrap() = vec!["bug".to_string(), "flaky".to_string()];
+    let active_ids = active(&state);
+    assert!(active_ids.contains(&"repro".to_string()));
+    assert!(validate_elements(&popup.elements, &state, &active_ids).is_empty());
+
+    state
+        .get_tags_mut("labels")
+        .unwrap()
+        .push("Perf".to_string());
+    let errors = validate_elements(&popup.elements, &state, &active(&state));
+    assert_eq!(errors.len(), 1);
+    assert_eq!(errors[0].message, "At most 2 tags allowed");
+
+    state.get_tags_mut("labels").unwrap().remove(0);
+    let errors = validate_elements(&popup.elements, &state, &active(&state));
+    assert!(errors[0].message.contains("`Perf`"), "{:?}", errors);
+
+    let bad_pattern = r#"{"title": "T", "elements": [{"tags": "T", "pattern": "[a-"}]}"#;
+    assert!(parse_popup_json(bad_pattern).is_err());
+}
+
+#[test]
+fn test_list_rows_keep_their_own_tag_inputs() {
+    use crate::gui::tests::tag_inputs_after_frame_for_test;
+    use popup_common::PopupState;
+
+    let json = r#"{
+        "title": "Releases",
+        "elements": [
+            {"list": "Releases", "min_items": 2, "elements": [{"tags": "Labels"}]}
+        ]
+    }"#;
+    let popup = parse_popup_json(json).unwrap();
+    assert_eq!(PopupState::new(&popup).get_list_mut("releases").unwrap().len(), 2);
+
+    // Text half-typed into the first row's input stays out of the second's
+    let inputs = tag_inputs_after_frame_for_test(popup, &[("0.row0.0", "urgen")]);
+    assert_eq!(
+        inputs,
+        vec![
+            ("0.row0.0".to_string(), "urgen".to_string()),
+            ("0.row1.0".to_string(), String::new()),
+        ]
+    );
+}
+
+#[test]
+fn test_searchable_select() {
+    use crate::gui::tests::option_matches_for_test;
+
+    let json = r#"{
+        "title": "Code host",
+        "elements": [
+            {"select": "Zone", "searchable": true, "options": [
+                {"value": "Hugh", "description": "Not a code host"},
+                {"value": "GitHub", "description": "Code hosting"},
+                {"value": "Gitea", "description": "Self-hosted git"}
+            ]},
+            {"multi": "Regions", "options": "us-east, eu-west"}
+        ]
+    }"#;
+    let popup = parse_popup_json(json).unwrap();
+    let options = match &popup.elements[0] {
+        Element::Select {
+            options,
+            searchable,
+            option_children,
+            ..
+        } => {
+            assert!(searchable);
+            assert!(option_children.is_empty());
+            options
+        }
+        other => panic!("Expected select element, got {:?}", other),
+    };
+    assert!(matches!(
+        &popup.elements[1],
+        Element::Multi {
+            searchable: false,
+            ..
+        }
+    ));
+
+    // Empty query keeps the given order
+    assert_eq!(option_matches_for_test(options, " ")[..3], [0, 1, 2]);
+    // Word starts and runs rank higher
+    assert_eq!(option_matches_for_test(options, "gh")[..2], [1, 0]);
+    assert_eq!(option_matches_for_test(options, "GIT")[..2], [1, 2]);
+    // Descriptions are searched too
+    assert_eq!(option_matches_for_test(options, "self"), vec![2]);
+    assert!(option_matches_for_test(options, "zzz").is_empty());
+
+    let serialized = serde_json::to_value(&popup.elements[0]).unwrap();
+    assert_eq!(serialized["searchable"], true);
+    let serialized = serde_json::to_value(&popup.elements[1]).unwrap();
+    assert!(serialized.get("searchable").is_none());
+}
+
+#[test]
+fn test_secret_element() {
+    use popup_common::{PopupResult, PopupState};
+
+    let json = r#"{
+        "title": "Credentials",
+        "elements": [
+            {"secret": "API token", "to_file": true},
+            {"secret": "Passphrase", "placeholder": "optional"}
+        ]
+    }"#;
+    let popup = parse_popup_json(json).unwrap();
+    match &popup.elements[0] {
+        Element::Secret { id, to_file, .. } => {
+            assert_eq!(id, "api_token");
+            assert!(to_file);
+        }
+        other => panic!("Expected secret element, got {:?}", other),
+    }
+
+    let mut state = PopupState::new(&popup);
+    *state.get_text_mut("api_token").unwrap() = "sk-live-123".to_string();
+    *state.get_text_mut("passphrase").unwrap() = "hunter2".to_string();
+    state.button_clicked = Some("submit".to_string());
+
+    // Summaries show that a secret was given, never what it was
+    let summary = crate::gui::tests::answered_summary_for_test(&popup.elements, &state);
+    assert!(
+        !summary.contains("sk-live") && !summary.contains("hunter2"),
+        "{}",
+        summary
+    );
+
+    let mut result = PopupResult::from_state_with_context(&state, &popup);
+    result.move_secrets_to_files(&popup);
+    let PopupResult::Completed { values, .. } = result else {
+        panic!("Expected completed result");
+    };
+    assert_eq!(values["passphrase"], "hunter2");
+
+    let path = values["api_token"]["file"].as_str().unwrap();
+    assert_eq!(std::fs::read_to_string(path).unwrap(), "sk-live-123");
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        let mode = std::fs::metadata(path).unwrap().permissions().mode();
+        assert_eq!(mode & 0o777, 0o600);
+    }
+    std::fs::remove_file(path).unwrap();
+}
+
+#[test]
+fn test_rating_and_matrix_elements() {
+    use popup_common::{PopupResult, PopupState};
+
+    let json = r#"{
+        "title": "Retro",
+        "elements": [
+            {"rating": "Overall", "max": 5, "low_label": "Rough", "high_label": "Great"},
+            {"matrix": "How far do you agree?", "id": "survey",
+             "rows": ["Pace was right", {"id": "scope", "label": "Scope was clear"}],
+             "columns": "Disagree, Neutral, Agree"},
+            {"input": "What made scope unclear?", "id": "why", "when": "survey.scope == 'Disagree'"}
+        ]
+    }"#;
+    let popup = parse_popup_json(json).unwrap();
+    match &popup.elements[1] {
+        Element::Matrix { rows, columns, .. } => {
+            let ids: Vec<&str> = rows.iter().map(|r| r.id.as_str()).collect();
+            assert_eq!(ids, vec!["pace_was_right", "scope"]);
+            assert_eq!(columns.len(), 3);
+        }
+        other => panic!("Expected matrix element, got {:?}", other),
+    }
+
+    let mut state = PopupState::new(&popup);
+    let active = |state: &PopupState| {
+        crate::gui::tests::collect_active_elements_for_test(&popup.elements, state, &popup.elements)
+    };
+    assert!(!active(&state).contains(&"why".to_string()));
+
+    *state.get_rating_mut("overall").unwrap() = Some(4);
+    state.get_matrix_mut("survey").unwrap()[1] = Some(0);
+    assert!(active(&state).contains(&"why".to_string()));
+
+    state.button_clicked = Some("submit".to_string());
+    let PopupResult::Completed { values, .. } =
+        PopupResult::from_state_with_context(&state, &popup)
+    else {
+        panic!("Expected completed result");
+    };
+    assert_eq!(values["overall"], 4);
+    // Unanswered rows are left out
+    assert_eq!(values["survey"], serde_json::json!({"scope": "Disagree"}));
+
+    let bad = r#"{"title": "T", "elements": [{"rating": "R", "max": 5, "default": 6}]}"#;
+    assert!(parse_popup_json(bad).is_err());
+    let bad =
+        r#"{"title": "T", "elements": [{"matrix": "M", "rows": ["A", "a"], "columns": "X, Y"}]}"#;
+    assert!(parse_popup_json(bad).is_err());
+}
+
+#[test]
+fn test_confirm_phrase_element() {
+    use popup_common::{validate_elements, PopupResult, PopupState};
+
+    let json = r#"{
+        "title": "Drop database",
+        "elements": [
+            {"confirm_phrase": "prod-db-main", "label": "This deletes the production database"},
+            {"confirm_phrase": "Yes", "id": "loose", "case_sensitive": false}
+        ]
+    }"#;
+    let popup = parse_popup_json(json).unwrap();
+    match &popup.elements[0] {
+        Element::ConfirmPhrase {
+            id, case_sensitive, ..
+        } => {
+            assert_eq!(id, "confirm_prod_db_main");
+            assert!(*case_sensitive);
+        }
+        other => panic!("Expected confirm_phrase element, got {:?}", other),
+    }
+
+    let mut state = PopupState::new(&popup);
+    let ids = vec!["confirm_prod_db_main".to_string(), "loose".to_string()];
+    let errors = validate_elements(&popup.elements, &state, &ids);
+    assert_eq!(errors.len(), 2);
+    assert_eq!(errors[0].message, "Type `prod-db-main` to confirm");
+
+    *state.get_text_mut("confirm_prod_db_main").unwrap() = "PROD-DB-MAIN".to_string();
+    *state.get_text_mut("loose").unwrap() = " yes ".to_string();
+    let errors = validate_elements(&popup.elements, &state, &ids);
+    assert_eq!(errors.len(), 1);
+    assert_eq!(errors[0].id, "confirm_prod_db_main");
+
+    *state.get_text_mut("confirm_prod_db_main").unwrap() = "prod-db-main".to_string();
+    assert!(validate_elements(&popup.elements, &state, &ids).is_empty());
+
+    state.button_clicked = Some("submit".to_string());
+    let PopupResult::Completed { values, .. } =
+        PopupResult::from_state_with_context(&state, &popup)
+    else {
+        panic!("Expected completed result");
+    };
+    assert_eq!(values["confirm_prod_db_main"], true);
+    assert_eq!(values["loose"], true);
+
+    let bad = r#"{"title": "T", "elements": [{"confirm_phrase": "  "}]}"#;
+    assert!(parse_popup_json(bad).is_err());
+}
+
+#[test]
+fn test_color_element() {
+    use popup_common::{validate_elements, PopupResult, PopupState};
+
+    let json = r##"{
+        "title": "Theme",
+        "elements": [
+            {"color": "Accent", "default": "#36F", "palette": ["#B22222", "daa520"]},
+            {"color": "Overlay", "alpha": true, "default": "#00000080"},
+            {"color": "Border"}
+        ]
+    }"##;
+    let popup = parse_popup_json(json).unwrap();
+    match &popup.elements[0] {
+        Element::Color {
+            id,
+            default,
+            palette,
+            alpha,
+            ..
+        } => {
+            assert_eq!(id, "accent");
+            assert!(!alpha);
+            // Defaults and presets come back normalized
+            assert_eq!(default.as_deref(), Some("#3366ff"));
+            assert_eq!(palette, &vec!["#b22222".to_string(), "#daa520".to_string()]);
+        }
+        other => panic!("Expected color element, got {:?}", other),
+    }
+
+    let mut state = PopupState::new(&popup);
+    assert_eq!(state.get_text("overlay").unwrap(), "#00000080");
+    assert!(!state.values["border"].is_answered());
+
+    let ids = vec![
+        "accent".to_string(),
+        "overlay".to_string(),
+        "border".to_string(),
+    ];
+    *state.get_text_mut("border").unwrap() = "teal".to_string();
+    let errors = validate_elements(&popup.elements, &state, &ids);
+    assert_eq!(errors.len(), 1);
+    assert_eq!(errors[0].message, "`teal` isn't a hex color");
+    state.get_text_mut("border").unwrap().clear();
+
+    state.button_clicked = Some("submit".to_string());
+    let PopupResult::Completed { values, .. } =
+        PopupResult::from_state_with_context(&state, &popup)
+    else {
+        panic!("Expected completed result");
+    };
+    assert_eq!(values["accent"], "#3366ff");
+    assert_eq!(values["overlay"], "#00000080");
+    assert!(!values.contains_key("border"));
+
+    let bad = r#"{"title": "T", "elements": [{"color": "C", "default": "red"}]}"#;
+    assert!(parse_popup_json(bad).is_err());
+    let bad = r##"{"title": "T", "elements": [{"color": "C", "palette": ["#12345"]}]}"##;
+    assert!(parse_popup_json(bad).is_err());
+}
+
+#[test]
+fn test_path_element() {
+    use popup_common::{validate_elements, PathMode, PopupResult, PopupState};
+
+    let root = concat!(env!("CARGO_MANIFEST_DIR"), "/src");
+    let json = format!(
+        r#"{{
+        "title": "Pick files",
+        "elements": [
+            {{"path": "Entry point", "root": "{root}", "filters": "*.rs"}},
+            {{"path": "Sources", "root": "{root}", "mode": "both", "multiple": true}}
+        ]
+    }}"#
+    );
+    let popup = parse_popup_json(&json).unwrap();
+    match &popup.elements[0] {
+        Element::Path {
+            id,
+            filters,
+            mode,
+            multiple,
+            ..
+        } => {
+            assert_eq!(id, "entry_point");
+            assert_eq!(filters, &vec!["*.rs".to_string()]);
+            assert_eq!(*mode, PathMode::File);
+            assert!(!multiple);
+        }
+        other => panic!("Expected path element, got {:?}", other),
+    }
+
+    let dir = std::path::Path::new(root).canonicalize().unwrap();
+    let lib = dir.join("lib.rs").display().to_string();
+    let gui = dir.join("gui").display().to_string();
+    let backup = dir.join("popup.pest.backup").display().to_string();
+
+    let mut state = PopupState::new(&popup);
+    let ids = vec!["entry_point".to_string(), "sources".to_string()];
+    *state.get_paths_mut("entry_point").unwrap() = vec![backup];
+    *state.get_paths_mut("sources").unwrap() =
+        vec![gui.clone(), dir.join("gone.rs").display().to_string()];
+    let errors = validate_elements(&popup.elements, &state, &ids);
+    assert_eq!(errors.len(), 2);
+    assert_eq!(errors[0].message, "`popup.pest.backup` doesn't match *.rs");
+    assert!(errors[1].message.ends_with("does not exist"));
+
+    // Paths must stay under the root
+    let outside = dir.parent().unwrap().join("Cargo.toml").display().to_string();
+    state.get_paths_mut("sources").unwrap()[1] = outside;
+    let errors = validate_elements(&popup.elements, &state, &ids);
+    assert!(errors[1].message.contains("is outside"));
+
+    *state.get_paths_mut("entry_point").unwrap() = vec![lib.clone()];
+    state.get_paths_mut("sources").unwrap()[1] = lib.clone();
+    assert!(validate_elements(&popup.elements, &state, &ids).is_empty());
+
+    state.button_clicked = Some("submit".to_string());
+    let PopupResult::Completed { values, .. } =
+        PopupResult::from_state_with_context(&state, &popup)
+    else {
+        panic!("Expected completed result");
+    };
+    assert_eq!(values["entry_point"], lib.as_str());
+    assert_eq!(values["sources"], serde_json::json!([gui, lib]));
+}
+
+#[test]
+fn test_range_element() {
+    use popup_common::{validate_elements, PopupResult, PopupState};
+
+    let json = r#"{
+        "title": "Budget",
+        "elements": [
+            {"range": "Latency (ms)", "id": "latency", "min": 0, "max": 1000, "step": 50, "low": 100, "high": 400},
+            {"range": "Ratio", "min": 0, "max": 1, "step": 0.1},
+            {"input": "Why so wide?", "id": "why", "when": "latency.high - latency.low > 500"}
+        ]
+    }"#;
+    let popup = parse_popup_json(json).unwrap();
+    match &popup.elements[1] {
+        Element::Range {
+            id,
+            step,
+            low,
+            high,
+            ..
+        } => {
+            assert_eq!(id, "ratio");
+            assert_eq!(*step, 0.1);
+            assert_eq!((*low, *high), (None, None));
+        }
+        other => panic!("Expected range element, got {:?}", other),
+    }
+
+    let mut state = PopupState::new(&popup);
+    let active = |state: &PopupState| {
+        crate::gui::tests::collect_active_elements_for_test(&popup.elements, state, &popup.elements)
+    };
+    assert!(!active(&state).contains(&"why".to_string()));
+
+    *state.get_range_mut("latency").unwrap().1 = 800.0;
+    assert!(active(&state).contains(&"why".to_string()));
+
+    // A crossed range never gets through
+    *state.get_range_mut("latency").unwrap().0 = 900.0;
+    let errors = validate_elements(&popup.elements, &state, &active(&state));
+    assert_eq!(errors.len(), 1);
+    assert_eq!(errors[0].id, "latency");
+    *state.get_range_mut("latency").unwrap().0 = 250.0;
+    *state.get_range_mut("ratio").unwrap().0 = 0.3;
+
+    state.button_clicked = Some("submit".to_string());
+    let PopupResult::Completed { values, .. } =
+        PopupResult::from_state_with_context(&state, &popup)
+    else {
+        panic!("Expected completed result");
+    };
+    assert_eq!(
+        values["latency"],
+        serde_json::json!({"low": 250, "high": 800})
+    );
+    assert_eq!(values["ratio"], serde_json::json!({"low": 0.3, "high": 1}));
+
+    for bad in [
+        r#"{"range": "R", "min": 0, "max": 10, "low": 8, "high": 2}"#,
+        r#"{"range": "R", "min": 0, "max": 10, "high": 12}"#,
+        r#"{"range": "R", "min": 5, "max": 5}"#,
+        r#"{"range": "R", "min": 0, "max": 10, "step": 0}"#,
+    ] {
+        let json = format!(r#"{{"title": "T", "elements": [{}]}}"#, bad);
+        assert!(
+            parse_popup_json(&json).is_err(),
+            "{} should be rejected",
+            bad
+        );
+    }
+}
+
+#[test]
+fn test_previous_result_feeds_back_as_initial_values() {
+    use crate::transform::inject_other_options;
+    use popup_common::{ElementValue, PopupResult, PopupState};
+
+    let json = r#"{
+        "title": "Deploy",
+        "elements": [
+            {"input": "Service"},
+            {"select": "Env", "options": ["Dev", "Prod"]},
+            {"multi": "Regions", "options": ["us", "eu"]},
+            {"secret": "Token", "to_file": true},
+            {"confirm_phrase": "ship-it", "id": "confirm"}
+        ]
+    }"#;
+    let popup = inject_other_options(parse_popup_json(json).unwrap());
+    let mut state = PopupState::new(&popup);
+    *state.get_text_mut("service").unwrap() = "api".to_string();
+    *state.get_choice_mut("env").unwrap() = Some(2);
+    *state.get_text_mut("env_other_text").unwrap() = "QA".to_string();
+    *state.get_multichoice_mut("regions").unwrap() = vec![true, false, true];
+    *state.get_text_mut("regions_other_text").unwrap() = "mars".to_string();
+    *state.get_text_mut("token").unwrap() = "hunter2".to_string();
+    *state.get_text_mut("confirm").unwrap() = "ship-it".to_string();
+    state.button_clicked = Some("submit".to_string());
+
+    let active = state.active_element_ids(&popup.elements, &popup.condition_values(&state));
+    let mut result = PopupResult::from_state_with_active_elements(&state, &popup, &active);
+    result.move_secrets_to_files(&popup);
+    let result = serde_json::to_value(&result).unwrap();
+    std::fs::remove_file(result["token"]["file"].as_str().unwrap()).unwrap();
+
+    // The whole result, status and button included, goes back in as is
+    let mut again = popup.clone();
+    again.initial_values = result.as_object().unwrap().clone().into_iter().collect();
+    assert_eq!(again.initial_value_errors(), Vec::<String>::new());
+
+    let restored = PopupState::new(&again);
+    for id in ["service", "env", "env_other_text", "regions", "regions_other_text"] {
+        assert_eq!(restored.values[id], state.values[id], "{}", id);
+    }
+    // Secrets and confirmations are answered again
+    assert_eq!(restored.values["token"], ElementValue::Text(String::new()));
+    assert_eq!(restored.values["confirm"], ElementValue::Text(String::new()));
+
+    // Before the Other options exist, the Other picks have nothing to match
+    let mut untransformed = parse_popup_json(json).unwrap();
+    untransformed.initial_values = again.initial_values.clone();
+    assert!(!untransformed.initial_value_errors().is_empty());
+}
@@ -4,6 +4,9 @@ use std::collections::HashMap;
 /// Recursively inject "Other" options into all Multi and Select elements
 pub fn inject_other_options(mut def: PopupDefinition) -> PopupDefinition {
     inject_other_in_elements(&mut def.elements);
+    for page in &mut def.pages {
+        inject_other_in_elements(&mut page.elements);
+    }
     def
 }
 
@@ -48,7 +51,7 @@ fn inject_other_in_element(element: &mut Element) {
         Element::Check { reveals, .. } => {
             inject_other_in_elements(reveals);
         }
-        Element::Group { elements, .. } => {
+        Element::Group { elements, .. } | Element::List { elements, .. } => {
             inject_other_in_elements(elements);
         }
         _ => {} // No children to recurse into
@@ -80,6 +83,8 @@ fn inject_other_option(
             id: text_input_id,
             placeholder: None,
             rows: None,
+            default: None,
+            span: None,
             when: None,
         };
 
@@ -103,10 +108,14 @@ mod tests {
                     OptionValue::Simple("A".to_string()),
                     OptionValue::Simple("B".to_string()),
                 ],
+                default: Vec::new(),
                 option_children: HashMap::new(),
                 reveals: vec![],
+                searchable: false,
+                span: None,
                 when: None,
             }],
+            ..Default::default()
         };
 
         let transformed = inject_other_options(def);
@@ -150,8 +159,11 @@ mod tests {
                 default: None,
                 option_children: HashMap::new(),
                 reveals: vec![],
+                searchable: false,
+                span: None,
                 when: None,
             }],
+            ..Default::default()
         };
 
         let transformed = inject_other_options(def);
@@ -194,8 +206,11 @@ mod tests {
                 default: None,
                 option_children: HashMap::new(),
                 reveals: vec![],
+                searchable: false,
+                span: None,
                 when: None,
             }],
+            ..Default::default()
         };
 
         let transformed = inject_other_options(def);
@@ -221,13 +236,17 @@ mod tests {
                     OptionValue::WithDescription {
                         value: "Other".to_string(),
                         description: "Custom option".to_string(),
+                        icon: None,
                     },
                 ],
                 default: None,
                 option_children: HashMap::new(),
                 reveals: vec![],
+                searchable: false,
+                span: None,
                 when: None,
             }],
+            ..Default::default()
         };
 
         let transformed = inject_other_options(def);
@@ -253,12 +272,17 @@ mod tests {
                     multi: "Advanced features".to_string(),
                     id: "advanced_features".to_string(),
                     options: vec![OptionValue::Simple("Feature1".to_string())],
+                    default: Vec::new(),
                     option_children: HashMap::new(),
                     reveals: vec![],
+                    searchable: false,
+                    span: None,
                     when: None,
                 }],
+                span: None,
                 when: None,
             }],
+            ..Default::default()
         };
 
         let transformed = inject_other_options(def);
@@ -289,6 +313,8 @@ mod tests {
                 default: None,
                 option_children: HashMap::new(),
                 reveals: vec![],
+                searchable: false,
+                span: None,
                 when: None,
             }],
         );
@@ -305,8 +331,11 @@ mod tests {
                 default: None,
                 option_children,
                 reveals: vec![],
+                searchable: false,
+                span: None,
                 when: None,
             }],
+            ..Default::default()
         };
 
         let transformed = inject_other_options(def);
@@ -346,12 +375,20 @@ mod tests {
                     multi: "Options".to_string(),
                     id: "options".to_string(),
                     options: vec![OptionValue::Simple("Opt1".to_string())],
+                    default: Vec::new(),
                     option_children: HashMap::new(),
                     reveals: vec![],
+                    searchable: false,
+                    span: None,
                     when: None,
                 }],
+                layout: None,
+                collapsed: false,
+                summary: None,
+                span: None,
                 when: None,
             }],
+            ..Default::default()
         };
 
         let transformed = inject_other_options(def);
@@ -380,8 +417,11 @@ mod tests {
                 multi: "Advanced features".to_string(),
                 id: "advanced_features".to_string(),
                 options: vec![OptionValue::Simple("Feature1".to_string())],
+                default: Vec::new(),
                 option_children: HashMap::new(),
                 reveals: vec![],
+                searchable: false,
+                span: None,
                 when: None,
             }],
         );
@@ -405,12 +445,20 @@ mod tests {
                         default: None,
                         option_children: select_option_children,
                         reveals: vec![],
+                        searchable: false,
+                        span: None,
                         when: None,
                     }],
+                    span: None,
                     when: None,
                 }],
+                layout: None,
+                collapsed: false,
+                summary: None,
+                span: None,
                 when: None,
             }],
+            ..Default::default()
         };
 
         let transformed = inject_other_options(def);
@@ -445,4 +493,37 @@ mod tests {
             _ => panic!("Expected Group element"),
         }
     }
+
+    #[test]
+    fn test_inject_other_into_pages() {
+        let def: PopupDefinition = serde_json::from_value(serde_json::json!({
+            "title": "Wizard",
+            "pages": [
+                {"title": "One", "elements": [{"select": "Env", "id": "env", "options": ["Dev", "Prod"]}]},
+                {"title": "Two", "elements": [{"multi": "Tags", "id": "tags", "options": ["a"]}]}
+            ]
+        }))
+        .unwrap();
+
+        let transformed = inject_other_options(def);
+
+        for page in &transformed.pages {
+            match &page.elements[0] {
+                Element::Select {
+                    options,
+                    option_children,
+                    ..
+                }
+                | Element::Multi {
+                    options,
+                    option_children,
+                    ..
+                } => {
+                    assert_eq!(options.last().unwrap().value(), "Other (please specify)");
+                    assert!(option_children.contains_key("Other (please specify)"));
+                }
+                _ => panic!("Expected Select or Multi on page {}", page.title),
+            }
+        }
+    }
 }